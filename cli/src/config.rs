@@ -0,0 +1,59 @@
+use candid::Principal;
+use dab_sdk::{NFT_REGISTRY_ID, TOKEN_REGISTRY_ID};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+const MAINNET_URL: &str = "https://ic0.app";
+
+// Read from `~/.config/dab/config.toml` unless `--config` is given.
+// Every field is optional and defaults to the mainnet deployment.
+//
+// url = "https://ic0.app"
+// identity = "/home/me/.config/dfx/identity/default/identity.pem"
+// nft_registry = "ctqxp-yyaaa-aaaah-abbda-cai"
+// token_registry = "qwt65-nyaaa-aaaah-qcl4q-cai"
+#[derive(Deserialize, Default)]
+pub struct Config {
+    pub url: Option<String>,
+    pub identity: Option<PathBuf>,
+    pub nft_registry: Option<Principal>,
+    pub token_registry: Option<Principal>,
+}
+
+impl Config {
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Config::default()),
+            },
+        };
+
+        let text = std::fs::read_to_string(&path)
+            .map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+        toml::from_str(&text).map_err(|err| format!("invalid config {}: {}", path.display(), err))
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_deref().unwrap_or(MAINNET_URL)
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        self.url() == MAINNET_URL
+    }
+
+    pub fn nft_registry(&self) -> Principal {
+        self.nft_registry
+            .unwrap_or_else(|| Principal::from_text(NFT_REGISTRY_ID).unwrap())
+    }
+
+    pub fn token_registry(&self) -> Principal {
+        self.token_registry
+            .unwrap_or_else(|| Principal::from_text(TOKEN_REGISTRY_ID).unwrap())
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("dab").join("config.toml"))
+}
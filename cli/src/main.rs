@@ -0,0 +1,232 @@
+//! `dab`, the command line client of the DAB registries.
+//!
+//! ```sh
+//! dab token add --file token.json
+//! dab nft list
+//! dab pending list
+//! dab pending approve <principal>
+//! ```
+
+mod config;
+
+use candid::Principal;
+use clap::{Parser, Subcommand};
+use dab_sdk::*;
+use ic_agent::agent::http_transport::ReqwestHttpReplicaV2Transport;
+use ic_agent::identity::{AnonymousIdentity, BasicIdentity, Secp256k1Identity};
+use ic_agent::{Agent, Identity};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+
+use config::Config;
+
+#[derive(Parser)]
+#[clap(name = "dab", about = "Administer the DAB registries")]
+struct Cli {
+    /// Config file, defaults to ~/.config/dab/config.toml
+    #[clap(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// PEM file of the identity to sign calls with, overrides the config file
+    #[clap(long, global = true)]
+    identity: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage the NFT registry
+    #[clap(subcommand)]
+    Nft(EntryCommand),
+    /// Manage the token registry
+    #[clap(subcommand)]
+    Token(EntryCommand),
+    /// Review submissions waiting for approval in either registry
+    #[clap(subcommand)]
+    Pending(PendingCommand),
+}
+
+#[derive(Subcommand)]
+enum EntryCommand {
+    List,
+    Get {
+        principal_id: Principal,
+    },
+    /// Adds or updates the entry described by a JSON file
+    Add {
+        #[clap(long)]
+        file: PathBuf,
+        #[clap(long)]
+        trusted_source: Option<Principal>,
+    },
+    Remove {
+        principal_id: Principal,
+        #[clap(long)]
+        trusted_source: Option<Principal>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PendingCommand {
+    List,
+    Approve { principal_id: Principal },
+    Reject { principal_id: Principal },
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(err) = run(Cli::parse()).await {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), String> {
+    let config = Config::load(cli.config.as_deref())?;
+    let agent = agent(
+        &config,
+        cli.identity.as_deref().or(config.identity.as_deref()),
+    )
+    .await?;
+    let nft = NftRegistry::with_agent(agent.clone(), config.nft_registry());
+    let tokens = TokenRegistry::with_agent(agent, config.token_registry());
+
+    match cli.command {
+        Command::Nft(command) => match command {
+            EntryCommand::List => print(&nft.get_all().await.map_err(call_error)?),
+            EntryCommand::Get { principal_id } => {
+                print(&nft.get(principal_id).await.map_err(call_error)?)
+            }
+            EntryCommand::Add {
+                file,
+                trusted_source,
+            } => {
+                let input: AddNftInput = read_json(&file)?;
+                response(nft.add(trusted_source, input).await)
+            }
+            EntryCommand::Remove {
+                principal_id,
+                trusted_source,
+            } => response(nft.remove(trusted_source, principal_id).await),
+        },
+        Command::Token(command) => match command {
+            EntryCommand::List => print(&tokens.get_all().await.map_err(call_error)?),
+            EntryCommand::Get { principal_id } => {
+                print(&tokens.get(principal_id).await.map_err(call_error)?)
+            }
+            EntryCommand::Add {
+                file,
+                trusted_source,
+            } => {
+                let input: AddTokenInput = read_json(&file)?;
+                response(tokens.add(trusted_source, input).await)
+            }
+            EntryCommand::Remove {
+                principal_id,
+                trusted_source,
+            } => response(tokens.remove(trusted_source, principal_id).await),
+        },
+        Command::Pending(command) => {
+            let nft_submissions = nft.get_submissions().await.map_err(call_error)?;
+            let token_submissions = tokens.get_submissions().await.map_err(call_error)?;
+            let in_nft = |principal_id: &Principal| {
+                nft_submissions
+                    .iter()
+                    .any(|submission| submission.entry.principal_id == *principal_id)
+            };
+            let in_tokens = |principal_id: &Principal| {
+                token_submissions
+                    .iter()
+                    .any(|submission| submission.entry.principal_id == *principal_id)
+            };
+
+            // A principal is only submitted to one registry, the command goes to the one holding it
+            match command {
+                PendingCommand::List => print(&serde_json::json!({
+                    "nft": nft_submissions,
+                    "token": token_submissions,
+                })),
+                PendingCommand::Approve { principal_id } if in_nft(&principal_id) => {
+                    response(nft.approve_submission(principal_id).await)
+                }
+                PendingCommand::Approve { principal_id } if in_tokens(&principal_id) => {
+                    response(tokens.approve_submission(principal_id).await)
+                }
+                PendingCommand::Reject { principal_id } if in_nft(&principal_id) => {
+                    response(nft.reject_submission(principal_id).await)
+                }
+                PendingCommand::Reject { principal_id } if in_tokens(&principal_id) => {
+                    response(tokens.reject_submission(principal_id).await)
+                }
+                PendingCommand::Approve { principal_id }
+                | PendingCommand::Reject { principal_id } => {
+                    Err(format!("no pending submission for {}", principal_id))
+                }
+            }
+        }
+    }
+}
+
+async fn agent(config: &Config, identity: Option<&Path>) -> Result<Agent, String> {
+    let identity: Box<dyn Identity> = match identity {
+        Some(path) => load_identity(path)?,
+        None => Box::new(AnonymousIdentity),
+    };
+
+    let transport =
+        ReqwestHttpReplicaV2Transport::create(config.url()).map_err(|err| err.to_string())?;
+    let agent = Agent::builder()
+        .with_transport(transport)
+        .with_boxed_identity(identity)
+        .build()
+        .map_err(|err| err.to_string())?;
+
+    // Local replicas use a root key of their own
+    if !config.is_mainnet() {
+        agent
+            .fetch_root_key()
+            .await
+            .map_err(|err| err.to_string())?;
+    }
+
+    Ok(agent)
+}
+
+// dfx writes either secp256k1 or ed25519 keys depending on how the identity was created
+fn load_identity(path: &Path) -> Result<Box<dyn Identity>, String> {
+    if let Ok(identity) = Secp256k1Identity::from_pem_file(path) {
+        return Ok(Box::new(identity));
+    }
+
+    BasicIdentity::from_pem_file(path)
+        .map(|identity| Box::new(identity) as Box<dyn Identity>)
+        .map_err(|err| format!("could not load identity {}: {}", path.display(), err))
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+    serde_json::from_str(&text).map_err(|err| format!("invalid {}: {}", path.display(), err))
+}
+
+fn print<T: Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|err| err.to_string())?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn response(result: Result<Result<(), OperationError>, Error>) -> Result<(), String> {
+    match result.map_err(call_error)? {
+        Ok(()) => Ok(()),
+        Err(err) => Err(format!("the registry rejected the call: {:?}", err)),
+    }
+}
+
+fn call_error(err: Error) -> String {
+    match err {
+        Error::Candid(err) => format!("could not decode the reply: {}", err),
+        Error::Call(msg) => msg,
+    }
+}
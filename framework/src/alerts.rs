@@ -0,0 +1,98 @@
+use ic_cdk::api::call::notify;
+use ic_kit::candid::Principal;
+use ic_kit::*;
+
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Heap usage past which operators are warned, out of the 4GiB a canister can address
+const HEAP_ALERT_THRESHOLD: u64 = 3 * 1024 * 1024 * 1024;
+
+// Canister and method receiving a one-way call with every `Alert`
+#[derive(Default)]
+pub struct AlertSink {
+    pub sink: Option<(Principal, String)>,
+    capacity_alerted: bool,
+}
+
+impl AlertSink {
+    pub fn raise(&self, kind: AlertKind) {
+        if let Some((canister, method)) = &self.sink {
+            let alert = Alert {
+                registry: ic::id(),
+                time: ic::time(),
+                kind,
+            };
+
+            // Alerting is best effort, it must never fail the operation that raised it
+            let _ = notify(*canister, method, (alert,));
+        }
+    }
+
+    // Raises a single alert when the heap crosses the threshold
+    pub fn check_capacity(&mut self) {
+        let heap_size = heap_size();
+        if heap_size < HEAP_ALERT_THRESHOLD {
+            self.capacity_alerted = false;
+        } else if !self.capacity_alerted {
+            self.capacity_alerted = true;
+            self.raise(AlertKind::CapacityNearlyFull { heap_size });
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn heap_size() -> u64 {
+    const WASM_PAGE_SIZE: u64 = 64 * 1024;
+    core::arch::wasm32::memory_size(0) as u64 * WASM_PAGE_SIZE
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn heap_size() -> u64 {
+    0
+}
+
+pub fn raise(kind: AlertKind) {
+    ic::get::<AlertSink>().raise(kind);
+}
+
+// `None` stops alerting
+pub fn set_alert_sink(sink: Option<(Principal, String)>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    if let Some((_, method)) = &sink {
+        if method.is_empty() {
+            return Err(OperationError::BadParameters);
+        }
+    }
+
+    ic::get_mut::<AlertSink>().sink = sink;
+    Ok(())
+}
+
+pub fn get_alert_sink() -> Option<(Principal, String)> {
+    ic::get::<AlertSink>().sink.clone()
+}
+
+// Generates the methods of `AlertSink`
+#[macro_export]
+macro_rules! alerts_methods {
+    () => {
+        #[$crate::ic_kit::macros::update]
+        pub fn set_alert_sink(
+            sink: Option<($crate::ic_kit::Principal, String)>,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::alerts::set_alert_sink(sink)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_alert_sink() -> Option<($crate::ic_kit::Principal, String)> {
+            $crate::alerts::get_alert_sink()
+        }
+    };
+}
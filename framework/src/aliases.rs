@@ -0,0 +1,93 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::HashMap;
+
+use crate::entries::EntryStore;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Maps the legacy principal of a redeployed canister to its current entry
+#[derive(Default)]
+pub struct Aliases(HashMap<Principal, Principal>);
+
+impl Aliases {
+    pub fn archive(&mut self) -> Vec<(Principal, Principal)> {
+        let map = std::mem::replace(&mut self.0, HashMap::new());
+        map.into_iter().collect()
+    }
+
+    pub fn load(&mut self, archive: Vec<(Principal, Principal)>) {
+        self.0 = archive.into_iter().collect();
+    }
+
+    pub fn resolve(&self, old: &Principal) -> Option<Principal> {
+        self.0.get(old).copied()
+    }
+
+    pub fn get_all(&self) -> Vec<(Principal, Principal)> {
+        self.0.iter().map(|(old, new)| (*old, *new)).collect()
+    }
+}
+
+pub fn add_alias<R: EntryStore>(old: Principal, new: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    // Aliases only ever point at a registered entry and never shadow one
+    let db = ic::get::<R>();
+    if db.entry(&new).is_none() {
+        return Err(OperationError::NonExistentItem);
+    } else if old == new || db.entry(&old).is_some() {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::get_mut::<Aliases>().0.insert(old, new);
+    Ok(())
+}
+
+pub fn remove_alias(old: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    match ic::get_mut::<Aliases>().0.remove(&old) {
+        Some(_) => Ok(()),
+        None => Err(OperationError::NonExistentItem),
+    }
+}
+
+pub fn get_aliases() -> Vec<(Principal, Principal)> {
+    ic::get::<Aliases>().get_all()
+}
+
+// Generates the methods of `Aliases`, `$registry` is the `EntryStore` of the registry
+#[macro_export]
+macro_rules! aliases_methods {
+    ($registry:ty) => {
+        #[$crate::ic_kit::macros::update]
+        pub fn add_alias(
+            old: $crate::ic_kit::Principal,
+            new: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::aliases::add_alias::<$registry>(old, new)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn remove_alias(
+            old: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::aliases::remove_alias(old)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_aliases() -> Vec<($crate::ic_kit::Principal, $crate::ic_kit::Principal)> {
+            $crate::aliases::get_aliases()
+        }
+    };
+}
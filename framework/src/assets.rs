@@ -0,0 +1,281 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::entries::SubmissionStore;
+use crate::logger::log;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+use crate::DetailValue;
+
+// Blobs too large to be kept in the details of an entry, addressed by the SHA-256 hash of
+// their content. Details reference an asset with a `Slice` holding its hash.
+#[derive(Default)]
+pub struct Assets {
+    pub content: BTreeMap<Vec<u8>, Vec<u8>>,
+    // Assets kept from before upload times were recorded count as uploaded at 0
+    pub uploaded_at: BTreeMap<Vec<u8>, u64>,
+    // Assets kept from before uploaders were recorded have none
+    pub uploaded_by: BTreeMap<Vec<u8>, Principal>,
+}
+
+impl Assets {
+    pub fn archive(&mut self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        std::mem::take(&mut self.content).into_iter().collect()
+    }
+
+    pub fn archive_uploads(&mut self) -> Vec<(Vec<u8>, u64)> {
+        std::mem::take(&mut self.uploaded_at).into_iter().collect()
+    }
+
+    pub fn archive_uploaders(&mut self) -> Vec<(Vec<u8>, Principal)> {
+        std::mem::take(&mut self.uploaded_by).into_iter().collect()
+    }
+
+    pub fn load(
+        &mut self,
+        archive: Vec<(Vec<u8>, Vec<u8>)>,
+        uploads: Vec<(Vec<u8>, u64)>,
+        uploaders: Vec<(Vec<u8>, Principal)>,
+    ) {
+        self.content = archive.into_iter().collect();
+        self.uploaded_at = uploads.into_iter().collect();
+        self.uploaded_by = uploaders.into_iter().collect();
+    }
+
+    // Bytes held by the store
+    pub fn size(&self) -> u64 {
+        self.content
+            .values()
+            .map(|content| content.len() as u64)
+            .sum()
+    }
+
+    // Bytes uploaded by `uploader` that no entry or pending submission references yet
+    pub fn unreferenced_size<S: SubmissionStore>(&self, uploader: &Principal) -> u64 {
+        let refs = ic::get::<AssetRefs>();
+        let pending = pending_hashes::<S>();
+        self.uploaded_by
+            .iter()
+            .filter(|(hash, by)| *by == uploader && !refs.is_referenced(hash))
+            .filter(|(hash, _)| !pending.contains(*hash))
+            .filter_map(|(hash, _)| self.content.get(hash))
+            .map(|content| content.len() as u64)
+            .sum()
+    }
+
+    // Stores `content` for `uploader` and returns its hash, uploading the same content twice
+    // is a no-op. The store is filled up to `store_limit` bytes.
+    pub fn insert(
+        &mut self,
+        uploader: Principal,
+        content: Vec<u8>,
+        store_limit: u64,
+    ) -> Result<Vec<u8>, OperationError> {
+        let hash = Sha256::digest(&content).to_vec();
+        if self.content.contains_key(&hash) {
+            return Ok(hash);
+        }
+
+        if content.is_empty()
+            || content.len() as u64 > ASSET_SIZE_LIMIT
+            || self.size() + content.len() as u64 > store_limit
+        {
+            return Err(OperationError::BadParameters);
+        }
+
+        self.uploaded_at.insert(hash.clone(), ic::time());
+        self.uploaded_by.insert(hash.clone(), uploader);
+        self.content.insert(hash.clone(), content);
+        Ok(hash)
+    }
+
+    // Deletes an asset and returns its size
+    pub fn remove(&mut self, hash: &[u8]) -> u64 {
+        self.uploaded_at.remove(hash);
+        self.uploaded_by.remove(hash);
+        self.content
+            .remove(hash)
+            .map_or(0, |content| content.len() as u64)
+    }
+}
+
+// Entries referencing each asset. Kept up to date by the registry as entries are written,
+// and rebuilt from its entries after an upgrade.
+#[derive(Default, PartialEq)]
+pub struct AssetRefs(pub BTreeMap<Vec<u8>, BTreeSet<Principal>>);
+
+impl AssetRefs {
+    pub fn track(&mut self, principal_id: Principal, details: &[(String, DetailValue)]) {
+        for hash in asset_hashes(details) {
+            self.0.entry(hash).or_default().insert(principal_id);
+        }
+    }
+
+    // Forgets the references of an entry, returning the assets no entry references anymore
+    pub fn untrack(
+        &mut self,
+        principal_id: &Principal,
+        details: &[(String, DetailValue)],
+    ) -> Vec<Vec<u8>> {
+        let mut orphans = vec![];
+        for hash in asset_hashes(details) {
+            if let Some(entries) = self.0.get_mut(&hash) {
+                entries.remove(principal_id);
+                if entries.is_empty() {
+                    self.0.remove(&hash);
+                    orphans.push(hash);
+                }
+            }
+        }
+        orphans
+    }
+
+    // Moves the references of an entry from its `previous` details to `details`, returning
+    // the assets only the previous details referenced
+    pub fn retrack(
+        &mut self,
+        principal_id: Principal,
+        previous: &[(String, DetailValue)],
+        details: &[(String, DetailValue)],
+    ) -> Vec<Vec<u8>> {
+        let orphans = self.untrack(&principal_id, previous);
+        self.track(principal_id, details);
+        orphans
+            .into_iter()
+            .filter(|hash| !self.is_referenced(hash))
+            .collect()
+    }
+
+    pub fn is_referenced(&self, hash: &[u8]) -> bool {
+        self.0.contains_key(hash)
+    }
+}
+
+fn collect_hashes(value: &DetailValue, hashes: &mut BTreeSet<Vec<u8>>) {
+    match value {
+        DetailValue::Slice(bytes) if bytes.len() == 32 => {
+            hashes.insert(bytes.clone());
+        }
+        DetailValue::Vec(values) => values
+            .iter()
+            .for_each(|value| collect_hashes(value, hashes)),
+        _ => (),
+    }
+}
+
+// Slices as long as a SHA-256 hash, whether or not they were uploaded yet
+fn asset_hashes(details: &[(String, DetailValue)]) -> BTreeSet<Vec<u8>> {
+    let mut hashes = BTreeSet::new();
+    for (_, value) in details {
+        collect_hashes(value, &mut hashes);
+    }
+    hashes
+}
+
+// Assets pending submissions will reference once approved
+fn pending_hashes<S: SubmissionStore>() -> BTreeSet<Vec<u8>> {
+    ic::get::<S>()
+        .pending_details()
+        .into_iter()
+        .flat_map(asset_hashes)
+        .collect()
+}
+
+// Deletes the assets among `hashes` nothing references anymore and returns the bytes
+// reclaimed. Called with the assets an entry stopped referencing when it's written or removed.
+pub fn release<S: SubmissionStore>(hashes: Vec<Vec<u8>>) -> u64 {
+    if hashes.is_empty() {
+        return 0;
+    }
+
+    let refs = ic::get::<AssetRefs>();
+    let pending = pending_hashes::<S>();
+    let assets = ic::get_mut::<Assets>();
+    hashes
+        .iter()
+        .filter(|hash| !refs.is_referenced(hash) && !pending.contains(*hash))
+        .map(|hash| assets.remove(hash))
+        .sum()
+}
+
+// Returns the hash to reference the asset by in the details of an entry. Other callers than
+// admins hold at most `ASSET_UPLOAD_QUOTA` bytes of assets nothing references yet, and can't
+// use the last `ASSET_ADMIN_RESERVE` bytes of the store.
+pub fn upload_asset<S: SubmissionStore>(content: Vec<u8>) -> Result<Vec<u8>, OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    let caller = ic::caller();
+    let assets = ic::get_mut::<Assets>();
+    if is_admin(&caller) {
+        return assets.insert(caller, content, ASSET_STORE_LIMIT);
+    }
+
+    let hash = Sha256::digest(&content).to_vec();
+    if assets.content.contains_key(&hash) {
+        return Ok(hash);
+    } else if assets.unreferenced_size::<S>(&caller) + content.len() as u64 > ASSET_UPLOAD_QUOTA {
+        return Err(OperationError::BadParameters);
+    }
+    assets.insert(caller, content, ASSET_STORE_LIMIT - ASSET_ADMIN_RESERVE)
+}
+
+pub fn get_asset(hash: Vec<u8>) -> Option<&'static Vec<u8>> {
+    ic::get::<Assets>().content.get(&hash)
+}
+
+// Deletes the assets no entry or pending submission references and returns the bytes
+// reclaimed. Assets uploaded less than `ASSET_GC_GRACE` ago are kept, they're likely
+// waiting for the `add` or `submit` call referencing them.
+pub fn gc_assets<S: SubmissionStore>() -> Result<u64, OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    let caller = ic::caller();
+    if !is_admin(&caller) {
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
+    }
+
+    let refs = ic::get::<AssetRefs>();
+    let pending = pending_hashes::<S>();
+    let assets = ic::get_mut::<Assets>();
+    let cutoff = ic::time().saturating_sub(ASSET_GC_GRACE);
+    let orphans: Vec<Vec<u8>> = assets
+        .content
+        .keys()
+        .filter(|hash| !refs.is_referenced(hash) && !pending.contains(*hash))
+        .filter(|hash| assets.uploaded_at.get(*hash).map_or(0, |time| *time) < cutoff)
+        .cloned()
+        .collect();
+    let reclaimed: u64 = orphans.iter().map(|hash| assets.remove(hash)).sum();
+
+    let message = format!(
+        "{} assets deleted, {} bytes reclaimed",
+        orphans.len(),
+        reclaimed
+    );
+    log(LogLevel::Info, "gc_assets", caller, message);
+    Ok(reclaimed)
+}
+
+// Generates the methods of `Assets`, `$submissions` is the `SubmissionStore` of the registry
+#[macro_export]
+macro_rules! assets_methods {
+    ($submissions:ty) => {
+        #[$crate::ic_kit::macros::update]
+        pub fn upload_asset(content: Vec<u8>) -> Result<Vec<u8>, $crate::types::OperationError> {
+            $crate::assets::upload_asset::<$submissions>(content)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_asset(hash: Vec<u8>) -> Option<&'static Vec<u8>> {
+            $crate::assets::get_asset(hash)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn gc_assets() -> Result<u64, $crate::types::OperationError> {
+            $crate::assets::gc_assets::<$submissions>()
+        }
+    };
+}
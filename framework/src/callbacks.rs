@@ -0,0 +1,167 @@
+use ic_cdk::api::call::notify;
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::BTreeMap;
+
+use crate::entries::{EntryStore, ListedEntry, SubmissionStore};
+use crate::guard::check_principal;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::retries::Retries;
+use crate::types::*;
+
+// Methods of their own canisters entry owners want called when the status of their entry
+// changes, keyed by the principal of the entry. Notices are one-way calls, the failed ones
+// are replayed by the `Retries` queue.
+#[derive(Default)]
+pub struct EntryCallbacks(pub BTreeMap<Principal, (Principal, String)>);
+
+impl EntryCallbacks {
+    pub fn archive(&self) -> Vec<(Principal, Principal, String)> {
+        self.0
+            .iter()
+            .map(|(principal_id, (target, method))| (*principal_id, *target, method.clone()))
+            .collect()
+    }
+
+    pub fn load(&mut self, archive: Vec<(Principal, Principal, String)>) {
+        self.0 = archive
+            .into_iter()
+            .map(|(principal_id, target, method)| (principal_id, (target, method)))
+            .collect();
+    }
+}
+
+// Sends `notice` to `method` of `target`, queueing a retry when the call can't be made
+pub fn send_notice(target: Principal, method: String, notice: EntryNotice) {
+    if let Err(code) = notify(target, &method, (notice.clone(),)) {
+        let kind = TaskKind::EntryCallback {
+            target,
+            method,
+            notice,
+        };
+        ic::get_mut::<Retries>().schedule(kind, format!("{:?}", code));
+    }
+}
+
+// Tells the owner of the entry about a status change, when they set a callback
+pub fn notify_owner(principal_id: Principal, status: EntryStatus) {
+    if let Some((target, method)) = ic::get::<EntryCallbacks>().0.get(&principal_id) {
+        let notice = EntryNotice {
+            principal_id,
+            status,
+            time: ic::time(),
+        };
+        send_notice(*target, method.clone(), notice);
+    }
+}
+
+// The manager of a listed entry, or the submitter of a pending one, owns its callback
+fn check_owner<R: EntryStore, S: SubmissionStore>(
+    caller: &Principal,
+    principal_id: &Principal,
+) -> Result<(), OperationError> {
+    let manager = ic::get::<R>()
+        .entry(principal_id)
+        .map(|entry| entry.manager());
+    let submitter = ic::get::<S>().submitter(principal_id);
+
+    if manager.is_none() && submitter.is_none() {
+        Err(OperationError::NonExistentItem)
+    } else if manager != Some(*caller) && submitter != Some(*caller) && !is_admin(caller) {
+        Err(OperationError::not_authorized(Role::Submitter, caller))
+    } else {
+        Ok(())
+    }
+}
+
+// Notices only go to the entry itself or to another entry listed by the same manager, so a
+// callback can't make the registry call canisters its owner doesn't control
+fn check_target<R: EntryStore>(
+    principal_id: &Principal,
+    target: &Principal,
+) -> Result<(), OperationError> {
+    let registry = ic::get::<R>();
+    let manager =
+        |principal_id: &Principal| registry.entry(principal_id).map(|entry| entry.manager());
+
+    if target == principal_id
+        || (manager(target).is_some() && manager(target) == manager(principal_id))
+    {
+        Ok(())
+    } else {
+        Err(OperationError::BadParameters)
+    }
+}
+
+// Calls `method` of `target` with an `EntryNotice` when the entry is approved, edited by an
+// admin, reported or delisted
+pub fn set_entry_callback<R: EntryStore, S: SubmissionStore>(
+    principal_id: Principal,
+    target: Principal,
+    method: String,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    check_principal(&target)?;
+    check_owner::<R, S>(&ic::caller(), &principal_id)?;
+    check_target::<R>(&principal_id, &target)?;
+
+    if method.is_empty() {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::get_mut::<EntryCallbacks>()
+        .0
+        .insert(principal_id, (target, method));
+    Ok(())
+}
+
+pub fn remove_entry_callback<R: EntryStore, S: SubmissionStore>(
+    principal_id: Principal,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    check_owner::<R, S>(&ic::caller(), &principal_id)?;
+
+    match ic::get_mut::<EntryCallbacks>().0.remove(&principal_id) {
+        Some(_) => Ok(()),
+        None => Err(OperationError::NonExistentItem),
+    }
+}
+
+pub fn get_entry_callback(principal_id: Principal) -> Option<(Principal, String)> {
+    ic::get::<EntryCallbacks>().0.get(&principal_id).cloned()
+}
+
+// Generates the methods of `EntryCallbacks`. `$registry` is the `EntryStore` of the registry
+// and `$submissions` its `SubmissionStore`.
+#[macro_export]
+macro_rules! callbacks_methods {
+    ($registry:ty, $submissions:ty) => {
+        #[$crate::ic_kit::macros::update]
+        pub fn set_entry_callback(
+            principal_id: $crate::ic_kit::Principal,
+            target: $crate::ic_kit::Principal,
+            method: String,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::callbacks::set_entry_callback::<$registry, $submissions>(
+                principal_id,
+                target,
+                method,
+            )
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn remove_entry_callback(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::callbacks::remove_entry_callback::<$registry, $submissions>(principal_id)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_entry_callback(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Option<($crate::ic_kit::Principal, String)> {
+            $crate::callbacks::get_entry_callback(principal_id)
+        }
+    };
+}
@@ -0,0 +1,232 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
+
+use crate::entries::SubmissionStore;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Curators vote on pending submissions. Once a submission gathers `threshold`
+// approvals it is added to the registry, and once it gathers `threshold`
+// rejections it is dropped. Voting is disabled while no threshold is set.
+#[derive(Default)]
+pub struct Curation {
+    curators: BTreeSet<Principal>,
+    threshold: Option<u32>,
+    votes: HashMap<Principal, Votes>,
+}
+
+impl Curation {
+    pub fn archive(&mut self) -> CurationArchive {
+        let curation = std::mem::replace(self, Curation::default());
+        CurationArchive {
+            curators: curation.curators.into_iter().collect(),
+            threshold: curation.threshold,
+            votes: curation.votes.into_iter().collect(),
+        }
+    }
+
+    pub fn load(&mut self, archive: CurationArchive) {
+        self.curators = archive.curators.into_iter().collect();
+        self.threshold = archive.threshold;
+        self.votes = archive.votes.into_iter().collect();
+    }
+
+    pub fn threshold(&self) -> Option<u32> {
+        self.threshold
+    }
+
+    pub fn is_curator(&self, account: &Principal) -> bool {
+        self.curators.contains(account)
+    }
+
+    // Records the vote, replacing any earlier vote of the curator on the same
+    // submission, and returns whether the threshold has been reached.
+    pub fn vote(&mut self, curator: Principal, principal_id: Principal, approve: bool) -> bool {
+        let votes = self.votes.entry(principal_id).or_default();
+        votes.approvals.retain(|account| *account != curator);
+        votes.rejections.retain(|account| *account != curator);
+
+        let tally = if approve {
+            &mut votes.approvals
+        } else {
+            &mut votes.rejections
+        };
+        tally.push(curator);
+
+        match self.threshold {
+            Some(threshold) => tally.len() as u32 >= threshold,
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self, principal_id: &Principal) {
+        self.votes.remove(principal_id);
+    }
+
+    pub fn get_votes(&self, principal_id: &Principal) -> Votes {
+        self.votes.get(principal_id).cloned().unwrap_or_default()
+    }
+}
+
+// `resolve` adds the submission to the registry or drops it, once the vote reached the threshold
+async fn vote<S, F>(
+    principal_id: Principal,
+    approve: bool,
+    resolve: fn(Principal) -> F,
+) -> Result<(), OperationError>
+where
+    S: SubmissionStore,
+    F: Future<Output = Result<(), OperationError>>,
+{
+    let caller = ic::caller();
+    let curation = ic::get_mut::<Curation>();
+
+    if !curation.is_curator(&caller) {
+        return Err(OperationError::not_authorized(Role::Curator, &caller));
+    } else if curation.threshold.is_none() {
+        return Err(OperationError::BadParameters);
+    } else if ic::get::<S>().submitter(&principal_id).is_none() {
+        return Err(OperationError::NonExistentItem);
+    }
+
+    if !curation.vote(caller, principal_id, approve) {
+        return Ok(());
+    }
+
+    resolve(principal_id).await
+}
+
+pub async fn vote_approve<S, F>(
+    principal_id: Principal,
+    promote: fn(Principal) -> F,
+) -> Result<(), OperationError>
+where
+    S: SubmissionStore,
+    F: Future<Output = Result<(), OperationError>>,
+{
+    ic::get::<Maintenance>().check()?;
+
+    vote::<S, F>(principal_id, true, promote).await
+}
+
+pub async fn vote_reject<S, F>(
+    principal_id: Principal,
+    dismiss: fn(Principal) -> F,
+) -> Result<(), OperationError>
+where
+    S: SubmissionStore,
+    F: Future<Output = Result<(), OperationError>>,
+{
+    ic::get::<Maintenance>().check()?;
+
+    vote::<S, F>(principal_id, false, dismiss).await
+}
+
+pub fn get_votes(principal_id: Principal) -> Votes {
+    ic::get::<Curation>().get_votes(&principal_id)
+}
+
+pub fn add_curator(curator: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    ic::get_mut::<Curation>().curators.insert(curator);
+    Ok(())
+}
+
+pub fn remove_curator(curator: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    match ic::get_mut::<Curation>().curators.remove(&curator) {
+        true => Ok(()),
+        false => Err(OperationError::NonExistentItem),
+    }
+}
+
+pub fn get_curators() -> Vec<Principal> {
+    ic::get::<Curation>().curators.iter().copied().collect()
+}
+
+pub fn set_vote_threshold(threshold: Option<u32>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if threshold == Some(0) {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::get_mut::<Curation>().threshold = threshold;
+    Ok(())
+}
+
+pub fn get_vote_threshold() -> Option<u32> {
+    ic::get::<Curation>().threshold()
+}
+
+// Generates the methods of `Curation`. `$submissions` is the `SubmissionStore` of the registry,
+// `$promote` adds a submission to the registry and `$dismiss` drops it.
+#[macro_export]
+macro_rules! curation_methods {
+    ($submissions:ty, $promote:path, $dismiss:path) => {
+        #[$crate::ic_kit::macros::update]
+        pub async fn vote_approve(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::curation::vote_approve::<$submissions, _>(principal_id, $promote).await
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub async fn vote_reject(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::curation::vote_reject::<$submissions, _>(principal_id, $dismiss).await
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_votes(principal_id: $crate::ic_kit::Principal) -> $crate::types::Votes {
+            $crate::curation::get_votes(principal_id)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn add_curator(
+            curator: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::curation::add_curator(curator)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn remove_curator(
+            curator: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::curation::remove_curator(curator)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_curators() -> Vec<$crate::ic_kit::Principal> {
+            $crate::curation::get_curators()
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn set_vote_threshold(
+            threshold: Option<u32>,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::curation::set_vote_threshold(threshold)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_vote_threshold() -> Option<u32> {
+            $crate::curation::get_vote_threshold()
+        }
+    };
+}
@@ -0,0 +1,47 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+
+use crate::alerts::raise;
+use crate::callbacks::{notify_owner, EntryCallbacks};
+use crate::types::*;
+
+// Entries taken down by `emergency_delist`, oldest first
+#[derive(Default)]
+pub struct Delistings(pub Vec<Delisting>);
+
+impl Delistings {
+    // Records an entry the registry just delisted, and tells operators and its owner
+    pub fn record(&mut self, principal_id: Principal, name: String, reason: String, by: Principal) {
+        self.0.push(Delisting {
+            principal_id,
+            name,
+            reason,
+            delisted_by: by,
+            delisted_at: ic::time(),
+        });
+        raise(AlertKind::EntryDelisted { principal_id, by });
+        notify_owner(principal_id, EntryStatus::Delisted);
+        ic::get_mut::<EntryCallbacks>().0.remove(&principal_id);
+    }
+
+    // Drops the oldest delistings until at most `keep` are left
+    pub fn trim(&mut self, keep: usize) {
+        let excess = self.0.len().saturating_sub(keep);
+        self.0.drain(..excess);
+    }
+}
+
+pub fn get_delistings() -> Vec<Delisting> {
+    ic::get::<Delistings>().0.clone()
+}
+
+// Generates the methods of `Delistings`, the registry defines `emergency_delist` itself
+#[macro_export]
+macro_rules! delisting_methods {
+    () => {
+        #[$crate::ic_kit::macros::query]
+        pub fn get_delistings() -> Vec<$crate::types::Delisting> {
+            $crate::delisting::get_delistings()
+        }
+    };
+}
@@ -0,0 +1,183 @@
+use ic_kit::candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+// Value of an entry detail, shared by the registries and the SDK so the lookups and
+// conversions below are written once
+#[derive(CandidType, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum DetailValue {
+    True,
+    False,
+    U64(u64),
+    I64(i64),
+    Float(f64),
+    Text(String),
+    Principal(Principal),
+    #[serde(with = "serde_bytes")]
+    Slice(Vec<u8>),
+    Vec(Vec<DetailValue>),
+}
+
+impl DetailValue {
+    // NaN breaks equality and neither NaN nor the infinities survive a JSON round trip,
+    // so floats must be finite, including the ones nested in vectors
+    pub fn is_finite(&self) -> bool {
+        match self {
+            DetailValue::Float(value) => value.is_finite(),
+            DetailValue::Vec(values) => values.iter().all(DetailValue::is_finite),
+            _ => true,
+        }
+    }
+
+    // A scalar has a depth of 1, a vector one more than its deepest element
+    pub fn depth(&self) -> u32 {
+        match self {
+            DetailValue::Vec(values) => {
+                1 + values.iter().map(DetailValue::depth).max().unwrap_or(0)
+            }
+            _ => 1,
+        }
+    }
+
+    // Length of the longest slice in the value, 0 when it holds none
+    pub fn largest_slice(&self) -> u64 {
+        match self {
+            DetailValue::Slice(bytes) => bytes.len() as u64,
+            DetailValue::Vec(values) => values
+                .iter()
+                .map(DetailValue::largest_slice)
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        }
+    }
+}
+
+// Lookups of the value held by a detail. Integers are read as any integer type
+// they fit in, and as floats, so callers don't depend on how a value was submitted.
+impl DetailValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            DetailValue::True => Some(true),
+            DetailValue::False => Some(false),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            DetailValue::U64(value) => Some(*value),
+            DetailValue::I64(value) => u64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            DetailValue::I64(value) => Some(*value),
+            DetailValue::U64(value) => i64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            DetailValue::Float(value) => Some(*value),
+            DetailValue::U64(value) => Some(*value as f64),
+            DetailValue::I64(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            DetailValue::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_principal(&self) -> Option<Principal> {
+        match self {
+            DetailValue::Principal(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_slice(&self) -> Option<&[u8]> {
+        match self {
+            DetailValue::Slice(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_vec(&self) -> Option<&[DetailValue]> {
+        match self {
+            DetailValue::Vec(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for DetailValue {
+    fn from(value: bool) -> Self {
+        match value {
+            true => DetailValue::True,
+            false => DetailValue::False,
+        }
+    }
+}
+
+impl From<u64> for DetailValue {
+    fn from(value: u64) -> Self {
+        DetailValue::U64(value)
+    }
+}
+
+impl From<i64> for DetailValue {
+    fn from(value: i64) -> Self {
+        DetailValue::I64(value)
+    }
+}
+
+impl From<f64> for DetailValue {
+    fn from(value: f64) -> Self {
+        DetailValue::Float(value)
+    }
+}
+
+impl From<String> for DetailValue {
+    fn from(value: String) -> Self {
+        DetailValue::Text(value)
+    }
+}
+
+impl From<&str> for DetailValue {
+    fn from(value: &str) -> Self {
+        DetailValue::Text(value.to_string())
+    }
+}
+
+impl From<Principal> for DetailValue {
+    fn from(value: Principal) -> Self {
+        DetailValue::Principal(value)
+    }
+}
+
+impl From<Vec<u8>> for DetailValue {
+    fn from(value: Vec<u8>) -> Self {
+        DetailValue::Slice(value)
+    }
+}
+
+impl From<Vec<DetailValue>> for DetailValue {
+    fn from(values: Vec<DetailValue>) -> Self {
+        DetailValue::Vec(values)
+    }
+}
+
+// Value of the first detail named `key`
+pub fn detail<'a>(details: &'a [(String, DetailValue)], key: &str) -> Option<&'a DetailValue> {
+    details
+        .iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value)
+}
@@ -0,0 +1,37 @@
+use ic_kit::candid::{CandidType, Principal};
+use serde::de::DeserializeOwned;
+
+use crate::types::{Event, LocalizedText};
+use crate::DetailValue;
+
+// An entry of a registry using the shared stores
+pub trait ListedEntry: CandidType + DeserializeOwned + Clone + 'static {
+    fn principal_id(&self) -> Principal;
+
+    // The principal allowed to edit the entry besides admins
+    fn manager(&self) -> Principal;
+
+    // Replaces the name and description with a translation, see `get_localized`
+    fn localize(&mut self, text: &LocalizedText);
+}
+
+// The entries of a registry, kept in `ic::get`
+pub trait EntryStore: Default + 'static {
+    type Entry: ListedEntry;
+
+    fn entry(&self, principal_id: &Principal) -> Option<&Self::Entry>;
+
+    // Principals of every entry, in no particular order
+    fn principals(&self) -> Vec<Principal>;
+
+    // Replays an event of the upstream registry, see `Mirror`
+    fn replay(&mut self, event: Event<Self::Entry>);
+}
+
+// The pending submissions of a registry, kept in `ic::get`
+pub trait SubmissionStore: Default + 'static {
+    fn submitter(&self, principal_id: &Principal) -> Option<Principal>;
+
+    // Details of every pending submission, the assets they reference are kept
+    fn pending_details(&self) -> Vec<&[(String, DetailValue)]>;
+}
@@ -0,0 +1,18 @@
+use ic_kit::*;
+
+use crate::types::OperationError;
+
+// The anonymous principal is shared by every unauthenticated caller. It can't change the
+// registry, and can't be given a role in it either.
+pub fn check_principal(principal: &Principal) -> Result<(), OperationError> {
+    match *principal == Principal::anonymous() {
+        true => Err(OperationError::Anonymous),
+        false => Ok(()),
+    }
+}
+
+// Called first by every update endpoint, through `Maintenance::check` for the ones that
+// change the registry data
+pub fn check_caller() -> Result<(), OperationError> {
+    check_principal(&ic::caller())
+}
@@ -0,0 +1,78 @@
+use ic_kit::*;
+
+use crate::entries::ListedEntry;
+use crate::types::*;
+
+// Ordered log of the latest changes applied to the registry. Event ids start at 1
+// and are contiguous, the oldest events are dropped once the log outgrows `HISTORY_LIMIT`.
+pub struct History<T>(pub Vec<Event<T>>);
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        History(vec![])
+    }
+}
+
+impl<T: ListedEntry> History<T> {
+    pub fn archive(&mut self) -> Vec<Event<T>> {
+        std::mem::replace(&mut self.0, Vec::new())
+    }
+
+    pub fn load(&mut self, archive: Vec<Event<T>>) {
+        self.0 = archive;
+        self.trim(HISTORY_LIMIT);
+    }
+
+    // Drops the oldest events until at most `keep` are left
+    pub fn trim(&mut self, keep: usize) {
+        let excess = self.0.len().saturating_sub(keep);
+        self.0.drain(..excess);
+    }
+
+    // Id of the oldest event still in the log
+    pub fn first_id(&self) -> u64 {
+        self.0.first().map(|event| event.id).unwrap_or(1)
+    }
+
+    // Appends the event of a change to `entry`. The registry notifies subscribers and
+    // routers of it, see its `store_event`.
+    pub fn push(&mut self, kind: EventKind, entry: &T) -> &Event<T> {
+        let event = Event {
+            id: self.0.last().map(|event| event.id + 1).unwrap_or(1),
+            time: ic::time(),
+            kind,
+            entry: entry.clone(),
+        };
+        self.0.push(event);
+
+        // Trims a tenth of the limit at once, so the log isn't shifted on every event
+        if self.0.len() > HISTORY_LIMIT {
+            self.trim(HISTORY_LIMIT - HISTORY_LIMIT / 10);
+        }
+
+        self.0.last().unwrap()
+    }
+
+    // None for ids that were never given out and for events that were already dropped
+    pub fn get(&self, id: u64) -> Option<&Event<T>> {
+        let position = id.checked_sub(self.first_id())?;
+        self.0.get(position as usize)
+    }
+
+    // Returns up to `limit` events that happened after `since_event_id`. Clients that fell
+    // behind the oldest kept event get `HistoryTruncated` with its id, the events in between
+    // are gone and they have to reload the registry before following the log again.
+    pub fn get_changes(
+        &self,
+        since_event_id: u64,
+        limit: u16,
+    ) -> Result<Vec<&Event<T>>, OperationError> {
+        let first_id = self.first_id();
+        if since_event_id.saturating_add(1) < first_id {
+            return Err(OperationError::HistoryTruncated(first_id));
+        }
+
+        let start = std::cmp::min((since_event_id + 1 - first_id) as usize, self.0.len());
+        Ok(self.0[start..].iter().take(limit as usize).collect())
+    }
+}
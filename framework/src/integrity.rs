@@ -0,0 +1,105 @@
+use ic_kit::candid::{encode_one, Principal};
+use ic_kit::*;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use crate::entries::ListedEntry;
+use crate::types::*;
+
+// Digest of every entry, kept up to date on each write. Entries are hashed on their own and
+// the hashes added modulo 2^256, so the result doesn't depend on the order they were written
+// in and an update only needs the hash of the entry it replaces.
+#[derive(Default, PartialEq)]
+pub struct Integrity {
+    sum: [u8; 32],
+    hashes: HashMap<Principal, [u8; 32]>,
+}
+
+fn entry_hash<T: ListedEntry>(entry: &T) -> [u8; 32] {
+    let bytes = encode_one(entry).unwrap_or_default();
+    Sha256::digest(&bytes).into()
+}
+
+fn add(sum: &mut [u8; 32], hash: &[u8; 32]) {
+    let mut carry = 0;
+    for i in (0..32).rev() {
+        let total = sum[i] as u16 + hash[i] as u16 + carry;
+        sum[i] = total as u8;
+        carry = total >> 8;
+    }
+}
+
+fn sub(sum: &mut [u8; 32], hash: &[u8; 32]) {
+    let mut borrow = 0;
+    for i in (0..32).rev() {
+        let total = sum[i] as i16 - hash[i] as i16 - borrow;
+        sum[i] = total.rem_euclid(256) as u8;
+        borrow = (total < 0) as i16;
+    }
+}
+
+impl Integrity {
+    pub fn load<T: ListedEntry>(&mut self, entries: Vec<&T>) {
+        *self = Integrity::default();
+        for entry in entries {
+            self.put(entry);
+        }
+        self.certify();
+    }
+
+    pub fn insert<T: ListedEntry>(&mut self, entry: &T) {
+        self.put(entry);
+        self.certify();
+    }
+
+    fn put<T: ListedEntry>(&mut self, entry: &T) {
+        if let Some(old) = self.hashes.remove(&entry.principal_id()) {
+            sub(&mut self.sum, &old);
+        }
+
+        let hash = entry_hash(entry);
+        add(&mut self.sum, &hash);
+        self.hashes.insert(entry.principal_id(), hash);
+    }
+
+    pub fn remove(&mut self, principal_id: &Principal) {
+        if let Some(hash) = self.hashes.remove(principal_id) {
+            sub(&mut self.sum, &hash);
+            self.certify();
+        }
+    }
+
+    // Commits to the number of entries as well, so an empty registry has a distinct hash
+    pub fn hash(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(&(self.hashes.len() as u64).to_be_bytes());
+        hasher.update(&self.sum);
+        hasher.finalize().to_vec()
+    }
+
+    pub fn certify(&self) {
+        ic_cdk::api::set_certified_data(&self.hash());
+    }
+}
+
+// The registry hash, certified by the subnet so mirrors and auditors can check it against
+// mainnet without trusting the replica answering the query
+pub fn registry_hash() -> RegistryHash {
+    let integrity = ic::get::<Integrity>();
+    RegistryHash {
+        hash: integrity.hash(),
+        entries: integrity.hashes.len() as u64,
+        certificate: ic_cdk::api::data_certificate(),
+    }
+}
+
+// Generates the methods of `Integrity`
+#[macro_export]
+macro_rules! integrity_methods {
+    () => {
+        #[$crate::ic_kit::macros::query]
+        pub fn registry_hash() -> $crate::types::RegistryHash {
+            $crate::integrity::registry_hash()
+        }
+    };
+}
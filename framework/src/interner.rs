@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+// Id of a string in the `StringPool`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StrId(u32);
+
+// Strings repeated across entries, such as standards and frontend hosts, stored once and
+// referred to by id. The registry and the frontend index each keep their own pool, so they
+// work without any other canister state. Strings aren't removed as entries change, a store
+// starts a new pool with the ones still in use when it's rebuilt by `compact` or an upgrade.
+#[derive(Default)]
+pub struct StringPool {
+    ids: HashMap<String, StrId>,
+    strings: Vec<String>,
+}
+
+impl StringPool {
+    pub fn intern(&mut self, string: &str) -> StrId {
+        if let Some(id) = self.ids.get(string) {
+            return *id;
+        }
+
+        let id = StrId(self.strings.len() as u32);
+        self.strings.push(string.to_string());
+        self.ids.insert(string.to_string(), id);
+        id
+    }
+
+    // Id of `string` when it was interned, lookups of strings never stored don't grow the pool
+    pub fn id(&self, string: &str) -> Option<StrId> {
+        self.ids.get(string).copied()
+    }
+
+    pub fn get(&self, id: StrId) -> &str {
+        &self.strings[id.0 as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+}
@@ -0,0 +1,182 @@
+use ic_kit::candid::{CandidType, Deserialize, Nat, Principal};
+use ic_kit::*;
+
+use crate::logger::log;
+use crate::retries::Retries;
+use crate::types::*;
+
+// ICRC-1 types used to verify, refund and burn submission deposits
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Account {
+    pub owner: Principal,
+    pub subaccount: Option<Vec<u8>>,
+}
+
+#[derive(CandidType)]
+struct TransferArg {
+    from_subaccount: Option<Vec<u8>>,
+    to: Account,
+    amount: Nat,
+    fee: Option<Nat>,
+    memo: Option<Vec<u8>>,
+    created_at_time: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+enum TransferError {
+    BadFee { expected_fee: Nat },
+    BadBurn { min_burn_amount: Nat },
+    InsufficientFunds { balance: Nat },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: Nat },
+    TemporarilyUnavailable,
+    GenericError { error_code: Nat, message: String },
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+enum TransferResult {
+    Ok(Nat),
+    Err(TransferError),
+}
+
+// Every submitter deposits into their own subaccount of the registry,
+// derived from their principal.
+pub fn deposit_subaccount(submitter: &Principal) -> Vec<u8> {
+    let bytes = submitter.as_slice();
+    let mut subaccount = vec![0; 32];
+    subaccount[0] = bytes.len() as u8;
+    subaccount[1..1 + bytes.len()].copy_from_slice(bytes);
+    subaccount
+}
+
+pub async fn deposit_balance(
+    ledger: Principal,
+    submitter: &Principal,
+) -> Result<Nat, OperationError> {
+    let account = Account {
+        owner: ic::id(),
+        subaccount: Some(deposit_subaccount(submitter)),
+    };
+
+    match ic::call(ledger, "icrc1_balance_of", (account,)).await {
+        Ok((balance,)) => Ok(balance),
+        Err((_code, msg)) => Err(OperationError::Unknown(msg)),
+    }
+}
+
+// Sends the deposit, minus the ledger fee, back to the submitter
+pub async fn refund_deposit(
+    deposit: &Deposit,
+    submitter: &Principal,
+) -> Result<(), OperationError> {
+    let fee: Nat = match ic::call(deposit.ledger, "icrc1_fee", ()).await {
+        Ok((fee,)) => fee,
+        Err((_code, msg)) => {
+            return Err(OperationError::Unknown(msg));
+        }
+    };
+
+    let amount = Nat::from(deposit.amount);
+    if fee >= amount {
+        return Ok(());
+    }
+
+    let to = Account {
+        owner: *submitter,
+        subaccount: None,
+    };
+    transfer(
+        deposit.ledger,
+        submitter,
+        to,
+        amount - fee.clone(),
+        Some(fee),
+    )
+    .await
+}
+
+// Refunds the deposit of the submission of `principal_id`. A refund the ledger rejects is
+// queued on `Retries`, so the submitter still gets it back once the ledger answers.
+pub async fn refund_or_retry(
+    deposit: &Deposit,
+    submitter: &Principal,
+    principal_id: Principal,
+) -> Result<(), OperationError> {
+    let result = refund_deposit(deposit, submitter).await;
+    if let Err(err) = &result {
+        let message = format!("refund of {} failed: {:?}", principal_id, err);
+        log(LogLevel::Error, "refund", *submitter, message);
+        let kind = TaskKind::Refund {
+            principal_id,
+            submitter: *submitter,
+            deposit: deposit.clone(),
+        };
+        ic::get_mut::<Retries>().schedule(kind, format!("{:?}", err));
+    }
+    result
+}
+
+// Runs a refund queued by `refund_or_retry`, putting it back in the queue when it fails again
+pub async fn retry_refund(task: RetryTask) {
+    let (deposit, submitter) = match &task.kind {
+        TaskKind::Refund {
+            deposit, submitter, ..
+        } => (deposit.clone(), *submitter),
+        _ => return,
+    };
+
+    if let Err(err) = refund_deposit(&deposit, &submitter).await {
+        ic::get_mut::<Retries>().reschedule(task, format!("{:?}", err));
+    }
+}
+
+// Transfers to the minting account are burns on ICRC-1 ledgers
+pub async fn burn_deposit(deposit: &Deposit, submitter: &Principal) -> Result<(), OperationError> {
+    let minting_account: Option<Account> =
+        match ic::call(deposit.ledger, "icrc1_minting_account", ()).await {
+            Ok((account,)) => account,
+            Err((_code, msg)) => {
+                return Err(OperationError::Unknown(msg));
+            }
+        };
+
+    match minting_account {
+        Some(to) => {
+            transfer(
+                deposit.ledger,
+                submitter,
+                to,
+                Nat::from(deposit.amount),
+                None,
+            )
+            .await
+        }
+        None => Err(OperationError::Unknown(String::from(
+            "The ledger has no minting account",
+        ))),
+    }
+}
+
+async fn transfer(
+    ledger: Principal,
+    submitter: &Principal,
+    to: Account,
+    amount: Nat,
+    fee: Option<Nat>,
+) -> Result<(), OperationError> {
+    let arg = TransferArg {
+        from_subaccount: Some(deposit_subaccount(submitter)),
+        to,
+        amount,
+        fee,
+        memo: None,
+        created_at_time: None,
+    };
+
+    match ic::call(ledger, "icrc1_transfer", (arg,)).await {
+        Ok((TransferResult::Ok(_),)) => Ok(()),
+        Ok((TransferResult::Err(err),)) => Err(OperationError::Unknown(format!("{:?}", err))),
+        Err((_code, msg)) => Err(OperationError::Unknown(msg)),
+    }
+}
@@ -0,0 +1,404 @@
+// Storage, admin handling and upgrade code shared by simple registries. A registry
+// implements `RegistryEntry` for its entry type and invokes `registry_canister!`, which
+// generates the standard methods around a `Registry` of that type. With the `canister`
+// feature it also holds the stores the NFT and token registries share: each module keeps
+// its endpoints as plain functions and exports a `<module>_methods!` macro that generates
+// the canister methods in the registry.
+
+mod details;
+
+#[cfg(feature = "canister")]
+pub mod alerts;
+#[cfg(feature = "canister")]
+pub mod aliases;
+#[cfg(feature = "canister")]
+pub mod assets;
+#[cfg(feature = "canister")]
+pub mod callbacks;
+#[cfg(feature = "canister")]
+pub mod curation;
+#[cfg(feature = "canister")]
+pub mod delisting;
+#[cfg(feature = "canister")]
+pub mod entries;
+#[cfg(feature = "canister")]
+pub mod guard;
+#[cfg(feature = "canister")]
+pub mod history;
+#[cfg(feature = "canister")]
+pub mod integrity;
+#[cfg(feature = "canister")]
+pub mod interner;
+#[cfg(feature = "canister")]
+pub mod ledger;
+#[cfg(feature = "canister")]
+pub mod locks;
+#[cfg(feature = "canister")]
+pub mod logger;
+#[cfg(feature = "canister")]
+pub mod maintenance;
+#[cfg(feature = "canister")]
+pub mod management;
+#[cfg(feature = "canister")]
+pub mod messages;
+#[cfg(feature = "canister")]
+pub mod metrics;
+#[cfg(feature = "canister")]
+pub mod mirror;
+#[cfg(feature = "canister")]
+pub mod pinning;
+#[cfg(feature = "canister")]
+pub mod protection;
+#[cfg(feature = "canister")]
+pub mod reports;
+#[cfg(feature = "canister")]
+pub mod requests;
+#[cfg(feature = "canister")]
+pub mod reserved;
+#[cfg(feature = "canister")]
+pub mod retries;
+#[cfg(feature = "canister")]
+pub mod sponsors;
+#[cfg(feature = "canister")]
+pub mod subscribers;
+#[cfg(feature = "canister")]
+pub mod translations;
+#[cfg(feature = "canister")]
+pub mod types;
+#[cfg(feature = "canister")]
+pub mod usage;
+
+pub use details::*;
+pub use ic_kit;
+
+use ic_kit::candid::{CandidType, Deserialize, Principal};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+
+// Most entries `get_page` returns at once
+pub const PAGE_LIMIT: u64 = 100;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum Error {
+    NotAuthorized,
+    BadParameters,
+    NonExistentItem,
+    Unknown(String),
+}
+
+// Reply of `get_v2` and `list_v2` in the registries. `schema_version` is bumped whenever the
+// shape of the entries in `data` changes, so a client can tell it's talking to a newer
+// canister than it was built for instead of failing on a decoding error.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Envelope<T> {
+    pub schema_version: u16,
+    pub data: T,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(schema_version: u16, data: T) -> Self {
+        Envelope {
+            schema_version,
+            data,
+        }
+    }
+}
+
+// An entry of a registry, keyed by the principal it describes
+pub trait RegistryEntry: CandidType + DeserializeOwned + Clone + 'static {
+    fn principal_id(&self) -> Principal;
+
+    // Checks an entry before it's stored, every entry is accepted by default
+    fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+// Kept in stable memory across upgrades
+#[derive(CandidType, Deserialize)]
+pub struct RegistryArchive<T> {
+    pub db: Vec<(Principal, T)>,
+    pub admins: Vec<Principal>,
+}
+
+// Entries ordered by principal, and the admins allowed to change them
+pub struct Registry<T> {
+    entries: BTreeMap<Principal, T>,
+    admins: Vec<Principal>,
+}
+
+impl<T> Default for Registry<T> {
+    fn default() -> Self {
+        Registry {
+            entries: BTreeMap::new(),
+            admins: vec![],
+        }
+    }
+}
+
+impl<T: RegistryEntry> Registry<T> {
+    pub fn archive(&mut self) -> RegistryArchive<T> {
+        let registry = std::mem::take(self);
+        RegistryArchive {
+            db: registry.entries.into_iter().collect(),
+            admins: registry.admins,
+        }
+    }
+
+    pub fn load(&mut self, archive: RegistryArchive<T>) {
+        self.entries = archive.db.into_iter().collect();
+        self.admins = archive.admins;
+    }
+
+    pub fn is_admin(&self, account: &Principal) -> bool {
+        self.admins.contains(account)
+    }
+
+    // Makes the installer of the canister its first admin, called from `init` only
+    pub fn init(&mut self, installer: Principal) {
+        if !self.is_admin(&installer) {
+            self.admins.push(installer);
+        }
+    }
+
+    pub fn add_admin(&mut self, caller: &Principal, account: Principal) -> Result<(), Error> {
+        if !self.is_admin(caller) {
+            return Err(Error::NotAuthorized);
+        }
+
+        if !self.is_admin(&account) {
+            self.admins.push(account);
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, principal_id: &Principal) -> Option<&T> {
+        self.entries.get(principal_id)
+    }
+
+    pub fn get_all(&self) -> Vec<&T> {
+        self.entries.values().collect()
+    }
+
+    pub fn get_page(&self, offset: u64, limit: u64) -> Vec<&T> {
+        self.entries
+            .values()
+            .skip(offset as usize)
+            .take(limit.min(PAGE_LIMIT) as usize)
+            .collect()
+    }
+
+    // Adds the entry, or replaces the one listed under the same principal
+    pub fn add(&mut self, caller: &Principal, entry: T) -> Result<(), Error> {
+        if !self.is_admin(caller) {
+            return Err(Error::NotAuthorized);
+        }
+
+        entry.validate()?;
+        self.entries.insert(entry.principal_id(), entry);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, caller: &Principal, principal_id: &Principal) -> Result<(), Error> {
+        if !self.is_admin(caller) {
+            return Err(Error::NotAuthorized);
+        }
+
+        match self.entries.remove(principal_id) {
+            Some(_) => Ok(()),
+            None => Err(Error::NonExistentItem),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// Generates the methods of a registry canister named `$name` holding `$entry` values:
+// `name`, `get`, `get_all`, `get_page`, `add`, `remove`, `set_admin`, `is_admin` and the
+// upgrade hooks. The invoking crate depends on `ic-kit` and `ic-cdk`.
+#[macro_export]
+macro_rules! registry_canister {
+    ($name:expr, $entry:ty) => {
+        type __Registry = $crate::Registry<$entry>;
+
+        #[$crate::ic_kit::macros::init]
+        fn init() {
+            let caller = $crate::ic_kit::ic::caller();
+            $crate::ic_kit::ic::get_mut::<__Registry>().init(caller);
+        }
+
+        #[$crate::ic_kit::macros::query]
+        fn name() -> String {
+            String::from($name)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        fn get(principal_id: $crate::ic_kit::Principal) -> Option<$entry> {
+            $crate::ic_kit::ic::get::<__Registry>()
+                .get(&principal_id)
+                .cloned()
+        }
+
+        #[$crate::ic_kit::macros::query]
+        fn get_all() -> Vec<$entry> {
+            $crate::ic_kit::ic::get::<__Registry>()
+                .get_all()
+                .into_iter()
+                .cloned()
+                .collect()
+        }
+
+        #[$crate::ic_kit::macros::query]
+        fn get_page(offset: u64, limit: u64) -> Vec<$entry> {
+            $crate::ic_kit::ic::get::<__Registry>()
+                .get_page(offset, limit)
+                .into_iter()
+                .cloned()
+                .collect()
+        }
+
+        #[$crate::ic_kit::macros::update]
+        fn add(entry: $entry) -> Result<(), $crate::Error> {
+            let caller = $crate::ic_kit::ic::caller();
+            $crate::ic_kit::ic::get_mut::<__Registry>().add(&caller, entry)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        fn remove(principal_id: $crate::ic_kit::Principal) -> Result<(), $crate::Error> {
+            let caller = $crate::ic_kit::ic::caller();
+            $crate::ic_kit::ic::get_mut::<__Registry>().remove(&caller, &principal_id)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        fn set_admin(account: $crate::ic_kit::Principal) -> Result<(), $crate::Error> {
+            let caller = $crate::ic_kit::ic::caller();
+            $crate::ic_kit::ic::get_mut::<__Registry>().add_admin(&caller, account)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        fn is_admin(account: $crate::ic_kit::Principal) -> bool {
+            $crate::ic_kit::ic::get::<__Registry>().is_admin(&account)
+        }
+
+        #[$crate::ic_kit::macros::pre_upgrade]
+        fn pre_upgrade() {
+            let archive = $crate::ic_kit::ic::get_mut::<__Registry>().archive();
+            if let Err(candid_err) = $crate::ic_kit::ic::stable_store((archive,)) {
+                $crate::ic_kit::ic::trap(&format!(
+                    "An error occurred when saving to stable memory (pre_upgrade): {:?}",
+                    candid_err
+                ));
+            }
+        }
+
+        #[$crate::ic_kit::macros::post_upgrade]
+        fn post_upgrade() {
+            // Failing the upgrade keeps the previous state, an empty registry would have no admins
+            match $crate::ic_kit::ic::stable_restore::<($crate::RegistryArchive<$entry>,)>() {
+                Ok((archive,)) => $crate::ic_kit::ic::get_mut::<__Registry>().load(archive),
+                Err(candid_err) => $crate::ic_kit::ic::trap(&format!(
+                    "An error occurred when restoring from stable memory (post_upgrade): {:?}",
+                    candid_err
+                )),
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+    struct Entry {
+        principal_id: Principal,
+        name: String,
+    }
+
+    impl RegistryEntry for Entry {
+        fn principal_id(&self) -> Principal {
+            self.principal_id
+        }
+
+        fn validate(&self) -> Result<(), Error> {
+            match self.name.is_empty() {
+                true => Err(Error::BadParameters),
+                false => Ok(()),
+            }
+        }
+    }
+
+    fn entry(byte: u8) -> Entry {
+        Entry {
+            principal_id: Principal::from_slice(&[byte; 29]),
+            name: String::from("entry"),
+        }
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let admin = Principal::from_slice(&[1; 29]);
+        let user = Principal::from_slice(&[2; 29]);
+        let mut registry = Registry::<Entry>::default();
+        registry.init(admin);
+
+        assert_eq!(registry.add(&user, entry(3)), Err(Error::NotAuthorized));
+        let mut unnamed = entry(3);
+        unnamed.name.clear();
+        assert_eq!(registry.add(&admin, unnamed), Err(Error::BadParameters));
+        registry.add(&admin, entry(3)).unwrap();
+        assert_eq!(registry.get(&entry(3).principal_id), Some(&entry(3)));
+
+        let principal_id = entry(3).principal_id;
+        assert_eq!(
+            registry.remove(&user, &principal_id),
+            Err(Error::NotAuthorized)
+        );
+        registry.remove(&admin, &principal_id).unwrap();
+        assert_eq!(
+            registry.remove(&admin, &principal_id),
+            Err(Error::NonExistentItem)
+        );
+    }
+
+    #[test]
+    fn test_add_admin() {
+        let admin = Principal::from_slice(&[1; 29]);
+        let user = Principal::from_slice(&[2; 29]);
+        let mut registry = Registry::<Entry>::default();
+
+        // Without an installer nobody can make themselves admin
+        assert_eq!(registry.add_admin(&user, user), Err(Error::NotAuthorized));
+        assert!(!registry.is_admin(&user));
+
+        registry.init(admin);
+        assert_eq!(registry.add_admin(&user, user), Err(Error::NotAuthorized));
+        registry.add_admin(&admin, user).unwrap();
+        assert!(registry.is_admin(&user));
+    }
+
+    #[test]
+    fn test_get_page_and_archive() {
+        let admin = Principal::from_slice(&[1; 29]);
+        let mut registry = Registry::<Entry>::default();
+        registry.init(admin);
+        for byte in 3..8 {
+            registry.add(&admin, entry(byte)).unwrap();
+        }
+
+        assert_eq!(registry.get_page(1, 2), vec![&entry(4), &entry(5)]);
+        assert_eq!(registry.get_page(4, 10), vec![&entry(7)]);
+
+        let archive = registry.archive();
+        assert!(registry.is_empty());
+        registry.load(archive);
+        assert_eq!(registry.len(), 5);
+        assert!(registry.is_admin(&admin));
+    }
+}
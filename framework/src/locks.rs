@@ -0,0 +1,79 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::HashSet;
+
+use crate::entries::EntryStore;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Entries that can't be edited or removed by anyone, admins and trusted sources included,
+// until they are unlocked
+#[derive(Default)]
+pub struct Locks(pub HashSet<Principal>);
+
+impl Locks {
+    pub fn check(&self, principal_id: &Principal) -> Result<(), OperationError> {
+        match self.0.contains(principal_id) {
+            true => Err(OperationError::EntryLocked),
+            false => Ok(()),
+        }
+    }
+}
+
+pub fn lock_entry<R: EntryStore>(principal_id: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    if ic::get::<R>().entry(&principal_id).is_none() {
+        return Err(OperationError::NonExistentItem);
+    }
+
+    ic::get_mut::<Locks>().0.insert(principal_id);
+    Ok(())
+}
+
+pub fn unlock_entry(principal_id: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    match ic::get_mut::<Locks>().0.remove(&principal_id) {
+        true => Ok(()),
+        false => Err(OperationError::NonExistentItem),
+    }
+}
+
+pub fn get_locked_entries() -> Vec<&'static Principal> {
+    ic::get::<Locks>().0.iter().collect()
+}
+
+// Generates the methods of `Locks`, `$registry` is the `EntryStore` of the registry
+#[macro_export]
+macro_rules! locks_methods {
+    ($registry:ty) => {
+        #[$crate::ic_kit::macros::update]
+        pub fn lock_entry(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::locks::lock_entry::<$registry>(principal_id)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn unlock_entry(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::locks::unlock_entry(principal_id)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_locked_entries() -> Vec<&'static $crate::ic_kit::Principal> {
+            $crate::locks::get_locked_entries()
+        }
+    };
+}
@@ -0,0 +1,142 @@
+use ic_cdk::api::call::notify;
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::VecDeque;
+
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Records kept before the oldest ones are dropped
+const LOG_CAPACITY: usize = 1_000;
+
+// Bounded buffer of the most recent log records. Every record is also sent to the sink,
+// when one is set, with a one-way call.
+#[derive(Default)]
+pub struct Logger {
+    records: VecDeque<LogRecord>,
+    pub sink: Option<(Principal, String)>,
+}
+
+impl Logger {
+    pub fn write(&mut self, level: LogLevel, endpoint: &str, caller: Principal, message: String) {
+        let record = LogRecord {
+            level,
+            timestamp: ic::time(),
+            endpoint: String::from(endpoint),
+            caller,
+            message,
+        };
+
+        // Forwarding is best effort, like alerting
+        if let Some((canister, method)) = &self.sink {
+            let _ = notify(*canister, method, (record.clone(),));
+        }
+
+        if self.records.len() >= LOG_CAPACITY {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    pub fn archive(&self) -> Vec<LogRecord> {
+        self.records.iter().cloned().collect()
+    }
+
+    pub fn load(&mut self, records: Vec<LogRecord>) {
+        self.records = records.into_iter().collect();
+    }
+
+    // Drops the oldest records until at most `keep` are left
+    pub fn trim(&mut self, keep: usize) {
+        let excess = self.records.len().saturating_sub(keep);
+        self.records.drain(..excess);
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+}
+
+pub fn log(level: LogLevel, endpoint: &str, caller: Principal, message: String) {
+    ic::get_mut::<Logger>().write(level, endpoint, caller, message);
+}
+
+// Logs a failed operation. Validation failures are warnings, failed inter-canister calls
+// surface as `Unknown` and are errors.
+pub fn log_result(endpoint: &str, caller: Principal, result: &Result<(), OperationError>) {
+    let err = match result {
+        Ok(()) => return,
+        Err(err) => err,
+    };
+    let level = match err {
+        OperationError::Unknown(_) => LogLevel::Error,
+        OperationError::Validation(_) | OperationError::BadParameters => LogLevel::Warn,
+        _ => LogLevel::Info,
+    };
+    log(level, endpoint, caller, format!("{:?}", err));
+}
+
+// Records of `level` and above, newest first
+pub fn get_logs(level: LogLevel, limit: u16) -> Result<Vec<LogRecord>, OperationError> {
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    Ok(ic::get::<Logger>()
+        .records
+        .iter()
+        .rev()
+        .filter(|record| record.level >= level)
+        .take(limit as usize)
+        .cloned()
+        .collect())
+}
+
+// `None` stops forwarding, records are still kept
+pub fn set_log_sink(sink: Option<(Principal, String)>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    if let Some((_, method)) = &sink {
+        if method.is_empty() {
+            return Err(OperationError::BadParameters);
+        }
+    }
+
+    ic::get_mut::<Logger>().sink = sink;
+    Ok(())
+}
+
+pub fn get_log_sink() -> Option<(Principal, String)> {
+    ic::get::<Logger>().sink.clone()
+}
+
+// Generates the methods of `Logger`
+#[macro_export]
+macro_rules! logger_methods {
+    () => {
+        #[$crate::ic_kit::macros::query]
+        pub fn get_logs(
+            level: $crate::types::LogLevel,
+            limit: u16,
+        ) -> Result<Vec<$crate::types::LogRecord>, $crate::types::OperationError> {
+            $crate::logger::get_logs(level, limit)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn set_log_sink(
+            sink: Option<($crate::ic_kit::Principal, String)>,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::logger::set_log_sink(sink)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_log_sink() -> Option<($crate::ic_kit::Principal, String)> {
+            $crate::logger::get_log_sink()
+        }
+    };
+}
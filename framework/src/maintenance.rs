@@ -0,0 +1,92 @@
+use ic_kit::*;
+
+use crate::guard::check_caller;
+use crate::management::*;
+use crate::types::*;
+
+// Freezes the registry data. While enabled every endpoint that changes it fails with
+// `OperationError::Maintenance`, queries and the caches refreshed in the background keep working.
+#[derive(Default)]
+pub struct Maintenance(pub bool);
+
+// Cycles balance under which writes are refused with `OperationError::LowCycles`. A canister
+// that runs out of cycles freezes, possibly between the steps of a write.
+pub struct CyclesThreshold(pub u64);
+
+impl Default for CyclesThreshold {
+    fn default() -> Self {
+        CyclesThreshold(100_000_000_000)
+    }
+}
+
+impl Maintenance {
+    // Called first by every endpoint that changes the registry data
+    pub fn check(&self) -> Result<(), OperationError> {
+        check_caller()?;
+
+        if self.0 {
+            return Err(OperationError::Maintenance);
+        } else if ic::balance() < ic::get::<CyclesThreshold>().0 {
+            return Err(OperationError::LowCycles);
+        }
+
+        Ok(())
+    }
+}
+
+pub fn set_maintenance_mode(enabled: bool) -> Result<(), OperationError> {
+    check_caller()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    ic::store(Maintenance(enabled));
+    Ok(())
+}
+
+pub fn get_maintenance_mode() -> bool {
+    ic::get::<Maintenance>().0
+}
+
+// Zero turns the guard off
+pub fn set_cycles_threshold(threshold: u64) -> Result<(), OperationError> {
+    check_caller()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    ic::store(CyclesThreshold(threshold));
+    Ok(())
+}
+
+pub fn get_cycles_threshold() -> u64 {
+    ic::get::<CyclesThreshold>().0
+}
+
+// Generates the methods of `Maintenance` and `CyclesThreshold`
+#[macro_export]
+macro_rules! maintenance_methods {
+    () => {
+        #[$crate::ic_kit::macros::update]
+        pub fn set_maintenance_mode(enabled: bool) -> Result<(), $crate::types::OperationError> {
+            $crate::maintenance::set_maintenance_mode(enabled)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_maintenance_mode() -> bool {
+            $crate::maintenance::get_maintenance_mode()
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn set_cycles_threshold(threshold: u64) -> Result<(), $crate::types::OperationError> {
+            $crate::maintenance::set_cycles_threshold(threshold)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_cycles_threshold() -> u64 {
+            $crate::maintenance::get_cycles_threshold()
+        }
+    };
+}
@@ -0,0 +1,150 @@
+use ic_kit::ic;
+use ic_kit::Principal;
+
+use crate::alerts::raise;
+use crate::guard::check_principal;
+use crate::maintenance::Maintenance;
+use crate::types::{AlertKind, ControllerProposal, OperationError, Role};
+
+pub struct Admins(pub Vec<Principal>);
+
+impl Default for Admins {
+    fn default() -> Self {
+        panic!()
+    }
+}
+
+pub fn is_admin(account: &Principal) -> bool {
+    ic::get::<Admins>().0.contains(account)
+}
+
+// Adds an admin seat, unlike `propose_controller` which hands the caller's own seat over.
+// It stays a direct call: registries created by `create_child_registry` are handed their
+// admins with it, and a new seat doesn't lock anyone out.
+pub fn add_admin(new_admin: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    check_principal(&new_admin)?;
+
+    if is_admin(&ic::caller()) {
+        if is_admin(&new_admin) {
+            return Ok(());
+        }
+        ic::get_mut::<Admins>().0.push(new_admin);
+        raise(AlertKind::AdminAdded {
+            admin: new_admin,
+            by: ic::caller(),
+        });
+        return Ok(());
+    }
+    Err(OperationError::not_authorized(Role::Admin, &ic::caller()))
+}
+
+// Admin seat offered by an admin to another principal. It only changes hands once the
+// candidate accepts, so a mistyped principal can't take over or lock admins out.
+#[derive(Default)]
+pub struct PendingController(pub Option<ControllerProposal>);
+
+// Offers the caller's admin seat to `candidate`, who can accept it once `timelock`
+// nanoseconds have passed. Replaces the pending proposal, if any.
+pub fn propose_controller(
+    candidate: Principal,
+    timelock: Option<u64>,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    check_principal(&candidate)?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if is_admin(&candidate) {
+        return Err(OperationError::BadParameters);
+    }
+
+    let now = ic::time();
+    ic::get_mut::<PendingController>().0 = Some(ControllerProposal {
+        candidate,
+        proposed_by: ic::caller(),
+        proposed_at: now,
+        accept_after: now.saturating_add(timelock.unwrap_or_default()),
+    });
+    Ok(())
+}
+
+// Called by the candidate, hands the admin seat over from the admin who proposed it
+pub fn accept_controller() -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    let proposal = match &ic::get::<PendingController>().0 {
+        Some(proposal) if proposal.candidate == ic::caller() => proposal.clone(),
+        _ => return Err(OperationError::NonExistentItem),
+    };
+
+    if ic::time() < proposal.accept_after {
+        return Err(OperationError::Timelocked(proposal.accept_after));
+    }
+
+    // The candidate may have been made admin with `add_admin` since the proposal
+    let admins = &mut ic::get_mut::<Admins>().0;
+    admins.retain(|admin| *admin != proposal.proposed_by && *admin != proposal.candidate);
+    admins.push(proposal.candidate);
+    ic::get_mut::<PendingController>().0 = None;
+
+    raise(AlertKind::AdminAdded {
+        admin: proposal.candidate,
+        by: proposal.proposed_by,
+    });
+    Ok(())
+}
+
+pub fn cancel_controller_proposal() -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    match ic::get_mut::<PendingController>().0.take() {
+        Some(_) => Ok(()),
+        None => Err(OperationError::NonExistentItem),
+    }
+}
+
+pub fn get_controller_proposal() -> Option<ControllerProposal> {
+    ic::get::<PendingController>().0.clone()
+}
+
+// Generates the methods of `Admins` and `PendingController`
+#[macro_export]
+macro_rules! management_methods {
+    () => {
+        #[$crate::ic_kit::macros::update]
+        pub fn add_admin(
+            new_admin: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::management::add_admin(new_admin)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn propose_controller(
+            candidate: $crate::ic_kit::Principal,
+            timelock: Option<u64>,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::management::propose_controller(candidate, timelock)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn accept_controller() -> Result<(), $crate::types::OperationError> {
+            $crate::management::accept_controller()
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn cancel_controller_proposal() -> Result<(), $crate::types::OperationError> {
+            $crate::management::cancel_controller_proposal()
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_controller_proposal() -> Option<$crate::types::ControllerProposal> {
+            $crate::management::get_controller_proposal()
+        }
+    };
+}
@@ -0,0 +1,168 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::HashMap;
+
+use crate::guard::check_caller;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Longest language tag accepted, e.g. "pt-BR"
+const LANGUAGE_TAG_LIMIT: usize = 35;
+
+// Translations of the human readable strings inside errors, for wallets showing them as
+// they are. Admins keep a catalog per language keyed by the English text, and callers pick
+// their language with `set_error_language`. Strings missing from the catalog stay as they are.
+#[derive(Default)]
+pub struct Messages {
+    catalogs: HashMap<String, HashMap<String, String>>,
+    languages: HashMap<Principal, String>,
+}
+
+impl Messages {
+    pub fn archive(&mut self) -> MessagesArchive {
+        let messages = std::mem::take(self);
+        MessagesArchive {
+            catalogs: messages
+                .catalogs
+                .into_iter()
+                .map(|(lang, catalog)| (lang, catalog.into_iter().collect()))
+                .collect(),
+            languages: messages.languages.into_iter().collect(),
+        }
+    }
+
+    pub fn load(&mut self, archive: MessagesArchive) {
+        self.catalogs = archive
+            .catalogs
+            .into_iter()
+            .map(|(lang, catalog)| (lang, catalog.into_iter().collect()))
+            .collect();
+        self.languages = archive.languages.into_iter().collect();
+    }
+
+    // Looks up `lang`, then its base language ("pt" for "pt-BR")
+    fn translate(&self, lang: &str, text: &str) -> Option<&String> {
+        let find = |lang: &str| self.catalogs.get(&lang.to_lowercase())?.get(text);
+        find(lang).or_else(|| find(lang.split('-').next().unwrap_or(lang)))
+    }
+
+    pub fn localize(&self, caller: &Principal, err: OperationError) -> OperationError {
+        let lang = match self.languages.get(caller) {
+            Some(lang) => lang,
+            None => return err,
+        };
+        let translate = |text: String| self.translate(lang, &text).cloned().unwrap_or(text);
+
+        match err {
+            OperationError::Validation(errors) => OperationError::Validation(
+                errors
+                    .into_iter()
+                    .map(|error| FieldError {
+                        reason: translate(error.reason),
+                        ..error
+                    })
+                    .collect(),
+            ),
+            OperationError::Unknown(msg) => OperationError::Unknown(translate(msg)),
+            err => err,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.languages.len()
+    }
+}
+
+// Translates the strings of a failed call to the language of the caller
+pub fn localize(result: Result<(), OperationError>) -> Result<(), OperationError> {
+    result.map_err(|err| ic::get::<Messages>().localize(&ic::caller(), err))
+}
+
+fn check_language(lang: &str) -> Result<(), OperationError> {
+    match lang.is_empty() || lang.len() > LANGUAGE_TAG_LIMIT {
+        true => Err(OperationError::BadParameters),
+        false => Ok(()),
+    }
+}
+
+// Replaces the catalog of `lang`, pairs of an English string and its translation. An empty
+// catalog removes the language.
+pub fn set_message_catalog(
+    lang: String,
+    messages: Vec<(String, String)>,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+    check_language(&lang)?;
+
+    let catalogs = &mut ic::get_mut::<Messages>().catalogs;
+    match messages.is_empty() {
+        true => catalogs.remove(&lang.to_lowercase()),
+        false => catalogs.insert(lang.to_lowercase(), messages.into_iter().collect()),
+    };
+    Ok(())
+}
+
+pub fn get_message_catalog(lang: String) -> Vec<(String, String)> {
+    ic::get::<Messages>()
+        .catalogs
+        .get(&lang.to_lowercase())
+        .map(|catalog| catalog.clone().into_iter().collect())
+        .unwrap_or_default()
+}
+
+// Language errors returned to the caller are translated to, `None` keeps them in English
+pub fn set_error_language(lang: Option<String>) -> Result<(), OperationError> {
+    check_caller()?;
+
+    let languages = &mut ic::get_mut::<Messages>().languages;
+    match lang {
+        Some(lang) => {
+            check_language(&lang)?;
+            languages.insert(ic::caller(), lang);
+        }
+        None => {
+            languages.remove(&ic::caller());
+        }
+    }
+    Ok(())
+}
+
+pub fn get_error_language() -> Option<String> {
+    ic::get::<Messages>().languages.get(&ic::caller()).cloned()
+}
+
+// Generates the methods of `Messages`
+#[macro_export]
+macro_rules! messages_methods {
+    () => {
+        #[$crate::ic_kit::macros::update]
+        pub fn set_message_catalog(
+            lang: String,
+            messages: Vec<(String, String)>,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::messages::set_message_catalog(lang, messages)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_message_catalog(lang: String) -> Vec<(String, String)> {
+            $crate::messages::get_message_catalog(lang)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn set_error_language(
+            lang: Option<String>,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::messages::set_error_language(lang)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_error_language() -> Option<String> {
+            $crate::messages::get_error_language()
+        }
+    };
+}
@@ -0,0 +1,98 @@
+use ic_kit::*;
+use std::collections::BTreeMap;
+
+use crate::types::*;
+
+// Minimum time between two snapshots taken from the heartbeat
+const COLLECT_INTERVAL: u64 = 60 * 1_000_000_000;
+
+// Counters and gauges in the shape IC monitoring tools scrape. Update calls are counted per
+// endpoint since the canister was installed, through `Usage::record`. Gauges are read when
+// the heartbeat takes a snapshot, so every sample of `get_metrics` is from the same instant.
+#[derive(Default)]
+pub struct Metrics {
+    calls: BTreeMap<String, u64>,
+    snapshot: Option<CanisterMetrics>,
+}
+
+fn metric(name: &str, labels: Vec<(&str, String)>, value: u64) -> Metric {
+    Metric {
+        name: String::from(name),
+        labels: labels
+            .into_iter()
+            .map(|(key, value)| (String::from(key), value))
+            .collect(),
+        value,
+    }
+}
+
+impl Metrics {
+    pub fn archive(&self) -> Vec<(String, u64)> {
+        self.calls.clone().into_iter().collect()
+    }
+
+    pub fn load(&mut self, archive: Vec<(String, u64)>) {
+        self.calls = archive.into_iter().collect();
+    }
+
+    pub fn count(&mut self, endpoint: &str) {
+        *self.calls.entry(String::from(endpoint)).or_default() += 1;
+    }
+
+    // Takes a snapshot once the last one is `COLLECT_INTERVAL` old, `report` reads the memory
+    // gauges of the registry. Called from the heartbeat.
+    pub fn tick(&mut self, report: fn() -> MemoryReport) {
+        let due = self.snapshot.as_ref().map_or(true, |snapshot| {
+            ic::time() >= snapshot.collected_at + COLLECT_INTERVAL
+        });
+        if due {
+            self.snapshot = Some(self.collect(report()));
+        }
+    }
+
+    fn collect(&self, memory: MemoryReport) -> CanisterMetrics {
+        let mut gauges = vec![
+            metric("heap_size_bytes", vec![], memory.heap_size),
+            metric("stable_memory_pages", vec![], memory.stable_pages),
+            metric("cycles_balance", vec![], ic::balance()),
+        ];
+        gauges.extend(
+            memory
+                .stores
+                .into_iter()
+                .map(|(store, len)| metric("store_size", vec![("store", store)], len)),
+        );
+
+        CanisterMetrics {
+            collected_at: ic::time(),
+            counters: self
+                .calls
+                .iter()
+                .map(|(endpoint, calls)| {
+                    metric("update_calls", vec![("endpoint", endpoint.clone())], *calls)
+                })
+                .collect(),
+            gauges,
+        }
+    }
+}
+
+// The last snapshot of the heartbeat, or a fresh one before the first
+pub fn get_metrics(report: fn() -> MemoryReport) -> CanisterMetrics {
+    let metrics = ic::get::<Metrics>();
+    metrics
+        .snapshot
+        .clone()
+        .unwrap_or_else(|| metrics.collect(report()))
+}
+
+// Generates the methods of `Metrics`, `$report` reads the memory gauges of the registry
+#[macro_export]
+macro_rules! metrics_methods {
+    ($report:path) => {
+        #[$crate::ic_kit::macros::query]
+        pub fn get_metrics() -> $crate::types::CanisterMetrics {
+            $crate::metrics::get_metrics($report)
+        }
+    };
+}
@@ -0,0 +1,249 @@
+use ic_kit::candid::{encode_one, Principal};
+use ic_kit::*;
+use std::collections::HashSet;
+
+use crate::entries::{EntryStore, ListedEntry};
+use crate::logger::log;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Minimum time between two pulls from the upstream registry
+const SYNC_INTERVAL: u64 = 60_000_000_000;
+const SYNC_BATCH: u16 = 100;
+
+// Follower mode. While an upstream registry is set, this canister loads a snapshot of
+// the upstream entries, then replays the upstream change log into its own database
+// and rejects local edits.
+#[derive(Default)]
+pub struct Mirror {
+    pub status: MirrorStatus,
+    syncing: bool,
+    // Whether the local entries were loaded from the upstream. Not kept across upgrades,
+    // the snapshot is taken again, it only writes the entries that differ.
+    loaded: bool,
+}
+
+impl Mirror {
+    pub fn check_writable(&self) -> Result<(), OperationError> {
+        match self.status.upstream {
+            Some(upstream) => Err(OperationError::ReadOnlyMirror(upstream)),
+            None => Ok(()),
+        }
+    }
+
+    // Starts a pull into `R` if one is due. Called from the heartbeat.
+    pub fn tick<R: EntryStore>(&mut self) {
+        let upstream = match self.status.upstream {
+            Some(upstream) => upstream,
+            None => return,
+        };
+
+        // Replaying upstream changes would write to a frozen registry
+        if self.syncing
+            || ic::get::<Maintenance>().0
+            || ic::time() < self.status.last_sync_at + SYNC_INTERVAL
+        {
+            return;
+        }
+
+        self.syncing = true;
+        if self.loaded {
+            ic_cdk::spawn(sync::<R>(upstream));
+        } else {
+            ic_cdk::spawn(load::<R>(upstream));
+        }
+    }
+
+    fn fail(&mut self, upstream: Principal, method: &str, reason: String) {
+        let message = format!("{} of {} failed: {}", method, upstream, reason);
+        log(LogLevel::Error, "sync", ic::id(), message);
+        self.status.last_error = Some(reason);
+    }
+}
+
+// Id of the event the upstream log can be followed from, every later event is kept
+async fn oldest_event_id<T: ListedEntry>(upstream: Principal) -> Result<u64, String> {
+    let result: Result<(Result<Vec<Event<T>>, OperationError>,), _> =
+        ic::call(upstream, "get_changes", (0u64, 1u16)).await;
+
+    match result {
+        Ok((Ok(_),)) => Ok(0),
+        Ok((Err(OperationError::HistoryTruncated(first_id)),)) => Ok(first_id - 1),
+        Ok((Err(error),)) => Err(format!("{:?}", error)),
+        Err((_code, msg)) => Err(msg),
+    }
+}
+
+async fn snapshot<T: ListedEntry>(upstream: Principal) -> Result<Vec<T>, String> {
+    let (principals,): (Vec<Principal>,) = ic::call(upstream, "get_all_principals", ())
+        .await
+        .map_err(|(_code, msg)| msg)?;
+
+    let mut entries = Vec::with_capacity(principals.len());
+    for principal_id in principals {
+        let (entry,): (Option<T>,) = ic::call(upstream, "get", (principal_id,))
+            .await
+            .map_err(|(_code, msg)| msg)?;
+        entries.extend(entry);
+    }
+
+    Ok(entries)
+}
+
+// Replaces the local entries with the upstream ones. The events kept upstream when the
+// snapshot starts are replayed afterwards, replaying an event the snapshot already holds
+// rewrites the entry with an older version until the later events are replayed too.
+async fn load<R: EntryStore>(upstream: Principal) {
+    let result = match oldest_event_id::<R::Entry>(upstream).await {
+        Ok(since_event_id) => snapshot::<R::Entry>(upstream)
+            .await
+            .map(|entries| (since_event_id, entries)),
+        Err(reason) => Err(reason),
+    };
+
+    let mirror = ic::get_mut::<Mirror>();
+    mirror.syncing = false;
+    mirror.status.last_sync_at = ic::time();
+
+    // The upstream may have been replaced while the calls were in flight
+    if mirror.status.upstream != Some(upstream) {
+        return;
+    }
+
+    let (since_event_id, entries) = match result {
+        Ok(snapshot) => snapshot,
+        Err(reason) => return mirror.fail(upstream, "snapshot", reason),
+    };
+
+    let db = ic::get_mut::<R>();
+    let kept: HashSet<Principal> = entries.iter().map(|entry| entry.principal_id()).collect();
+    for principal_id in db.principals() {
+        if !kept.contains(&principal_id) {
+            remove(db, &principal_id);
+        }
+    }
+    for entry in entries {
+        let kind = match db.entry(&entry.principal_id()) {
+            Some(current) if encode_one(current).ok() == encode_one(&entry).ok() => continue,
+            Some(_) => EventKind::Update,
+            None => EventKind::Add,
+        };
+        db.replay(local_event(kind, entry));
+    }
+
+    mirror.loaded = true;
+    mirror.status.last_event_id = since_event_id;
+    mirror.status.last_error = None;
+}
+
+async fn sync<R: EntryStore>(upstream: Principal) {
+    let since_event_id = ic::get::<Mirror>().status.last_event_id;
+    let result: Result<(Result<Vec<Event<R::Entry>>, OperationError>,), _> =
+        ic::call(upstream, "get_changes", (since_event_id, SYNC_BATCH)).await;
+
+    let mirror = ic::get_mut::<Mirror>();
+    mirror.syncing = false;
+    mirror.status.last_sync_at = ic::time();
+
+    // The upstream may have been replaced while the call was in flight
+    if mirror.status.upstream != Some(upstream) {
+        return;
+    }
+
+    let events = match result {
+        Ok((Ok(events),)) => events,
+        Ok((Err(error),)) => {
+            if let OperationError::HistoryTruncated(_) = error {
+                mirror.loaded = false;
+            }
+            return mirror.fail(upstream, "get_changes", format!("{:?}", error));
+        }
+        Err((_code, msg)) => return mirror.fail(upstream, "get_changes", msg),
+    };
+
+    // Events were skipped, only a new snapshot brings the entries they changed
+    if let Some(first) = events.first() {
+        if first.id > since_event_id + 1 {
+            mirror.loaded = false;
+            let reason = format!("expected event {}, got {}", since_event_id + 1, first.id);
+            return mirror.fail(upstream, "get_changes", reason);
+        }
+    }
+
+    mirror.status.last_error = None;
+    let db = ic::get_mut::<R>();
+    for event in events {
+        mirror.status.last_event_id = event.id;
+        db.replay(event);
+    }
+}
+
+// Events built by the mirror itself, `replay` only looks at their kind and entry
+fn local_event<T>(kind: EventKind, entry: T) -> Event<T> {
+    Event {
+        id: 0,
+        time: ic::time(),
+        kind,
+        entry,
+    }
+}
+
+fn remove<R: EntryStore>(db: &mut R, principal_id: &Principal) {
+    if let Some(entry) = db.entry(principal_id).cloned() {
+        db.replay(local_event(EventKind::Remove, entry));
+    }
+}
+
+// Turns this canister into a read-only mirror of `upstream`, or back into a
+// regular registry when `None` is given. The entries of a previous upstream are
+// dropped, a mirror turned back into a registry keeps them.
+pub fn set_upstream<R: EntryStore>(upstream: Option<Principal>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    let mirror = ic::get_mut::<Mirror>();
+    if mirror.status.upstream == upstream {
+        return Ok(());
+    }
+
+    mirror.status = MirrorStatus {
+        upstream,
+        ..MirrorStatus::default()
+    };
+    mirror.loaded = false;
+
+    if upstream.is_some() {
+        let db = ic::get_mut::<R>();
+        for principal_id in db.principals() {
+            remove(db, &principal_id);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn get_mirror_status() -> MirrorStatus {
+    ic::get::<Mirror>().status.clone()
+}
+
+// Generates the methods of `Mirror`
+#[macro_export]
+macro_rules! mirror_methods {
+    ($registry:ty) => {
+        #[$crate::ic_kit::macros::update]
+        pub fn set_upstream(
+            upstream: Option<$crate::ic_kit::Principal>,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::mirror::set_upstream::<$registry>(upstream)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_mirror_status() -> $crate::types::MirrorStatus {
+            $crate::mirror::get_mirror_status()
+        }
+    };
+}
@@ -0,0 +1,98 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+
+use crate::entries::EntryStore;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Entries featured by admins, in the order clients should show them
+#[derive(Default)]
+pub struct Pins(pub Vec<Principal>);
+
+impl Pins {
+    // Moves the entry to `position`, or to the end when `position` is past it
+    pub fn pin(&mut self, principal_id: Principal, position: u32) -> Result<(), OperationError> {
+        if !self.0.contains(&principal_id) && self.0.len() >= PINNED_LIMIT {
+            return Err(OperationError::BadParameters);
+        }
+
+        self.0.retain(|pinned| *pinned != principal_id);
+        let position = std::cmp::min(position as usize, self.0.len());
+        self.0.insert(position, principal_id);
+        Ok(())
+    }
+
+    pub fn unpin(&mut self, principal_id: &Principal) -> bool {
+        let pinned = self.0.len();
+        self.0.retain(|other| other != principal_id);
+        self.0.len() != pinned
+    }
+}
+
+// `position` starts at 0, entries already pinned are moved
+pub fn pin_entry<R: EntryStore>(
+    principal_id: Principal,
+    position: u32,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    if ic::get::<R>().entry(&principal_id).is_none() {
+        return Err(OperationError::NonExistentItem);
+    }
+
+    ic::get_mut::<Pins>().pin(principal_id, position)
+}
+
+pub fn unpin_entry(principal_id: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    match ic::get_mut::<Pins>().unpin(&principal_id) {
+        true => Ok(()),
+        false => Err(OperationError::NonExistentItem),
+    }
+}
+
+// Pinned entries in order, for a "featured" section
+pub fn get_featured<R: EntryStore>() -> Vec<&'static R::Entry> {
+    let db = ic::get::<R>();
+    ic::get::<Pins>()
+        .0
+        .iter()
+        .filter_map(|principal_id| db.entry(principal_id))
+        .collect()
+}
+
+// Generates the methods of `Pins`, `$registry` is the `EntryStore` of the registry
+#[macro_export]
+macro_rules! pinning_methods {
+    ($registry:ty) => {
+        #[$crate::ic_kit::macros::update]
+        pub fn pin_entry(
+            principal_id: $crate::ic_kit::Principal,
+            position: u32,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::pinning::pin_entry::<$registry>(principal_id, position)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn unpin_entry(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::pinning::unpin_entry(principal_id)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_featured() -> Vec<&'static <$registry as $crate::entries::EntryStore>::Entry> {
+            $crate::pinning::get_featured::<$registry>()
+        }
+    };
+}
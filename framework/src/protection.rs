@@ -0,0 +1,218 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+
+use crate::entries::EntryStore;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Entries under a two-admin rule: edits and removals made by one admin wait as a proposal
+// until another admin confirms them, so a single mistaken or rogue call can't apply. `I` is
+// the input the registry adds entries from.
+pub struct Protection<I> {
+    protected: HashSet<Principal>,
+    proposals: BTreeMap<u64, ChangeProposal<I>>,
+    next_id: u64,
+}
+
+impl<I> Default for Protection<I> {
+    fn default() -> Self {
+        Protection {
+            protected: HashSet::new(),
+            proposals: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl<I> Protection<I> {
+    pub fn archive(&mut self) -> ProtectionArchive<I> {
+        let protection = std::mem::take(self);
+        ProtectionArchive {
+            protected: protection.protected.into_iter().collect(),
+            proposals: protection.proposals.into_values().collect(),
+            next_id: protection.next_id,
+        }
+    }
+
+    pub fn load(&mut self, archive: ProtectionArchive<I>) {
+        self.protected = archive.protected.into_iter().collect();
+        self.proposals = archive
+            .proposals
+            .into_iter()
+            .map(|proposal| (proposal.id, proposal))
+            .collect();
+        self.next_id = archive.next_id;
+    }
+
+    pub fn is_protected(&self, principal_id: &Principal) -> bool {
+        self.protected.contains(principal_id)
+    }
+
+    // Records a change to a protected entry as a proposal, returning the error reporting it
+    pub fn defer(
+        &mut self,
+        principal_id: Principal,
+        trusted_source: Option<Principal>,
+        change: ProposedChange<I>,
+    ) -> OperationError {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.proposals.insert(
+            id,
+            ChangeProposal {
+                id,
+                principal_id,
+                trusted_source,
+                change,
+                proposed_by: ic::caller(),
+                proposed_at: ic::time(),
+            },
+        );
+        OperationError::PendingApproval(id)
+    }
+
+    pub fn get_all(&self) -> Vec<&ChangeProposal<I>> {
+        self.proposals.values().collect()
+    }
+}
+
+fn check_admin() -> Result<(), OperationError> {
+    match is_admin(&ic::caller()) {
+        true => Ok(()),
+        false => Err(OperationError::not_authorized(Role::Admin, &ic::caller())),
+    }
+}
+
+pub fn protect_entry<R: EntryStore, I: 'static>(
+    principal_id: Principal,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    check_admin()?;
+
+    if ic::get::<R>().entry(&principal_id).is_none() {
+        return Err(OperationError::NonExistentItem);
+    }
+
+    ic::get_mut::<Protection<I>>()
+        .protected
+        .insert(principal_id);
+    Ok(())
+}
+
+// Lifting the protection is itself a change a second admin has to confirm
+pub fn unprotect_entry<I: 'static>(principal_id: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    check_admin()?;
+
+    let protection = ic::get_mut::<Protection<I>>();
+    if !protection.is_protected(&principal_id) {
+        return Err(OperationError::NonExistentItem);
+    }
+
+    Err(protection.defer(principal_id, None, ProposedChange::Unprotect))
+}
+
+// Applies a proposal made by another admin with `apply`, which runs the change on the registry
+pub async fn confirm_change<I, F>(
+    proposal_id: u64,
+    apply: fn(ChangeProposal<I>) -> F,
+) -> Result<(), OperationError>
+where
+    I: Clone + 'static,
+    F: Future<Output = Result<(), OperationError>>,
+{
+    ic::get::<Maintenance>().check()?;
+    check_admin()?;
+
+    let protection = ic::get_mut::<Protection<I>>();
+    match protection.proposals.get(&proposal_id) {
+        Some(proposal) if proposal.proposed_by == ic::caller() => {
+            return Err(OperationError::BadParameters)
+        }
+        Some(_) => {}
+        None => return Err(OperationError::NonExistentItem),
+    }
+    let proposal = protection.proposals.remove(&proposal_id).unwrap();
+
+    let result = apply(proposal.clone()).await;
+
+    let protection = ic::get_mut::<Protection<I>>();
+    match result {
+        Ok(()) => {
+            if let ProposedChange::Remove | ProposedChange::Unprotect = proposal.change {
+                protection.protected.remove(&proposal.principal_id);
+            }
+            Ok(())
+        }
+        // Kept so it can be confirmed again once the cause is fixed, or rejected
+        Err(err) => {
+            protection.proposals.insert(proposal_id, proposal);
+            Err(err)
+        }
+    }
+}
+
+pub fn reject_change<I: 'static>(proposal_id: u64) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    check_admin()?;
+
+    match ic::get_mut::<Protection<I>>()
+        .proposals
+        .remove(&proposal_id)
+    {
+        Some(_) => Ok(()),
+        None => Err(OperationError::NonExistentItem),
+    }
+}
+
+pub fn get_change_proposals<I: 'static>() -> Vec<&'static ChangeProposal<I>> {
+    ic::get::<Protection<I>>().get_all()
+}
+
+pub fn get_protected_entries<I: 'static>() -> Vec<&'static Principal> {
+    ic::get::<Protection<I>>().protected.iter().collect()
+}
+
+// Generates the methods of `Protection`. `$registry` is the `EntryStore` of the registry,
+// `$input` the input it adds entries from and `$apply` runs a confirmed change on it.
+#[macro_export]
+macro_rules! protection_methods {
+    ($registry:ty, $input:ty, $apply:path) => {
+        #[$crate::ic_kit::macros::update]
+        pub fn protect_entry(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::protection::protect_entry::<$registry, $input>(principal_id)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn unprotect_entry(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::protection::unprotect_entry::<$input>(principal_id)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub async fn confirm_change(proposal_id: u64) -> Result<(), $crate::types::OperationError> {
+            $crate::protection::confirm_change::<$input, _>(proposal_id, $apply).await
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn reject_change(proposal_id: u64) -> Result<(), $crate::types::OperationError> {
+            $crate::protection::reject_change::<$input>(proposal_id)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_change_proposals() -> Vec<&'static $crate::types::ChangeProposal<$input>> {
+            $crate::protection::get_change_proposals::<$input>()
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_protected_entries() -> Vec<&'static $crate::ic_kit::Principal> {
+            $crate::protection::get_protected_entries::<$input>()
+        }
+    };
+}
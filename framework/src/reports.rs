@@ -0,0 +1,209 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::alerts::raise;
+use crate::callbacks::notify_owner;
+use crate::curation::Curation;
+use crate::entries::EntryStore;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Window over which the reports of a caller are counted
+const REPORT_WINDOW: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+// Reports filed by the community against listed entries. An entry is under review once
+// `threshold` distinct callers reported it, until a moderator dismisses its reports.
+pub struct Reports {
+    entries: BTreeMap<Principal, Vec<Report>>,
+    threshold: u32,
+    // Times of the recent reports of each caller, only kept for rate limiting
+    recent: HashMap<Principal, Vec<u64>>,
+}
+
+impl Default for Reports {
+    fn default() -> Self {
+        Reports {
+            entries: BTreeMap::new(),
+            threshold: DEFAULT_REPORT_THRESHOLD,
+            recent: HashMap::new(),
+        }
+    }
+}
+
+fn is_moderator(account: &Principal) -> bool {
+    is_admin(account) || ic::get::<Curation>().is_curator(account)
+}
+
+impl Reports {
+    pub fn archive(&mut self) -> ReportsArchive {
+        let reports = std::mem::take(self);
+        ReportsArchive {
+            entries: reports.entries.into_iter().collect(),
+            threshold: reports.threshold,
+        }
+    }
+
+    pub fn load(&mut self, archive: ReportsArchive) {
+        self.entries = archive.entries.into_iter().collect();
+        self.threshold = archive.threshold;
+    }
+
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    pub fn is_under_review(&self, principal_id: &Principal) -> bool {
+        self.entries
+            .get(principal_id)
+            .map_or(false, |reports| reports.len() as u32 >= self.threshold)
+    }
+
+    // Fails with the time the caller can report again once it used up its reports
+    fn limit(&mut self, reporter: Principal) -> Result<(), OperationError> {
+        let now = ic::time();
+        let recent = self.recent.entry(reporter).or_default();
+        recent.retain(|time| now.saturating_sub(*time) < REPORT_WINDOW);
+
+        if recent.len() >= REPORTS_PER_WINDOW {
+            return Err(OperationError::Timelocked(recent[0] + REPORT_WINDOW));
+        }
+        recent.push(now);
+        Ok(())
+    }
+
+    // Records the report, replacing any earlier report of the caller on the same entry
+    pub fn report(
+        &mut self,
+        reporter: Principal,
+        principal_id: Principal,
+        reason: String,
+    ) -> Result<(), OperationError> {
+        self.limit(reporter)?;
+
+        let was_under_review = self.is_under_review(&principal_id);
+        let reports = self.entries.entry(principal_id).or_default();
+        reports.retain(|report| report.reporter != reporter);
+        reports.push(Report {
+            reporter,
+            reason,
+            reported_at: ic::time(),
+        });
+
+        if !was_under_review && self.is_under_review(&principal_id) {
+            raise(AlertKind::EntryUnderReview { principal_id });
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self, principal_id: &Principal) -> bool {
+        self.entries.remove(principal_id).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+// Open to any caller but the anonymous principal, each can file `REPORTS_PER_WINDOW` reports
+// a day
+pub fn report_entry<R: EntryStore>(
+    principal_id: Principal,
+    reason: String,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    let reason = reason.trim().to_string();
+    if reason.is_empty() || reason.len() > REPORT_REASON_LIMIT {
+        return Err(OperationError::BadParameters);
+    } else if ic::get::<R>().entry(&principal_id).is_none() {
+        return Err(OperationError::NonExistentItem);
+    }
+
+    ic::get_mut::<Reports>().report(ic::caller(), principal_id, reason)?;
+    notify_owner(principal_id, EntryStatus::Reported);
+    Ok(())
+}
+
+// Reported entries for admins and curators, those under review first
+pub fn get_reported_entries() -> Result<Vec<ReportedEntry>, OperationError> {
+    if !is_moderator(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Curator, &ic::caller()));
+    }
+
+    let reports = ic::get::<Reports>();
+    let mut entries: Vec<ReportedEntry> = reports
+        .entries
+        .iter()
+        .map(|(principal_id, entry_reports)| ReportedEntry {
+            principal_id: *principal_id,
+            status: match reports.is_under_review(principal_id) {
+                true => ReportStatus::UnderReview,
+                false => ReportStatus::Reported,
+            },
+            reports: entry_reports.clone(),
+        })
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.reports.len()));
+    Ok(entries)
+}
+
+// Clears the reports of an entry a moderator found legitimate
+pub fn dismiss_reports(principal_id: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_moderator(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Curator, &ic::caller()));
+    }
+
+    match ic::get_mut::<Reports>().clear(&principal_id) {
+        true => Ok(()),
+        false => Err(OperationError::NonExistentItem),
+    }
+}
+
+pub fn set_report_threshold(threshold: u32) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if threshold == 0 {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::get_mut::<Reports>().threshold = threshold;
+    Ok(())
+}
+
+// Generates the methods of `Reports`, `$registry` is the `EntryStore` of the registry
+#[macro_export]
+macro_rules! reports_methods {
+    ($registry:ty) => {
+        #[$crate::ic_kit::macros::update]
+        pub fn report_entry(
+            principal_id: $crate::ic_kit::Principal,
+            reason: String,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::reports::report_entry::<$registry>(principal_id, reason)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_reported_entries(
+        ) -> Result<Vec<$crate::types::ReportedEntry>, $crate::types::OperationError> {
+            $crate::reports::get_reported_entries()
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn dismiss_reports(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::reports::dismiss_reports(principal_id)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn set_report_threshold(threshold: u32) -> Result<(), $crate::types::OperationError> {
+            $crate::reports::set_report_threshold(threshold)
+        }
+    };
+}
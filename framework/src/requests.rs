@@ -0,0 +1,103 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::{HashMap, VecDeque};
+
+use crate::types::*;
+
+// Results are kept for a day, or until the cache is full
+const REQUEST_TTL: u64 = 24 * 60 * 60 * 1_000_000_000;
+const REQUESTS_LIMIT: usize = 10_000;
+const REQUEST_ID_LENGTH: usize = 32;
+
+// Outcome of the update calls made with a request id, so a retried call returns
+// the result of the first one instead of being applied twice.
+#[derive(Default)]
+pub struct Requests {
+    results: HashMap<(Principal, Vec<u8>), ProcessedRequest>,
+    // Keys in the order they were first seen, for expiry
+    order: VecDeque<(Principal, Vec<u8>)>,
+}
+
+impl Requests {
+    pub fn archive(&mut self) -> Vec<ProcessedRequest> {
+        let Requests { mut results, order } = std::mem::take(self);
+        order
+            .into_iter()
+            .filter_map(|key| results.remove(&key))
+            .collect()
+    }
+
+    pub fn load(&mut self, archive: Vec<ProcessedRequest>) {
+        for request in archive {
+            let key = (request.caller, request.request_id.clone());
+            self.order.push_back(key.clone());
+            self.results.insert(key, request);
+        }
+    }
+
+    pub fn expire(&mut self) {
+        let now = ic::time();
+        while let Some(key) = self.order.front() {
+            let expired = match self.results.get(key) {
+                Some(request) => request.at + REQUEST_TTL < now,
+                None => true,
+            };
+
+            if !expired && self.order.len() < REQUESTS_LIMIT {
+                break;
+            }
+
+            let key = self.order.pop_front().unwrap();
+            self.results.remove(&key);
+        }
+    }
+
+    // Returns the result to answer with right away when the request was already seen,
+    // otherwise marks it as in flight until `finish` is called
+    pub fn start(
+        &mut self,
+        caller: &Principal,
+        request_id: &Option<Vec<u8>>,
+    ) -> Option<Result<(), OperationError>> {
+        let request_id = request_id.as_ref()?;
+        if request_id.len() != REQUEST_ID_LENGTH {
+            return Some(Err(OperationError::BadParameters));
+        }
+
+        self.expire();
+
+        let key = (*caller, request_id.clone());
+        if let Some(request) = self.results.get(&key) {
+            return Some(request.result.clone().unwrap_or_else(|| {
+                Err(OperationError::Unknown(String::from(
+                    "The request is still being processed",
+                )))
+            }));
+        }
+
+        self.order.push_back(key.clone());
+        self.results.insert(
+            key,
+            ProcessedRequest {
+                caller: *caller,
+                request_id: request_id.clone(),
+                at: ic::time(),
+                result: None,
+            },
+        );
+        None
+    }
+
+    pub fn finish(
+        &mut self,
+        caller: &Principal,
+        request_id: &Option<Vec<u8>>,
+        result: &Result<(), OperationError>,
+    ) {
+        if let Some(request_id) = request_id {
+            if let Some(request) = self.results.get_mut(&(*caller, request_id.clone())) {
+                request.result = Some(result.clone());
+            }
+        }
+    }
+}
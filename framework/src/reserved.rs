@@ -0,0 +1,118 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use regex::RegexBuilder;
+
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Names kept for well-known projects so they can't be impersonated
+#[derive(Default)]
+pub struct ReservedNames(pub Vec<ReservedName>);
+
+impl ReservedName {
+    fn matches(&self, name: &str) -> bool {
+        let name = name.trim();
+        if !self.is_pattern {
+            return self.name.eq_ignore_ascii_case(name);
+        }
+
+        RegexBuilder::new(&self.name)
+            .case_insensitive(true)
+            .build()
+            .map(|pattern| pattern.is_match(name))
+            .unwrap_or(false)
+    }
+}
+
+impl ReservedNames {
+    // Admins may use any name, everyone else only the names reserved for them
+    pub fn check(
+        &self,
+        name: &str,
+        submitter: &Principal,
+        principal_id: &Principal,
+    ) -> Result<(), OperationError> {
+        if is_admin(submitter) {
+            return Ok(());
+        }
+
+        match self.0.iter().find(|reserved| {
+            reserved.matches(name)
+                && !reserved.allowed.contains(submitter)
+                && !reserved.allowed.contains(principal_id)
+        }) {
+            Some(reserved) => Err(OperationError::ReservedName(reserved.name.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn reserve_name(
+    name: String,
+    is_pattern: bool,
+    allowed: Vec<Principal>,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if name.trim().is_empty() || (is_pattern && RegexBuilder::new(&name).build().is_err()) {
+        return Err(OperationError::BadParameters);
+    }
+
+    let reserved = ic::get_mut::<ReservedNames>();
+    reserved.0.retain(|reserved| reserved.name != name);
+    reserved.0.push(ReservedName {
+        name,
+        is_pattern,
+        allowed,
+    });
+    Ok(())
+}
+
+pub fn release_name(name: String) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    let reserved = ic::get_mut::<ReservedNames>();
+    let count = reserved.0.len();
+    reserved.0.retain(|reserved| reserved.name != name);
+
+    match reserved.0.len() < count {
+        true => Ok(()),
+        false => Err(OperationError::NonExistentItem),
+    }
+}
+
+pub fn get_reserved_names() -> Vec<ReservedName> {
+    ic::get::<ReservedNames>().0.clone()
+}
+
+// Generates the methods of `ReservedNames`
+#[macro_export]
+macro_rules! reserved_methods {
+    () => {
+        #[$crate::ic_kit::macros::update]
+        pub fn reserve_name(
+            name: String,
+            is_pattern: bool,
+            allowed: Vec<$crate::ic_kit::Principal>,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::reserved::reserve_name(name, is_pattern, allowed)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn release_name(name: String) -> Result<(), $crate::types::OperationError> {
+            $crate::reserved::release_name(name)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_reserved_names() -> Vec<$crate::types::ReservedName> {
+            $crate::reserved::get_reserved_names()
+        }
+    };
+}
@@ -0,0 +1,158 @@
+use ic_kit::*;
+
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::types::*;
+
+// Delay before the first retry of a failed call, doubled on every attempt
+pub const RETRY_BASE_DELAY: u64 = 5_000_000_000;
+pub const MAX_ATTEMPTS: u32 = 8;
+
+// Side effects whose calls failed. Pending tasks are replayed from the heartbeat with an
+// exponential backoff. Tasks out of attempts are kept as failed until an admin retries
+// them, so none is dropped silently. The registry runs the tasks, see `retries_methods!`.
+#[derive(Default)]
+pub struct Retries {
+    next_id: u64,
+    pending: Vec<RetryTask>,
+    failed: Vec<RetryTask>,
+}
+
+impl Retries {
+    pub fn archive(&mut self) -> RetriesArchive {
+        let retries = std::mem::take(self);
+        RetriesArchive {
+            next_id: retries.next_id,
+            pending: retries.pending,
+            failed: retries.failed,
+        }
+    }
+
+    pub fn load(&mut self, archive: RetriesArchive) {
+        self.next_id = archive.next_id;
+        self.pending = archive.pending;
+        self.failed = archive.failed;
+    }
+
+    fn contains(&self, kind: &TaskKind) -> bool {
+        self.pending
+            .iter()
+            .chain(self.failed.iter())
+            .any(|task| task.kind == *kind)
+    }
+
+    // Queues a side effect whose first call failed, unless it's queued already
+    pub fn schedule(&mut self, kind: TaskKind, error: String) {
+        if self.contains(&kind) {
+            return;
+        }
+
+        self.next_id += 1;
+        self.pending.push(RetryTask {
+            id: self.next_id,
+            kind,
+            attempts: 1,
+            next_attempt_at: ic::time() + RETRY_BASE_DELAY,
+            last_error: error,
+        });
+    }
+
+    // Records a side effect that already ran out of attempts elsewhere
+    pub fn fail(&mut self, kind: TaskKind, attempts: u32, error: String) {
+        if self.contains(&kind) {
+            return;
+        }
+
+        self.next_id += 1;
+        self.failed.push(RetryTask {
+            id: self.next_id,
+            kind,
+            attempts,
+            next_attempt_at: 0,
+            last_error: error,
+        });
+    }
+
+    // Puts back a task whose retry failed
+    pub fn reschedule(&mut self, mut task: RetryTask, error: String) {
+        task.last_error = error;
+        if task.attempts >= MAX_ATTEMPTS {
+            self.failed.push(task);
+            return;
+        }
+
+        task.next_attempt_at = ic::time() + (RETRY_BASE_DELAY << task.attempts);
+        task.attempts += 1;
+        self.pending.push(task);
+    }
+
+    // Replays the pending tasks that are due with `run`. Called from the heartbeat.
+    pub fn tick(&mut self, run: fn(RetryTask)) {
+        let now = ic::time();
+        let (due, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|task| task.next_attempt_at <= now);
+        self.pending = pending;
+
+        for task in due {
+            run(task);
+        }
+    }
+
+    // Pending and failed tasks of the kinds `matches` accepts
+    pub fn find(&self, matches: fn(&TaskKind) -> bool) -> Vec<RetryTask> {
+        self.pending
+            .iter()
+            .chain(self.failed.iter())
+            .filter(|task| matches(&task.kind))
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len() + self.failed.len()
+    }
+}
+
+pub fn get_failed_tasks() -> Result<Vec<RetryTask>, OperationError> {
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    Ok(ic::get::<Retries>().failed.clone())
+}
+
+// Runs a failed task once more, it's listed as failed again if the call fails
+pub fn retry_task(id: u64, run: fn(RetryTask)) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    let failed = &mut ic::get_mut::<Retries>().failed;
+    let position = failed
+        .iter()
+        .position(|task| task.id == id)
+        .ok_or(OperationError::NonExistentItem)?;
+    run(failed.remove(position));
+    Ok(())
+}
+
+// Generates the methods of `Retries`. `$run` runs the side effect of a task, putting it back
+// in the queue when it fails again.
+#[macro_export]
+macro_rules! retries_methods {
+    ($run:path) => {
+        #[$crate::ic_kit::macros::query]
+        pub fn get_failed_tasks(
+        ) -> Result<Vec<$crate::types::RetryTask>, $crate::types::OperationError> {
+            $crate::retries::get_failed_tasks()
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn retry_task(id: u64) -> Result<(), $crate::types::OperationError> {
+            $crate::retries::retry_task(id, $run)
+        }
+    };
+}
@@ -0,0 +1,123 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::HashMap;
+
+use crate::entries::EntryStore;
+use crate::guard::check_caller;
+use crate::maintenance::Maintenance;
+use crate::types::*;
+
+// Cycles the registry received, attributed to the principals that sent them
+#[derive(Default)]
+pub struct Sponsors(HashMap<Principal, Sponsor>);
+
+impl Sponsors {
+    pub fn archive(&mut self) -> Vec<Sponsor> {
+        let map = std::mem::replace(&mut self.0, HashMap::new());
+        map.into_values().collect()
+    }
+
+    pub fn load(&mut self, archive: Vec<Sponsor>) {
+        self.0 = archive
+            .into_iter()
+            .map(|sponsor| (sponsor.sponsor, sponsor))
+            .collect();
+    }
+
+    pub fn record(&mut self, sponsor: Principal, entry: Option<Principal>, cycles: u64) {
+        let record = self.0.entry(sponsor).or_insert(Sponsor {
+            sponsor,
+            cycles: 0,
+            entries: vec![],
+            last_donation_at: 0,
+        });
+        record.cycles += cycles;
+        record.last_donation_at = ic::time();
+
+        if let Some(entry) = entry {
+            match record
+                .entries
+                .iter_mut()
+                .find(|(principal_id, _)| *principal_id == entry)
+            {
+                Some((_, total)) => *total += cycles,
+                None => record.entries.push((entry, cycles)),
+            }
+        }
+    }
+
+    pub fn get_all(&self) -> Vec<&Sponsor> {
+        self.0.values().collect()
+    }
+
+    pub fn entry_total(&self, entry: &Principal) -> u64 {
+        self.0
+            .values()
+            .flat_map(|sponsor| sponsor.entries.iter())
+            .filter(|(principal_id, _)| principal_id == entry)
+            .map(|(_, cycles)| cycles)
+            .sum()
+    }
+}
+
+fn accept_cycles(entry: Option<Principal>) -> u64 {
+    let cycles = ic::msg_cycles_accept(ic::msg_cycles_available());
+    if cycles > 0 {
+        ic::get_mut::<Sponsors>().record(ic::caller(), entry, cycles);
+    }
+    cycles
+}
+
+// Accepts every cycle sent with the call, attributed to the caller
+pub fn wallet_receive() -> Result<u64, OperationError> {
+    check_caller()?;
+
+    Ok(accept_cycles(None))
+}
+
+// Accepts every cycle sent with the call, attributed to the caller and to the sponsored entry
+pub fn sponsor_entry<R: EntryStore>(principal_id: Principal) -> Result<u64, OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if ic::get::<R>().entry(&principal_id).is_none() {
+        return Err(OperationError::NonExistentItem);
+    }
+
+    Ok(accept_cycles(Some(principal_id)))
+}
+
+pub fn get_sponsors() -> Vec<&'static Sponsor> {
+    ic::get::<Sponsors>().get_all()
+}
+
+pub fn get_entry_sponsorship(principal_id: Principal) -> u64 {
+    ic::get::<Sponsors>().entry_total(&principal_id)
+}
+
+// Generates the methods of `Sponsors`, `$registry` is the `EntryStore` of the registry
+#[macro_export]
+macro_rules! sponsors_methods {
+    ($registry:ty) => {
+        #[$crate::ic_kit::macros::update]
+        pub fn wallet_receive() -> Result<u64, $crate::types::OperationError> {
+            $crate::sponsors::wallet_receive()
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn sponsor_entry(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Result<u64, $crate::types::OperationError> {
+            $crate::sponsors::sponsor_entry::<$registry>(principal_id)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_sponsors() -> Vec<&'static $crate::types::Sponsor> {
+            $crate::sponsors::get_sponsors()
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_entry_sponsorship(principal_id: $crate::ic_kit::Principal) -> u64 {
+            $crate::sponsors::get_entry_sponsorship(principal_id)
+        }
+    };
+}
@@ -0,0 +1,194 @@
+use ic_cdk::api::call::notify;
+use ic_kit::candid::{CandidType, Principal};
+use ic_kit::*;
+use std::collections::HashMap;
+
+use crate::alerts::raise;
+use crate::entries::ListedEntry;
+use crate::history::History;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::retries::{Retries, MAX_ATTEMPTS, RETRY_BASE_DELAY};
+use crate::types::*;
+
+// Canisters notified of every change to the registry. Each subscriber gets a
+// one-way call to its callback method with the `Event` of the change.
+#[derive(Default)]
+pub struct Subscribers {
+    subscribers: HashMap<Principal, String>,
+    pending: Vec<Delivery>,
+}
+
+impl Subscribers {
+    pub fn archive(&mut self) -> SubscribersArchive {
+        let subscribers = std::mem::replace(self, Subscribers::default());
+        SubscribersArchive {
+            subscribers: subscribers.subscribers.into_iter().collect(),
+            pending: subscribers.pending,
+        }
+    }
+
+    pub fn load(&mut self, archive: SubscribersArchive) {
+        self.subscribers = archive.subscribers.into_iter().collect();
+        self.pending = archive.pending;
+    }
+
+    pub fn subscribe(
+        &mut self,
+        subscriber: Principal,
+        callback: String,
+    ) -> Result<(), OperationError> {
+        if callback.is_empty()
+            || (!self.subscribers.contains_key(&subscriber)
+                && self.subscribers.len() >= SUBSCRIBERS_LIMIT)
+        {
+            return Err(OperationError::BadParameters);
+        }
+
+        self.subscribers.insert(subscriber, callback);
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self, subscriber: &Principal) -> Result<(), OperationError> {
+        self.pending
+            .retain(|delivery| delivery.subscriber != *subscriber);
+        match self.subscribers.remove(subscriber) {
+            Some(_) => Ok(()),
+            None => Err(OperationError::NonExistentItem),
+        }
+    }
+
+    pub fn get_all(&self) -> Vec<(Principal, String)> {
+        self.subscribers
+            .iter()
+            .map(|(subscriber, callback)| (*subscriber, callback.clone()))
+            .collect()
+    }
+
+    // Returns whether the notification left the canister, or doesn't need to anymore
+    pub fn send<T: CandidType + Clone>(&self, subscriber: &Principal, event: &Event<T>) -> bool {
+        match self.subscribers.get(subscriber) {
+            Some(callback) => notify(*subscriber, callback, (event.clone(),)).is_ok(),
+            None => true,
+        }
+    }
+
+    // Notifies every subscriber of the event, queueing failed notifications for a retry
+    pub fn publish<T: CandidType + Clone>(&mut self, event: &Event<T>) {
+        let now = ic::time();
+        let subscribers: Vec<Principal> = self.subscribers.keys().copied().collect();
+        for subscriber in subscribers {
+            if !self.send(&subscriber, event) {
+                self.pending.push(Delivery {
+                    subscriber,
+                    event_id: event.id,
+                    attempts: 1,
+                    next_attempt_at: now + RETRY_BASE_DELAY,
+                });
+            }
+        }
+    }
+
+    // Retries the queued notifications that are due. The ones out of attempts are handed
+    // over to the `Retries` queue as failed tasks. Called from the heartbeat.
+    pub fn retry<T: ListedEntry>(&mut self) {
+        let now = ic::time();
+        let pending = std::mem::replace(&mut self.pending, vec![]);
+        for mut delivery in pending {
+            if delivery.next_attempt_at > now {
+                self.pending.push(delivery);
+                continue;
+            }
+
+            let sent = match ic::get::<History<T>>().get(delivery.event_id) {
+                Some(event) => self.send(&delivery.subscriber, event),
+                None => true,
+            };
+            if !sent && delivery.attempts < MAX_ATTEMPTS {
+                delivery.next_attempt_at = now + (RETRY_BASE_DELAY << delivery.attempts);
+                delivery.attempts += 1;
+                self.pending.push(delivery);
+            } else if !sent {
+                raise(AlertKind::DeliveryFailed {
+                    subscriber: delivery.subscriber,
+                    event_id: delivery.event_id,
+                });
+                ic::get_mut::<Retries>().fail(
+                    TaskKind::Deliver {
+                        subscriber: delivery.subscriber,
+                        event_id: delivery.event_id,
+                    },
+                    delivery.attempts,
+                    String::from("the notification couldn't be sent"),
+                );
+            }
+        }
+    }
+
+    pub fn get_pending(&self) -> Vec<Delivery> {
+        self.pending.clone()
+    }
+}
+
+pub fn subscribe(callback_method: String) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    ic::get_mut::<Subscribers>().subscribe(ic::caller(), callback_method)
+}
+
+pub fn unsubscribe() -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    ic::get_mut::<Subscribers>().unsubscribe(&ic::caller())
+}
+
+pub fn remove_subscriber(subscriber: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    ic::get_mut::<Subscribers>().unsubscribe(&subscriber)
+}
+
+pub fn get_subscribers() -> Vec<(Principal, String)> {
+    ic::get::<Subscribers>().get_all()
+}
+
+pub fn get_pending_notifications() -> Vec<Delivery> {
+    ic::get::<Subscribers>().get_pending()
+}
+
+// Generates the methods of `Subscribers`
+#[macro_export]
+macro_rules! subscribers_methods {
+    () => {
+        #[$crate::ic_kit::macros::update]
+        pub fn subscribe(callback_method: String) -> Result<(), $crate::types::OperationError> {
+            $crate::subscribers::subscribe(callback_method)
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn unsubscribe() -> Result<(), $crate::types::OperationError> {
+            $crate::subscribers::unsubscribe()
+        }
+
+        #[$crate::ic_kit::macros::update]
+        pub fn remove_subscriber(
+            subscriber: $crate::ic_kit::Principal,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::subscribers::remove_subscriber(subscriber)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_subscribers() -> Vec<($crate::ic_kit::Principal, String)> {
+            $crate::subscribers::get_subscribers()
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_pending_notifications() -> Vec<$crate::types::Delivery> {
+            $crate::subscribers::get_pending_notifications()
+        }
+    };
+}
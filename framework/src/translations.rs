@@ -0,0 +1,167 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::entries::{EntryStore, ListedEntry};
+use crate::locks::Locks;
+use crate::maintenance::Maintenance;
+use crate::management::*;
+use crate::mirror::Mirror;
+use crate::types::*;
+
+// Localized names and descriptions of each entry, keyed by language code
+#[derive(Default)]
+pub struct Translations(HashMap<Principal, Vec<(String, LocalizedText)>>);
+
+impl Translations {
+    pub fn archive(&mut self) -> Vec<(Principal, Vec<(String, LocalizedText)>)> {
+        let map = std::mem::replace(&mut self.0, HashMap::new());
+        map.into_iter().collect()
+    }
+
+    pub fn load(&mut self, archive: Vec<(Principal, Vec<(String, LocalizedText)>)>) {
+        self.0 = archive.into_iter().collect();
+    }
+
+    pub fn set(&mut self, principal_id: Principal, translations: Vec<(String, LocalizedText)>) {
+        if translations.is_empty() {
+            self.0.remove(&principal_id);
+        } else {
+            self.0.insert(principal_id, translations);
+        }
+    }
+
+    pub fn remove(&mut self, principal_id: &Principal) {
+        self.0.remove(principal_id);
+    }
+
+    // Looks up `lang`, then its base language ("pt" for "pt-BR")
+    pub fn get(&self, principal_id: &Principal, lang: &str) -> Option<&LocalizedText> {
+        let translations = self.0.get(principal_id)?;
+        let find = |lang: &str| {
+            translations
+                .iter()
+                .find(|(code, _)| code.eq_ignore_ascii_case(lang))
+                .map(|(_, text)| text)
+        };
+
+        find(lang).or_else(|| find(lang.split('-').next().unwrap_or(lang)))
+    }
+
+    pub fn get_all(&self, principal_id: &Principal) -> Vec<(String, LocalizedText)> {
+        self.0.get(principal_id).cloned().unwrap_or_default()
+    }
+}
+
+// Writes a language tag with the usual casing, "PT_br" becomes "pt-BR" and "zh-hant"
+// becomes "zh-Hant". `None` unless the tag is made of alphanumeric subtags.
+pub fn normalize_lang(lang: &str) -> Option<String> {
+    let subtags = lang
+        .trim()
+        .split(|c| c == '-' || c == '_')
+        .enumerate()
+        .map(|(index, subtag)| {
+            if subtag.is_empty() || !subtag.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return None;
+            }
+            let lower = subtag.to_ascii_lowercase();
+            Some(match (index, subtag.len()) {
+                (0, _) => lower,
+                // Region
+                (_, 2) => subtag.to_ascii_uppercase(),
+                // Script
+                (_, 4) => lower[..1].to_ascii_uppercase() + &lower[1..],
+                _ => lower,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(subtags.join("-"))
+}
+
+// Replaces the translations of an entry. Language tags are normalized, each one can
+// appear once.
+pub fn set_translations<R: EntryStore>(
+    principal_id: Principal,
+    translations: Vec<(String, LocalizedText)>,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    ic::get::<Mirror>().check_writable()?;
+
+    let caller = ic::caller();
+    let entry = match ic::get::<R>().entry(&principal_id) {
+        Some(entry) => entry,
+        None => return Err(OperationError::NonExistentItem),
+    };
+
+    if entry.manager() != caller && !is_admin(&caller) {
+        return Err(OperationError::not_authorized(Role::Submitter, &caller));
+    }
+
+    ic::get::<Locks>().check(&principal_id)?;
+
+    if translations.len() > TRANSLATIONS_LIMIT {
+        return Err(OperationError::BadParameters);
+    }
+
+    let mut seen = HashSet::new();
+    let mut normalized = Vec::with_capacity(translations.len());
+    for (lang, text) in translations {
+        let lang = normalize_lang(&lang).ok_or(OperationError::BadParameters)?;
+        if lang.len() > LANG_LIMIT
+            || text.name.len() > NAME_LIMIT
+            || text.description.len() > DESCRIPTION_LIMIT
+            || !seen.insert(lang.clone())
+        {
+            return Err(OperationError::BadParameters);
+        }
+        normalized.push((lang, text));
+    }
+
+    ic::get_mut::<Translations>().set(principal_id, normalized);
+    Ok(())
+}
+
+pub fn get_translations(principal_id: Principal) -> Vec<(String, LocalizedText)> {
+    ic::get::<Translations>().get_all(&principal_id)
+}
+
+// Returns the entry with its name and description in `lang`, falling back to the default ones
+pub fn get_localized<R: EntryStore>(principal_id: Principal, lang: String) -> Option<R::Entry> {
+    let mut entry = ic::get::<R>().entry(&principal_id)?.clone();
+
+    if let Some(text) = ic::get::<Translations>().get(&principal_id, &lang) {
+        entry.localize(text);
+    }
+
+    Some(entry)
+}
+
+// Generates the methods of `Translations`, `$registry` is the `EntryStore` of the registry
+#[macro_export]
+macro_rules! translations_methods {
+    ($registry:ty) => {
+        #[$crate::ic_kit::macros::update]
+        pub fn set_translations(
+            principal_id: $crate::ic_kit::Principal,
+            translations: Vec<(String, $crate::types::LocalizedText)>,
+        ) -> Result<(), $crate::types::OperationError> {
+            $crate::translations::set_translations::<$registry>(principal_id, translations)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_translations(
+            principal_id: $crate::ic_kit::Principal,
+        ) -> Vec<(String, $crate::types::LocalizedText)> {
+            $crate::translations::get_translations(principal_id)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_localized(
+            principal_id: $crate::ic_kit::Principal,
+            lang: String,
+        ) -> Option<<$registry as $crate::entries::EntryStore>::Entry> {
+            $crate::translations::get_localized::<$registry>(principal_id, lang)
+        }
+    };
+}
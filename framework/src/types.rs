@@ -0,0 +1,427 @@
+// Types and limits of the stores shared by the registries. They re-export them from their
+// `common_types`, next to their own entry types.
+
+use ic_kit::{candid::CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+pub const DESCRIPTION_LIMIT: usize = 1200;
+pub const NAME_LIMIT: usize = 120;
+pub const LANG_LIMIT: usize = 16;
+// Languages an entry can be translated to
+pub const TRANSLATIONS_LIMIT: usize = 32;
+pub const SUBSCRIBERS_LIMIT: usize = 100;
+pub const REPORT_REASON_LIMIT: usize = 280;
+pub const REPORTS_PER_WINDOW: usize = 10;
+pub const PINNED_LIMIT: usize = 20;
+// Events kept in the history, older ones are dropped
+pub const HISTORY_LIMIT: usize = 10_000;
+// Largest asset, and total size of the asset store. The store lives on the heap and
+// pre_upgrade serializes all of it along with the rest of the state, so it's kept small.
+pub const ASSET_SIZE_LIMIT: u64 = 1024 * 1024;
+pub const ASSET_STORE_LIMIT: u64 = 32 * 1024 * 1024;
+// Unreferenced bytes a caller other than an admin may upload, and part of the store kept
+// for admins
+pub const ASSET_UPLOAD_QUOTA: u64 = 2 * 1024 * 1024;
+pub const ASSET_ADMIN_RESERVE: u64 = 8 * 1024 * 1024;
+// Age an unreferenced asset must reach before `gc_assets` deletes it
+pub const ASSET_GC_GRACE: u64 = 24 * 60 * 60 * 1_000_000_000;
+// Distinct reports putting an entry under review by default
+pub const DEFAULT_REPORT_THRESHOLD: u32 = 5;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct LocalizedText {
+    pub name: String,
+    pub description: String,
+}
+
+// Amount of `ledger` tokens a submitter has to lock while their submission is pending
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Deposit {
+    pub ledger: Principal,
+    pub amount: u64,
+}
+
+// `I` is the input the registry adds entries from
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ProposedChange<I> {
+    Update(I),
+    Remove,
+    Unprotect,
+}
+
+// A change to a protected entry, applied once an admin other than `proposed_by` confirms it
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChangeProposal<I> {
+    pub id: u64,
+    pub principal_id: Principal,
+    pub trusted_source: Option<Principal>,
+    pub change: ProposedChange<I>,
+    pub proposed_by: Principal,
+    pub proposed_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProtectionArchive<I> {
+    pub protected: Vec<Principal>,
+    pub proposals: Vec<ChangeProposal<I>>,
+    pub next_id: u64,
+}
+
+impl<I> Default for ProtectionArchive<I> {
+    fn default() -> Self {
+        ProtectionArchive {
+            protected: vec![],
+            proposals: vec![],
+            next_id: 0,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ControllerProposal {
+    pub candidate: Principal,
+    pub proposed_by: Principal,
+    pub proposed_at: u64,
+    pub accept_after: u64,
+}
+
+// Calls counted since `since`, the busiest methods and callers first
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct UsageStats {
+    pub enabled: bool,
+    pub since: u64,
+    pub methods: Vec<(String, u64)>,
+    pub callers: Vec<(Principal, u64)>,
+}
+
+// See `registry_hash`. `certificate` is the subnet's certificate over `hash`, only
+// available to query calls.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RegistryHash {
+    pub hash: Vec<u8>,
+    pub entries: u64,
+    pub certificate: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Votes {
+    pub approvals: Vec<Principal>,
+    pub rejections: Vec<Principal>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct CurationArchive {
+    pub curators: Vec<Principal>,
+    pub threshold: Option<u32>,
+    pub votes: Vec<(Principal, Votes)>,
+}
+
+// A notification that couldn't be delivered yet
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Delivery {
+    pub subscriber: Principal,
+    pub event_id: u64,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct SubscribersArchive {
+    pub subscribers: Vec<(Principal, String)>,
+    pub pending: Vec<Delivery>,
+}
+
+// A side effect of a change, replayed by the `Retries` queue after its call failed
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum TaskKind {
+    // A notification subscribers ran out of attempts for
+    Deliver {
+        subscriber: Principal,
+        event_id: u64,
+    },
+    // Dropping the entry from the cache of the conflict router
+    Invalidate {
+        principal_id: Principal,
+    },
+    // Finding or creating the CAP root bucket
+    CapHandshake,
+    // A notice for the callback of an entry's owner
+    EntryCallback {
+        target: Principal,
+        method: String,
+        notice: EntryNotice,
+    },
+    // Sending back the deposit of the submission of `principal_id`
+    Refund {
+        principal_id: Principal,
+        submitter: Principal,
+        deposit: Deposit,
+    },
+}
+
+// Status changes reported to the callback of an entry, see `set_entry_callback`
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EntryStatus {
+    Approved,
+    EditedByAdmin,
+    Reported,
+    Delisted,
+}
+
+// Single argument of the callback method of an entry
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct EntryNotice {
+    pub principal_id: Principal,
+    pub status: EntryStatus,
+    pub time: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RetryTask {
+    pub id: u64,
+    pub kind: TaskKind,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub last_error: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct RetriesArchive {
+    pub next_id: u64,
+    pub pending: Vec<RetryTask>,
+    pub failed: Vec<RetryTask>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct MessagesArchive {
+    pub catalogs: Vec<(String, Vec<(String, String)>)>,
+    pub languages: Vec<(Principal, String)>,
+}
+
+// A name, or a regular expression when `is_pattern` is set, that only the
+// `allowed` principals (as submitters or as the listed canister) may use
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReservedName {
+    pub name: String,
+    pub is_pattern: bool,
+    pub allowed: Vec<Principal>,
+}
+
+// Cycles donated by a sponsor, in total and per sponsored entry
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Sponsor {
+    pub sponsor: Principal,
+    pub cycles: u64,
+    pub entries: Vec<(Principal, u64)>,
+    pub last_donation_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EventKind {
+    Add,
+    Update,
+    Remove,
+    // A removal in response to an active scam or phishing incident
+    Delist,
+}
+
+// An entry of the registry's change log. `entry` holds the full entry after
+// the change, or the removed entry for `Remove` events.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Event<T> {
+    pub id: u64,
+    pub time: u64,
+    pub kind: EventKind,
+    pub entry: T,
+}
+
+// Upstream registry mirrored by this canister and how far it has been replayed
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MirrorStatus {
+    pub upstream: Option<Principal>,
+    pub last_event_id: u64,
+    pub last_sync_at: u64,
+    pub last_error: Option<String>,
+}
+
+// An update call made with a request id. `result` is `None` while the call is in flight.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProcessedRequest {
+    pub caller: Principal,
+    pub request_id: Vec<u8>,
+    pub at: u64,
+    pub result: Option<Result<(), OperationError>>,
+}
+
+// Memory held by the canister, with the number of items in each of its stores
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct MemoryReport {
+    pub heap_size: u64,
+    pub stable_pages: u64,
+    pub stores: Vec<(String, u64)>,
+}
+
+// A counter or gauge sample, e.g. `update_calls` labeled with `endpoint = "add"`
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Metric {
+    pub name: String,
+    pub labels: Vec<(String, String)>,
+    pub value: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct CanisterMetrics {
+    pub collected_at: u64,
+    pub counters: Vec<Metric>,
+    pub gauges: Vec<Metric>,
+}
+
+// Critical events pushed to the operators' alert sink
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum AlertKind {
+    AdminAdded {
+        admin: Principal,
+        by: Principal,
+    },
+    EntryRemoved {
+        principal_id: Principal,
+        by: Principal,
+    },
+    CapacityNearlyFull {
+        heap_size: u64,
+    },
+    DeliveryFailed {
+        subscriber: Principal,
+        event_id: u64,
+    },
+    EntryUnderReview {
+        principal_id: Principal,
+    },
+    EntryDelisted {
+        principal_id: Principal,
+        by: Principal,
+    },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Alert {
+    pub registry: Principal,
+    pub time: u64,
+    pub kind: AlertKind,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+// Kept by the `logger`, and the single argument of the log sink method
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    pub timestamp: u64,
+    pub endpoint: String,
+    pub caller: Principal,
+    pub message: String,
+}
+
+// An entry taken down by `emergency_delist`
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Delisting {
+    pub principal_id: Principal,
+    pub name: String,
+    pub reason: String,
+    pub delisted_by: Principal,
+    pub delisted_at: u64,
+}
+
+// A community report against a listed entry, see `report_entry`
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Report {
+    pub reporter: Principal,
+    pub reason: String,
+    pub reported_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ReportStatus {
+    Reported,
+    // Enough callers reported the entry for moderators to look at it
+    UnderReview,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReportedEntry {
+    pub principal_id: Principal,
+    pub status: ReportStatus,
+    pub reports: Vec<Report>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ReportsArchive {
+    pub entries: Vec<(Principal, Vec<Report>)>,
+    pub threshold: u32,
+}
+
+// The role a guarded endpoint expects from its caller
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    Admin,
+    // The manager of the entry, or an admin
+    Submitter,
+    Curator,
+    // Assigned to the submissions of a standard, see `assign_reviewers`
+    Reviewer,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+#[derive(CandidType, Debug, PartialEq, Deserialize, Clone)]
+pub enum OperationError {
+    NotAuthorized {
+        required_role: Role,
+        caller: Principal,
+    },
+    NonExistentItem,
+    BadParameters,
+    Conflict(Principal),
+    InsufficientDeposit,
+    ReservedName(String),
+    EntryLocked,
+    ReadOnlyMirror(Principal),
+    Maintenance,
+    // The cycles balance is below the threshold set by admins
+    LowCycles,
+    // The caller, or a principal given a role, is the anonymous principal
+    Anonymous,
+    // The action can't happen before this time
+    Timelocked(u64),
+    // The entry is protected, the change waits as the given proposal for a second admin
+    PendingApproval(u64),
+    // The encoded entry would take `bytes`, over the limit set by admins
+    EntryTooLarge {
+        bytes: u64,
+        limit: u64,
+    },
+    // The events after the requested one were dropped from the history, it starts at this id
+    HistoryTruncated(u64),
+    // Every field of the input that failed validation
+    Validation(Vec<FieldError>),
+    Unknown(String),
+}
+
+impl OperationError {
+    pub fn not_authorized(required_role: Role, caller: &Principal) -> Self {
+        OperationError::NotAuthorized {
+            required_role,
+            caller: *caller,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use std::collections::{HashMap, VecDeque};
+
+use crate::guard::check_caller;
+use crate::management::*;
+use crate::metrics::Metrics;
+use crate::types::*;
+
+// Length of a usage window and number of windows kept, a rolling day of hourly counts
+const WINDOW: u64 = 60 * 60 * 1_000_000_000;
+const WINDOWS: usize = 24;
+
+// Distinct callers counted per window, calls from the others only count per method
+const CALLERS_PER_WINDOW: usize = 1_000;
+
+// Callers listed in `get_usage_stats`, the busiest first
+const TOP_CALLERS: usize = 20;
+
+#[derive(Default)]
+struct Window {
+    start: u64,
+    by_method: HashMap<String, u64>,
+    by_caller: HashMap<Principal, u64>,
+}
+
+// Opt-in call counters by method and by caller. Changes made by a query are discarded,
+// so only update calls are counted, queries can't be observed from inside the canister.
+#[derive(Default)]
+pub struct Usage {
+    pub enabled: bool,
+    windows: VecDeque<Window>,
+}
+
+impl Usage {
+    pub fn record(&mut self, method: &str) {
+        ic::get_mut::<Metrics>().count(method);
+        if !self.enabled {
+            return;
+        }
+
+        let start = ic::time() - ic::time() % WINDOW;
+        if self
+            .windows
+            .back()
+            .map_or(true, |window| window.start != start)
+        {
+            if self.windows.len() == WINDOWS {
+                self.windows.pop_front();
+            }
+            self.windows.push_back(Window {
+                start,
+                ..Window::default()
+            });
+        }
+
+        let window = self.windows.back_mut().unwrap();
+        *window.by_method.entry(method.to_string()).or_default() += 1;
+
+        let caller = ic::caller();
+        if window.by_caller.len() < CALLERS_PER_WINDOW || window.by_caller.contains_key(&caller) {
+            *window.by_caller.entry(caller).or_default() += 1;
+        }
+    }
+
+    // Counts summed over the windows still within the rolling period
+    pub fn stats(&self) -> UsageStats {
+        let oldest = ic::time().saturating_sub(WINDOW * WINDOWS as u64);
+        let windows = self.windows.iter().filter(|window| window.start >= oldest);
+
+        let mut methods: HashMap<&str, u64> = HashMap::new();
+        let mut callers: HashMap<Principal, u64> = HashMap::new();
+        let mut since = None;
+        for window in windows {
+            since = since.or(Some(window.start));
+            for (method, count) in window.by_method.iter() {
+                *methods.entry(method).or_default() += count;
+            }
+            for (caller, count) in window.by_caller.iter() {
+                *callers.entry(*caller).or_default() += count;
+            }
+        }
+
+        let mut methods: Vec<(String, u64)> = methods
+            .into_iter()
+            .map(|(method, count)| (method.to_string(), count))
+            .collect();
+        methods.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let mut callers: Vec<(Principal, u64)> = callers.into_iter().collect();
+        callers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        callers.truncate(TOP_CALLERS);
+
+        UsageStats {
+            enabled: self.enabled,
+            since: since.unwrap_or_else(ic::time),
+            methods,
+            callers,
+        }
+    }
+}
+
+// Turning the tracking off drops the counts collected so far
+pub fn set_usage_tracking(enabled: bool) -> Result<(), OperationError> {
+    check_caller()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    ic::store(Usage {
+        enabled,
+        ..Usage::default()
+    });
+    Ok(())
+}
+
+pub fn get_usage_stats() -> Result<UsageStats, OperationError> {
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    Ok(ic::get::<Usage>().stats())
+}
+
+// Generates the methods of `Usage`
+#[macro_export]
+macro_rules! usage_methods {
+    () => {
+        #[$crate::ic_kit::macros::update]
+        pub fn set_usage_tracking(enabled: bool) -> Result<(), $crate::types::OperationError> {
+            $crate::usage::set_usage_tracking(enabled)
+        }
+
+        #[$crate::ic_kit::macros::query]
+        pub fn get_usage_stats() -> Result<$crate::types::UsageStats, $crate::types::OperationError>
+        {
+            $crate::usage::get_usage_stats()
+        }
+    };
+}
@@ -0,0 +1,144 @@
+//! Harness for running the registry Wasm modules inside PocketIC.
+//!
+//! The modules are read from `target/wasm32-unknown-unknown/release`, so build them first:
+//!
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release -p nft -p tokens -p registry
+//! POCKET_IC_BIN=/path/to/pocket-ic cargo test -p integration-tests
+//! ```
+//!
+//! `NFT_WASM`, `TOKENS_WASM` and `REGISTRY_WASM` override the module paths.
+
+use candid::utils::{ArgumentDecoder, ArgumentEncoder};
+use candid::{decode_args, encode_args, Principal};
+use pocket_ic::{PocketIc, WasmResult};
+use std::path::PathBuf;
+
+pub mod types;
+
+pub const INIT_CYCLES: u128 = 2_000_000_000_000;
+
+// The token registry mirrors every entry into the canister registry at this id
+pub const CANISTER_REGISTRY_ID: &str = "curr3-vaaaa-aaaah-abbdq-cai";
+
+pub fn admin() -> Principal {
+    Principal::from_slice(&[1; 29])
+}
+
+pub fn user() -> Principal {
+    Principal::from_slice(&[2; 29])
+}
+
+pub fn wasm(name: &str) -> Vec<u8> {
+    let path = match std::env::var(format!("{}_WASM", name.to_uppercase())) {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../target/wasm32-unknown-unknown/release")
+            .join(format!("{}.wasm", name)),
+    };
+
+    std::fs::read(&path).unwrap_or_else(|_| panic!("could not read {}", path.display()))
+}
+
+// A PocketIC instance with a single registry installed by `admin()`
+pub struct Env {
+    pub pic: PocketIc,
+    pub canister_id: Principal,
+    name: &'static str,
+}
+
+impl Env {
+    pub fn new<A: ArgumentEncoder>(name: &'static str, init_args: A) -> Self {
+        let pic = PocketIc::new();
+        let canister_id = pic.create_canister_with_settings(Some(admin()), None);
+        pic.add_cycles(canister_id, INIT_CYCLES);
+        pic.install_canister(
+            canister_id,
+            wasm(name),
+            encode_args(init_args).unwrap(),
+            Some(admin()),
+        );
+
+        Env {
+            pic,
+            canister_id,
+            name,
+        }
+    }
+
+    // Installs the canister registry at its mainnet id next to the registry under test
+    pub fn with_canister_registry(self) -> Self {
+        let registry_id = Principal::from_text(CANISTER_REGISTRY_ID).unwrap();
+        self.pic
+            .create_canister_with_id(Some(admin()), None, registry_id)
+            .expect("could not create the canister registry");
+        self.pic.add_cycles(registry_id, INIT_CYCLES);
+        self.pic.install_canister(
+            registry_id,
+            wasm("registry"),
+            encode_args(()).unwrap(),
+            Some(admin()),
+        );
+        self
+    }
+
+    pub fn upgrade(&self) {
+        self.pic
+            .upgrade_canister(
+                self.canister_id,
+                wasm(self.name),
+                encode_args(()).unwrap(),
+                Some(admin()),
+            )
+            .expect("upgrade failed");
+    }
+
+    pub fn update<A, R>(&self, sender: Principal, method: &str, args: A) -> R
+    where
+        A: ArgumentEncoder,
+        R: for<'a> ArgumentDecoder<'a>,
+    {
+        let result =
+            self.pic
+                .update_call(self.canister_id, sender, method, encode_args(args).unwrap());
+        decode_reply(method, result)
+    }
+
+    pub fn query<A, R>(&self, sender: Principal, method: &str, args: A) -> R
+    where
+        A: ArgumentEncoder,
+        R: for<'a> ArgumentDecoder<'a>,
+    {
+        self.query_at(self.canister_id, sender, method, args)
+    }
+
+    // Queries another canister of the instance, such as one the registry created
+    pub fn query_at<A, R>(
+        &self,
+        canister_id: Principal,
+        sender: Principal,
+        method: &str,
+        args: A,
+    ) -> R
+    where
+        A: ArgumentEncoder,
+        R: for<'a> ArgumentDecoder<'a>,
+    {
+        let result = self
+            .pic
+            .query_call(canister_id, sender, method, encode_args(args).unwrap());
+        decode_reply(method, result)
+    }
+}
+
+fn decode_reply<E: std::fmt::Debug, R>(method: &str, result: Result<WasmResult, E>) -> R
+where
+    R: for<'a> ArgumentDecoder<'a>,
+{
+    match result {
+        Ok(WasmResult::Reply(bytes)) => decode_args(&bytes)
+            .unwrap_or_else(|e| panic!("could not decode the reply of {}: {}", method, e)),
+        Ok(WasmResult::Reject(msg)) => panic!("{} was rejected: {}", method, msg),
+        Err(e) => panic!("{} failed: {:?}", method, e),
+    }
+}
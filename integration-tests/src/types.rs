@@ -0,0 +1,392 @@
+//! Mirrors of the registry Candid types, as seen from outside the canisters.
+
+use candid::{CandidType, Deserialize, Principal};
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum DetailValue {
+    True,
+    False,
+    U64(u64),
+    I64(i64),
+    Float(f64),
+    Text(String),
+    Principal(Principal),
+    #[serde(with = "serde_bytes")]
+    Slice(Vec<u8>),
+    Vec(Vec<DetailValue>),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct AddInput {
+    pub name: String,
+    pub description: String,
+    pub thumbnail: String,
+    pub frontend: Option<String>,
+    pub principal_id: Principal,
+    pub details: Vec<(String, DetailValue)>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub description: String,
+    pub thumbnail: String,
+    pub frontend: Option<String>,
+    pub principal_id: Principal,
+    pub submitter: Principal,
+    pub last_updated_by: Principal,
+    pub last_updated_at: u64,
+    pub details: Vec<(String, DetailValue)>,
+    pub resolved_from: Option<Principal>,
+    pub managed_by: Option<Principal>,
+    // Only set by the token registry
+    pub conflicted: Option<bool>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct SymbolCollision {
+    pub symbol: String,
+    pub principals: Vec<Principal>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct UsageStats {
+    pub enabled: bool,
+    pub since: u64,
+    pub methods: Vec<(String, u64)>,
+    pub callers: Vec<(Principal, u64)>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Deposit {
+    pub ledger: Principal,
+    pub amount: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Submission {
+    pub submitter: Principal,
+    pub submitted_at: u64,
+    pub deposit: Option<Deposit>,
+    pub entry: AddInput,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExpiredSubmission {
+    pub submission: Submission,
+    pub expired_at: u64,
+    pub refund_error: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum FilterOp {
+    Equals,
+    Contains,
+    GreaterThan,
+    LessThan,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Filter {
+    pub key: String,
+    pub op: FilterOp,
+    pub value: DetailValue,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    Admin,
+    Submitter,
+    Curator,
+    // Assigned to the submissions of a standard, see `assign_reviewers`
+    Reviewer,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum OperationError {
+    NotAuthorized {
+        required_role: Role,
+        caller: Principal,
+    },
+    NonExistentItem,
+    BadParameters,
+    Conflict(Principal),
+    InsufficientDeposit,
+    ReservedName(String),
+    EntryLocked,
+    ReadOnlyMirror(Principal),
+    Maintenance,
+    LowCycles,
+    Anonymous,
+    Timelocked(u64),
+    PendingApproval(u64),
+    EntryTooLarge {
+        bytes: u64,
+        limit: u64,
+    },
+    HistoryTruncated(u64),
+    Validation(Vec<FieldError>),
+    Unknown(String),
+}
+
+pub type OperationResponse = Result<(), OperationError>;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Page {
+    pub offset: u64,
+    pub limit: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Envelope<T> {
+    pub schema_version: u16,
+    pub data: T,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct GetAllResponse {
+    pub entries: Vec<Entry>,
+    pub truncated: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PartialEntry {
+    pub principal_id: Principal,
+    pub fields: Vec<(String, DetailValue)>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RegistryHash {
+    pub hash: Vec<u8>,
+    pub entries: u64,
+    pub certificate: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Metric {
+    pub name: String,
+    pub labels: Vec<(String, String)>,
+    pub value: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct CanisterMetrics {
+    pub collected_at: u64,
+    pub counters: Vec<Metric>,
+    pub gauges: Vec<Metric>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ApprovalRule {
+    pub standard: String,
+    pub submitters: Vec<Principal>,
+    pub verify_ledger: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct NamespaceConfig {
+    pub description: String,
+    pub writers: Vec<Principal>,
+    pub validate: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct DisplayInfo {
+    pub symbol: String,
+    pub decimals: u64,
+    pub fee: Option<u64>,
+    pub formatted_fee: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Report {
+    pub reporter: Principal,
+    pub reason: String,
+    pub reported_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ReportStatus {
+    Reported,
+    UnderReview,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReportedEntry {
+    pub principal_id: Principal,
+    pub status: ReportStatus,
+    pub reports: Vec<Report>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Delisting {
+    pub principal_id: Principal,
+    pub name: String,
+    pub reason: String,
+    pub delisted_by: Principal,
+    pub delisted_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ChildRegistryConfig {
+    pub name: String,
+    pub cycles: u64,
+    pub admins: Vec<Principal>,
+    pub controllers: Vec<Principal>,
+    pub entries: Vec<Principal>,
+    pub wasm: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChildRegistry {
+    pub canister_id: Principal,
+    pub name: String,
+    pub created_by: Principal,
+    pub created_at: u64,
+    pub entries: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DetailType {
+    Any,
+    Bool,
+    U64,
+    I64,
+    Float,
+    Text,
+    Principal,
+    Slice,
+    Vec,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct DetailKeySpec {
+    pub key: String,
+    pub value_type: DetailType,
+    pub required: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SliceLimits {
+    pub max_size: u64,
+    pub asset_threshold: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    pub timestamp: u64,
+    pub endpoint: String,
+    pub caller: Principal,
+    pub message: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum TaskKind {
+    Deliver {
+        subscriber: Principal,
+        event_id: u64,
+    },
+    Invalidate {
+        principal_id: Principal,
+    },
+    CapHandshake,
+    EntryCallback {
+        target: Principal,
+        method: String,
+        notice: EntryNotice,
+    },
+    Refund {
+        principal_id: Principal,
+        submitter: Principal,
+        deposit: Deposit,
+    },
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EntryStatus {
+    Approved,
+    EditedByAdmin,
+    Reported,
+    Delisted,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct EntryNotice {
+    pub principal_id: Principal,
+    pub status: EntryStatus,
+    pub time: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RetryTask {
+    pub id: u64,
+    pub kind: TaskKind,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub last_error: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum IntegrityIssue {
+    FutureTimestamp {
+        principal_id: Principal,
+        last_updated_at: u64,
+    },
+    AddedAfterUpdate {
+        principal_id: Principal,
+        added_at: u64,
+        last_updated_at: u64,
+    },
+    EventIdMismatch {
+        position: u64,
+        id: u64,
+    },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct IntegrityReport {
+    pub checked_at: u64,
+    pub entries: u64,
+    pub events: u64,
+    pub repaired: Vec<String>,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub struct RiskFlags {
+    pub fee_on_transfer: bool,
+    pub rebasing: bool,
+    pub pausable: bool,
+    pub blacklistable: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct EntryLimits {
+    pub max_size: u64,
+    pub max_details: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ProjectLinks {
+    pub website: Option<String>,
+    pub docs: Option<String>,
+    pub twitter: Option<String>,
+    pub discord: Option<String>,
+    pub github: Option<String>,
+    pub frontends: Vec<String>,
+}
@@ -0,0 +1,653 @@
+use candid::Principal;
+use integration_tests::types::*;
+use integration_tests::*;
+use std::time::Duration;
+
+fn setup() -> Env {
+    // `aaaaa-aa` as the canister registry skips mirroring entries into it
+    Env::new("nft", (Some(Principal::management_canister()),))
+}
+
+// Entries must point at a live canister, an empty one is enough
+fn collection(env: &Env) -> AddInput {
+    AddInput {
+        name: String::from("xtc"),
+        description: String::from("XTC is your cycles wallet."),
+        thumbnail: String::from("https://logo_url.com"),
+        frontend: Some(String::from("https://frontend_url.com")),
+        principal_id: env.pic.create_canister(),
+        details: vec![(
+            String::from("standard"),
+            DetailValue::Text(String::from("DIP721v2")),
+        )],
+    }
+}
+
+fn add(env: &Env, sender: Principal, input: AddInput) -> OperationResponse {
+    let (response,): (OperationResponse,) = env.update(sender, "add", (None::<Principal>, input));
+    response
+}
+
+fn get(env: &Env, principal_id: Principal) -> Option<Entry> {
+    let (entry,): (Option<Entry>,) = env.query(user(), "get", (principal_id,));
+    entry
+}
+
+#[test]
+fn test_name() {
+    let env = setup();
+    let (name,): (String,) = env.query(user(), "name", ());
+    assert_eq!(name, "NFT Registry Canister");
+}
+
+#[test]
+fn test_supported_features() {
+    let env = setup();
+    let (features,): (Vec<String>,) = env.query(user(), "supported_features", ());
+    assert!(features.contains(&String::from("pagination")));
+    assert!(features.contains(&String::from("certification")));
+}
+
+#[test]
+fn test_add_and_get() {
+    let env = setup();
+    let input = collection(&env);
+    assert_eq!(add(&env, admin(), input.clone()), Ok(()));
+
+    let entry = get(&env, input.principal_id).unwrap();
+    assert_eq!(entry.name, input.name);
+    assert_eq!(entry.details, input.details);
+    assert_eq!(entry.submitter, admin());
+}
+
+#[test]
+fn test_add_fails_because_of_unauthorized_caller() {
+    let env = setup();
+    let input = collection(&env);
+    assert_eq!(
+        add(&env, user(), input.clone()),
+        Err(OperationError::NotAuthorized {
+            required_role: Role::Admin,
+            caller: user(),
+        })
+    );
+    assert_eq!(get(&env, input.principal_id), None);
+}
+
+#[test]
+fn test_add_fails_because_of_bad_details() {
+    let env = setup();
+    let mut input = collection(&env);
+    input.details = vec![];
+    assert_eq!(
+        add(&env, admin(), input),
+        Err(OperationError::Validation(vec![FieldError {
+            field: String::from("details.standard"),
+            reason: String::from("missing"),
+        }]))
+    );
+}
+
+#[test]
+fn test_add_reports_every_invalid_field() {
+    let env = setup();
+    let mut input = collection(&env);
+    input.thumbnail = String::from("not a url");
+    input.frontend = Some(String::from("not a url either"));
+    input
+        .details
+        .push((String::from("unknown"), DetailValue::True));
+
+    let fields = match add(&env, admin(), input) {
+        Err(OperationError::Validation(errors)) => errors
+            .into_iter()
+            .map(|error| error.field)
+            .collect::<Vec<_>>(),
+        response => panic!("unexpected response {:?}", response),
+    };
+    assert_eq!(fields, vec!["thumbnail", "frontend", "details.unknown"]);
+}
+
+#[test]
+fn test_add_rejects_an_icrc7_collection_without_the_interface() {
+    let env = setup();
+    let mut input = collection(&env);
+    input.details = vec![(
+        String::from("standard"),
+        DetailValue::Text(String::from("ICRC-7")),
+    )];
+
+    let fields = match add(&env, admin(), input) {
+        Err(OperationError::Validation(errors)) => errors
+            .into_iter()
+            .map(|error| error.field)
+            .collect::<Vec<_>>(),
+        response => panic!("unexpected response {:?}", response),
+    };
+    assert_eq!(fields, vec!["details.standard"]);
+}
+
+#[test]
+fn test_add_fails_while_the_external_validator_is_unreachable() {
+    let env = setup();
+    let validator = env.pic.create_canister();
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "set_external_validator", (Some(validator),));
+    assert_eq!(response, Ok(()));
+
+    let input = collection(&env);
+    assert!(matches!(
+        add(&env, admin(), input.clone()),
+        Err(OperationError::Unknown(_))
+    ));
+    assert_eq!(get(&env, input.principal_id), None);
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "set_external_validator", (None::<Principal>,));
+    assert_eq!(response, Ok(()));
+    assert_eq!(add(&env, admin(), input), Ok(()));
+}
+
+#[test]
+fn test_marketplaces() {
+    let env = setup();
+    let marketplace = |name: &str, url: &str| {
+        DetailValue::Vec(vec![
+            DetailValue::Text(String::from(name)),
+            DetailValue::Text(String::from(url)),
+        ])
+    };
+
+    let mut input = collection(&env);
+    input.details.push((
+        String::from("marketplaces"),
+        DetailValue::Vec(vec![marketplace("Entrepot", "not a url")]),
+    ));
+    assert_eq!(
+        add(&env, admin(), input.clone()),
+        Err(OperationError::Validation(vec![FieldError {
+            field: String::from("details.marketplaces"),
+            reason: String::from("invalid URL"),
+        }]))
+    );
+
+    input.details[1].1 = DetailValue::Vec(vec![marketplace(
+        "Entrepot",
+        "https://entrepot.app/marketplace/xtc",
+    )]);
+    assert_eq!(add(&env, admin(), input.clone()), Ok(()));
+
+    let (marketplaces,): (Vec<(String, String)>,) =
+        env.query(user(), "get_marketplaces", (input.principal_id,));
+    assert_eq!(
+        marketplaces,
+        vec![(
+            String::from("Entrepot"),
+            String::from("https://entrepot.app/marketplace/xtc")
+        )]
+    );
+}
+
+#[test]
+fn test_add_fails_because_the_principal_is_not_a_canister() {
+    let env = setup();
+    let mut input = collection(&env);
+    input.principal_id = user();
+    assert_eq!(
+        add(&env, admin(), input),
+        Err(OperationError::BadParameters)
+    );
+}
+
+#[test]
+fn test_stale_submissions_expire() {
+    let env = setup();
+    let input = collection(&env);
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "set_submission_ttl", (1_000_000_000u64,));
+    assert_eq!(response, Ok(()));
+    let (response,): (OperationResponse,) = env.update(user(), "submit", (input.clone(),));
+    assert_eq!(response, Ok(()));
+
+    env.pic.advance_time(Duration::from_secs(2 * 60 * 60));
+    for _ in 0..3 {
+        env.pic.tick();
+    }
+
+    let (pending,): (Vec<Submission>,) = env.query(user(), "get_submissions", ());
+    assert!(pending.is_empty());
+    let (expired,): (Result<Vec<ExpiredSubmission>, OperationError>,) =
+        env.query(admin(), "get_expired", ());
+    let expired = expired.unwrap();
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].submission.entry, input);
+    assert_eq!(expired[0].refund_error, None);
+}
+
+// There is no CAP router in the test environment, so the handshake never succeeds
+#[test]
+fn test_failed_side_effects_are_kept() {
+    let env = setup();
+    add(&env, admin(), collection(&env)).unwrap();
+
+    let failed_tasks = |sender: Principal| -> Result<Vec<RetryTask>, OperationError> {
+        let (tasks,): (Result<Vec<RetryTask>, OperationError>,) =
+            env.query(sender, "get_failed_tasks", ());
+        tasks
+    };
+    assert_eq!(failed_tasks(admin()), Ok(vec![]));
+    for _ in 0..12 {
+        env.pic.advance_time(Duration::from_secs(15 * 60));
+        for _ in 0..3 {
+            env.pic.tick();
+        }
+    }
+
+    let tasks = failed_tasks(admin()).unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].kind, TaskKind::CapHandshake);
+    assert!(failed_tasks(user()).is_err());
+
+    let retry = |id: u64| -> OperationResponse {
+        let (response,): (OperationResponse,) = env.update(admin(), "retry_task", (id,));
+        response
+    };
+    assert_eq!(retry(tasks[0].id + 1), Err(OperationError::NonExistentItem));
+    assert_eq!(retry(tasks[0].id), Ok(()));
+    for _ in 0..3 {
+        env.pic.tick();
+    }
+    assert_eq!(failed_tasks(admin()).unwrap()[0].id, tasks[0].id);
+}
+
+#[test]
+fn test_featured_entries_follow_their_pins() {
+    let env = setup();
+    let (first, second) = (collection(&env), collection(&env));
+    add(&env, admin(), first.clone()).unwrap();
+    add(&env, admin(), second.clone()).unwrap();
+
+    let pin = |sender: Principal, principal_id: Principal, position: u32| -> OperationResponse {
+        let (response,): (OperationResponse,) =
+            env.update(sender, "pin_entry", (principal_id, position));
+        response
+    };
+    let featured = || -> Vec<Principal> {
+        let (entries,): (Vec<Entry>,) = env.query(user(), "get_featured", ());
+        entries
+            .into_iter()
+            .map(|entry| entry.principal_id)
+            .collect()
+    };
+    assert!(pin(user(), first.principal_id, 0).is_err());
+    pin(admin(), first.principal_id, 0).unwrap();
+    pin(admin(), second.principal_id, 0).unwrap();
+    assert_eq!(featured(), vec![second.principal_id, first.principal_id]);
+    pin(admin(), second.principal_id, 10).unwrap();
+    assert_eq!(featured(), vec![first.principal_id, second.principal_id]);
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "remove", (None::<Principal>, first.principal_id));
+    response.unwrap();
+    assert_eq!(featured(), vec![second.principal_id]);
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "unpin_entry", (second.principal_id,));
+    response.unwrap();
+    assert_eq!(featured(), vec![]);
+}
+
+#[test]
+fn test_get_cap_root_of() {
+    let env = setup();
+    let (linked, unlinked) = (collection(&env), collection(&env));
+    let root = env.pic.create_canister();
+    let mut input = linked.clone();
+    input
+        .details
+        .push((String::from("cap_root"), DetailValue::Principal(root)));
+    add(&env, admin(), input).unwrap();
+    add(&env, admin(), unlinked.clone()).unwrap();
+
+    let cap_root_of = |principal_id: Principal| -> Option<Principal> {
+        let (root,): (Option<Principal>,) = env.query(user(), "get_cap_root_of", (principal_id,));
+        root
+    };
+    assert_eq!(cap_root_of(linked.principal_id), Some(root));
+    assert_eq!(cap_root_of(unlinked.principal_id), None);
+}
+
+#[test]
+fn test_metrics_count_calls_per_endpoint() {
+    let env = setup();
+    add(&env, admin(), collection(&env)).unwrap();
+    add(&env, admin(), collection(&env)).unwrap();
+    env.pic.advance_time(Duration::from_secs(61));
+    env.pic.tick();
+
+    let (metrics,): (CanisterMetrics,) = env.query(user(), "get_metrics", ());
+    let sample = |samples: &[Metric], name: &str, label: (&str, &str)| -> Option<u64> {
+        samples
+            .iter()
+            .find(|metric| {
+                metric.name == name
+                    && metric.labels == vec![(label.0.to_string(), label.1.to_string())]
+            })
+            .map(|metric| metric.value)
+    };
+    assert_eq!(
+        sample(&metrics.counters, "update_calls", ("endpoint", "add")),
+        Some(2)
+    );
+    assert_eq!(
+        sample(&metrics.gauges, "store_size", ("store", "entries")),
+        Some(2)
+    );
+    assert!(metrics
+        .gauges
+        .iter()
+        .any(|metric| metric.name == "cycles_balance"));
+}
+
+#[test]
+fn test_remove() {
+    let env = setup();
+    let input = collection(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "remove", (None::<Principal>, input.principal_id));
+    assert_eq!(response, Ok(()));
+    assert_eq!(get(&env, input.principal_id), None);
+}
+
+#[test]
+fn test_transfer_entry_ownership() {
+    let env = setup();
+    let input = collection(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let transfer = |sender: Principal, new_owner: Principal| -> OperationResponse {
+        let (response,): (OperationResponse,) = env.update(
+            sender,
+            "transfer_entry_ownership",
+            (input.principal_id, new_owner),
+        );
+        response
+    };
+
+    assert_eq!(
+        transfer(user(), user()),
+        Err(OperationError::NotAuthorized {
+            required_role: Role::Submitter,
+            caller: user(),
+        })
+    );
+
+    assert_eq!(transfer(admin(), user()), Ok(()));
+    let entry = get(&env, input.principal_id).unwrap();
+    assert_eq!(entry.submitter, admin());
+    assert_eq!(entry.managed_by, Some(user()));
+
+    // The new owner can hand the entry over again
+    let multisig = Principal::from_slice(&[3; 29]);
+    assert_eq!(transfer(user(), multisig), Ok(()));
+    assert_eq!(
+        get(&env, input.principal_id).unwrap().managed_by,
+        Some(multisig)
+    );
+}
+
+#[test]
+fn test_get_entries_managed_by() {
+    let env = setup();
+    let managed_by = |owner: Principal| -> Vec<Principal> {
+        let (entries,): (Vec<Entry>,) = env.query(user(), "get_entries_managed_by", (owner,));
+        entries
+            .into_iter()
+            .map(|entry| entry.principal_id)
+            .collect()
+    };
+
+    let first = collection(&env);
+    let second = collection(&env);
+    add(&env, admin(), first.clone()).unwrap();
+    add(&env, admin(), second.clone()).unwrap();
+    assert_eq!(managed_by(admin()).len(), 2);
+    assert_eq!(managed_by(user()), vec![]);
+
+    let (response,): (OperationResponse,) = env.update(
+        admin(),
+        "transfer_entry_ownership",
+        (second.principal_id, user()),
+    );
+    response.unwrap();
+    assert_eq!(managed_by(admin()), vec![first.principal_id]);
+    assert_eq!(managed_by(user()), vec![second.principal_id]);
+}
+
+#[test]
+fn test_search_principal() {
+    let env = setup();
+    let input = collection(&env);
+    add(&env, admin(), input.clone()).unwrap();
+    add(&env, admin(), collection(&env)).unwrap();
+
+    let search = |prefix: String| -> Vec<Principal> {
+        let (entries,): (Vec<Entry>,) = env.query(user(), "search_principal", (prefix,));
+        entries
+            .into_iter()
+            .map(|entry| entry.principal_id)
+            .collect()
+    };
+
+    let text = input.principal_id.to_text();
+    assert_eq!(search(text.clone()), vec![input.principal_id]);
+    assert!(search(text[..3].to_uppercase()).contains(&input.principal_id));
+    assert_eq!(search(String::from("zzzzz")), vec![]);
+    assert_eq!(search(String::new()), vec![]);
+}
+
+#[test]
+fn test_get_all_v2_stops_at_the_response_budget() {
+    let env = setup();
+    for _ in 0..3 {
+        add(&env, admin(), collection(&env)).unwrap();
+    }
+
+    let get_all_v2 = || -> (usize, bool) {
+        let (response,): (GetAllResponse,) = env.query(user(), "get_all_v2", ());
+        (response.entries.len(), response.truncated)
+    };
+    assert_eq!(get_all_v2(), (3, false));
+
+    let (response,): (OperationResponse,) = env.update(admin(), "set_response_budget", (1u64,));
+    assert_eq!(response, Ok(()));
+    assert_eq!(get_all_v2(), (0, true));
+}
+
+#[test]
+fn test_namespaces_are_kept_apart() {
+    let env = setup();
+    let config = NamespaceConfig {
+        description: String::from("Collections deployed on the staging network"),
+        writers: vec![user()],
+        validate: false,
+    };
+    let (response,): (OperationResponse,) = env.update(
+        admin(),
+        "create_namespace",
+        (String::from("staging"), config),
+    );
+    assert_eq!(response, Ok(()));
+
+    let input = collection(&env);
+    let (response,): (OperationResponse,) = env.update(
+        user(),
+        "add_in_namespace",
+        (String::from("staging"), input.clone()),
+    );
+    assert_eq!(response, Ok(()));
+    assert_eq!(get(&env, input.principal_id), None);
+
+    let (entry,): (Option<Entry>,) = env.query(
+        user(),
+        "get_in_namespace",
+        (String::from("staging"), input.principal_id),
+    );
+    assert_eq!(entry.unwrap().submitter, user());
+
+    let (entries,): (Vec<Entry>,) = env.query(
+        user(),
+        "list_namespace",
+        (String::from("production"), 0u64, 10u16),
+    );
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn test_maintenance_mode_freezes_writes() {
+    let env = setup();
+    let input = collection(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let set_maintenance_mode = |enabled: bool| {
+        let (response,): (OperationResponse,) =
+            env.update(admin(), "set_maintenance_mode", (enabled,));
+        response.unwrap();
+    };
+
+    set_maintenance_mode(true);
+    assert_eq!(
+        add(&env, admin(), collection(&env)),
+        Err(OperationError::Maintenance)
+    );
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "remove", (None::<Principal>, input.principal_id));
+    assert_eq!(response, Err(OperationError::Maintenance));
+    assert!(get(&env, input.principal_id).is_some());
+
+    set_maintenance_mode(false);
+    assert_eq!(add(&env, admin(), collection(&env)), Ok(()));
+}
+
+#[test]
+fn test_low_cycles_freeze_writes() {
+    let env = setup();
+    let input = collection(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let set_cycles_threshold = |threshold: u64| {
+        let (response,): (OperationResponse,) =
+            env.update(admin(), "set_cycles_threshold", (threshold,));
+        response.unwrap();
+    };
+
+    set_cycles_threshold(u64::MAX);
+    assert_eq!(
+        add(&env, admin(), collection(&env)),
+        Err(OperationError::LowCycles)
+    );
+    assert!(get(&env, input.principal_id).is_some());
+
+    set_cycles_threshold(0);
+    assert_eq!(add(&env, admin(), collection(&env)), Ok(()));
+}
+
+#[test]
+fn test_controller_transfer() {
+    let env = setup();
+    let call = |sender: Principal, method: &str| -> OperationResponse {
+        let (response,): (OperationResponse,) = env.update(sender, method, ());
+        response
+    };
+    let propose = |timelock: Option<u64>| -> OperationResponse {
+        let (response,): (OperationResponse,) =
+            env.update(admin(), "propose_controller", (user(), timelock));
+        response
+    };
+
+    assert_eq!(propose(Some(60 * 60 * 1_000_000_000)), Ok(()));
+    assert!(matches!(
+        call(user(), "accept_controller"),
+        Err(OperationError::Timelocked(_))
+    ));
+    assert_eq!(call(admin(), "cancel_controller_proposal"), Ok(()));
+    assert_eq!(
+        call(user(), "accept_controller"),
+        Err(OperationError::NonExistentItem)
+    );
+
+    assert_eq!(propose(None), Ok(()));
+    assert_eq!(call(user(), "accept_controller"), Ok(()));
+    assert!(add(&env, admin(), collection(&env)).is_err());
+    assert_eq!(add(&env, user(), collection(&env)), Ok(()));
+}
+
+#[test]
+fn test_entries_survive_upgrade() {
+    let env = setup();
+    let input = collection(&env);
+    add(&env, admin(), input.clone()).unwrap();
+    let before = get(&env, input.principal_id);
+
+    env.upgrade();
+
+    assert_eq!(get(&env, input.principal_id), before);
+    let (all,): (Vec<Entry>,) = env.query(user(), "get_all", ());
+    assert_eq!(all.len(), 1);
+}
+
+#[test]
+fn test_reported_entries_go_under_review() {
+    let env = setup();
+    let input = collection(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let report = |reporter: Principal| -> OperationResponse {
+        let reason = String::from("Impersonates another collection");
+        let (response,): (OperationResponse,) =
+            env.update(reporter, "report_entry", (input.principal_id, reason));
+        response
+    };
+    let reported_entries = || -> Vec<ReportedEntry> {
+        let (response,): (Result<Vec<ReportedEntry>, OperationError>,) =
+            env.query(admin(), "get_reported_entries", ());
+        response.unwrap()
+    };
+
+    let (response,): (OperationResponse,) = env.update(admin(), "set_report_threshold", (2u32,));
+    response.unwrap();
+
+    // A second report of the same caller replaces the first one
+    report(user()).unwrap();
+    report(user()).unwrap();
+    assert_eq!(reported_entries()[0].status, ReportStatus::Reported);
+    assert_eq!(reported_entries()[0].reports.len(), 1);
+
+    report(Principal::from_slice(&[3; 29])).unwrap();
+    assert_eq!(reported_entries()[0].status, ReportStatus::UnderReview);
+
+    let (response,): (Result<Vec<ReportedEntry>, OperationError>,) =
+        env.query(user(), "get_reported_entries", ());
+    assert!(response.is_err());
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "dismiss_reports", (input.principal_id,));
+    response.unwrap();
+    assert_eq!(reported_entries(), vec![]);
+}
+
+#[test]
+fn test_exists_returns_the_standard() {
+    let env = setup();
+    let input = collection(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let exists = |principal_id: Principal| -> Option<String> {
+        let (standard,): (Option<String>,) = env.query(user(), "exists", (principal_id,));
+        standard
+    };
+    assert_eq!(exists(input.principal_id), Some(String::from("DIP721v2")));
+    assert_eq!(exists(env.pic.create_canister()), None);
+}
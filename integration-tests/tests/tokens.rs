@@ -0,0 +1,1205 @@
+use candid::Principal;
+use integration_tests::types::*;
+use integration_tests::*;
+
+fn setup() -> Env {
+    Env::new("tokens", ()).with_canister_registry()
+}
+
+// Entries must point at a live canister, an empty one is enough
+fn token(env: &Env) -> AddInput {
+    AddInput {
+        name: String::from("Wrapped ICP"),
+        description: String::from("Wrapped ICP on the DIP20 standard."),
+        thumbnail: String::from("https://logo_url.com"),
+        frontend: Some(String::from("https://frontend_url.com")),
+        principal_id: env.pic.create_canister(),
+        details: vec![
+            (
+                String::from("symbol"),
+                DetailValue::Text(String::from("WICP")),
+            ),
+            (
+                String::from("standard"),
+                DetailValue::Text(String::from("DIP20")),
+            ),
+            (String::from("total_supply"), DetailValue::U64(1_000_000)),
+            (String::from("verified"), DetailValue::True),
+            (String::from("decimals"), DetailValue::U64(8)),
+            (String::from("fee"), DetailValue::U64(0)),
+        ],
+    }
+}
+
+fn add(env: &Env, sender: Principal, input: AddInput) -> OperationResponse {
+    let (response,): (OperationResponse,) = env.update(sender, "add", (None::<Principal>, input));
+    response
+}
+
+fn get(env: &Env, principal_id: Principal) -> Option<Entry> {
+    let (entry,): (Option<Entry>,) = env.query(user(), "get", (principal_id,));
+    entry
+}
+
+#[test]
+fn test_name() {
+    let env = setup();
+    let (name,): (String,) = env.query(user(), "name", ());
+    assert_eq!(name, "Token Registry Canister");
+}
+
+#[test]
+fn test_add_and_get() {
+    let env = setup();
+    let input = token(&env);
+    assert_eq!(add(&env, admin(), input.clone()), Ok(()));
+
+    let entry = get(&env, input.principal_id).unwrap();
+    assert_eq!(entry.name, input.name);
+    assert_eq!(entry.details, input.details);
+    assert_eq!(entry.submitter, admin());
+}
+
+#[test]
+fn test_add_fails_because_of_unauthorized_caller() {
+    let env = setup();
+    assert_eq!(
+        add(&env, user(), token(&env)),
+        Err(OperationError::NotAuthorized {
+            required_role: Role::Admin,
+            caller: user(),
+        })
+    );
+}
+
+#[test]
+fn test_add_accepts_details_in_any_order() {
+    let env = setup();
+    let mut input = token(&env);
+    input.details.swap(0, 1);
+    assert_eq!(add(&env, admin(), input), Ok(()));
+}
+
+#[test]
+fn test_add_fails_because_of_duplicated_details() {
+    let env = setup();
+    let mut input = token(&env);
+    input.details.push(input.details[0].clone());
+    assert_eq!(
+        add(&env, admin(), input),
+        Err(OperationError::Validation(vec![FieldError {
+            field: String::from("details.symbol"),
+            reason: String::from("duplicated key"),
+        }]))
+    );
+}
+
+#[test]
+fn test_failed_adds_are_logged() {
+    let env = setup();
+    let mut input = token(&env);
+    input.details.push(input.details[0].clone());
+    assert!(add(&env, admin(), input).is_err());
+    add(&env, admin(), token(&env)).unwrap();
+
+    let logs = |sender: Principal, level: LogLevel| -> Result<Vec<LogRecord>, OperationError> {
+        let (logs,): (Result<Vec<LogRecord>, OperationError>,) =
+            env.query(sender, "get_logs", (level, 10u16));
+        logs
+    };
+    let warnings = logs(admin(), LogLevel::Warn).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].endpoint, "add");
+    assert_eq!(warnings[0].caller, admin());
+    assert!(warnings[0].message.contains("duplicated key"));
+    assert_eq!(logs(admin(), LogLevel::Error), Ok(vec![]));
+    assert!(logs(user(), LogLevel::Debug).is_err());
+}
+
+#[test]
+fn test_add_fails_because_of_non_finite_floats() {
+    let env = setup();
+    for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let mut input = token(&env);
+        input.details[5].1 = DetailValue::Vec(vec![DetailValue::Float(value)]);
+        assert_eq!(
+            add(&env, admin(), input.clone()),
+            Err(OperationError::Validation(vec![FieldError {
+                field: String::from("details.fee"),
+                reason: String::from("floats must be finite"),
+            }]))
+        );
+        assert_eq!(get(&env, input.principal_id), None);
+    }
+}
+
+#[test]
+fn test_add_fails_because_of_deeply_nested_details() {
+    let env = setup();
+    let mut input = token(&env);
+    let mut value = DetailValue::U64(0);
+    for _ in 0..4 {
+        value = DetailValue::Vec(vec![value]);
+    }
+    input.details[5].1 = value;
+    assert_eq!(
+        add(&env, admin(), input),
+        Err(OperationError::Validation(vec![FieldError {
+            field: String::from("details.fee"),
+            reason: String::from("nested too deeply"),
+        }]))
+    );
+}
+
+#[test]
+fn test_anonymous_principal_is_rejected() {
+    let env = setup();
+    let anonymous = Principal::anonymous();
+    let input = token(&env);
+
+    let (response,): (OperationResponse,) = env.update(anonymous, "submit", (input.clone(),));
+    assert_eq!(response, Err(OperationError::Anonymous));
+
+    let (response,): (OperationResponse,) = env.update(admin(), "add_admin", (anonymous,));
+    assert_eq!(response, Err(OperationError::Anonymous));
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "add", (Some(anonymous), input.clone()));
+    assert_eq!(response, Err(OperationError::Anonymous));
+    assert_eq!(get(&env, input.principal_id), None);
+}
+
+#[test]
+fn test_remove_fails_because_of_unauthorized_caller() {
+    let env = setup();
+    let input = token(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let (response,): (OperationResponse,) =
+        env.update(user(), "remove", (None::<Principal>, input.principal_id));
+    assert_eq!(
+        response,
+        Err(OperationError::NotAuthorized {
+            required_role: Role::Admin,
+            caller: user(),
+        })
+    );
+    assert!(get(&env, input.principal_id).is_some());
+}
+
+#[test]
+fn test_entries_survive_upgrade() {
+    let env = setup();
+    let input = token(&env);
+    add(&env, admin(), input.clone()).unwrap();
+    let before = get(&env, input.principal_id);
+
+    env.upgrade();
+
+    assert_eq!(get(&env, input.principal_id), before);
+    let (all,): (Vec<Entry>,) = env.query(user(), "get_all", (None::<String>, None::<Page>));
+    assert_eq!(all.len(), 1);
+}
+
+#[test]
+fn test_registry_hash_tracks_the_entries() {
+    let env = setup();
+    let registry_hash = || -> RegistryHash {
+        let (hash,): (RegistryHash,) = env.query(user(), "registry_hash", ());
+        hash
+    };
+
+    let empty = registry_hash();
+    assert_eq!(empty.entries, 0);
+
+    let input = token(&env);
+    add(&env, admin(), input.clone()).unwrap();
+    let one = registry_hash();
+    assert_eq!(one.entries, 1);
+    assert_ne!(one.hash, empty.hash);
+
+    env.upgrade();
+    assert_eq!(registry_hash().hash, one.hash);
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "remove", (None::<Principal>, input.principal_id));
+    response.unwrap();
+    assert_eq!(registry_hash().hash, empty.hash);
+}
+
+#[test]
+fn test_approval_rules() {
+    let env = setup();
+    let set_rule = |verify_ledger: bool| {
+        let rules = vec![ApprovalRule {
+            standard: String::from("dip20"),
+            submitters: vec![user()],
+            verify_ledger,
+        }];
+        let (response,): (OperationResponse,) = env.update(admin(), "set_approval_rules", (rules,));
+        response.unwrap();
+    };
+    let submit = |input: &AddInput| {
+        let (response,): (OperationResponse,) = env.update(user(), "submit", (input.clone(),));
+        response.unwrap();
+    };
+
+    set_rule(false);
+    let trusted = token(&env);
+    submit(&trusted);
+    assert_eq!(get(&env, trusted.principal_id).unwrap().submitter, user());
+
+    // An empty canister can't confirm the submitted symbol and decimals
+    set_rule(true);
+    let unverified = token(&env);
+    submit(&unverified);
+    assert_eq!(get(&env, unverified.principal_id), None);
+    let (pending,): (Vec<Submission>,) = env.query(user(), "get_submissions", ());
+    assert_eq!(pending.len(), 1);
+}
+
+#[test]
+fn test_review_queues_follow_the_standard() {
+    let env = setup();
+    let reviewer = Principal::from_slice(&[3; 29]);
+    let input = token(&env);
+    let (response,): (OperationResponse,) = env.update(user(), "submit", (input.clone(),));
+    response.unwrap();
+
+    let assign = |standard: &str| {
+        let (response,): (OperationResponse,) = env.update(
+            admin(),
+            "assign_reviewers",
+            (String::from(standard), vec![reviewer]),
+        );
+        response.unwrap();
+    };
+    let queue = || -> Vec<Principal> {
+        let (queue,): (Result<Vec<Submission>, OperationError>,) =
+            env.query(reviewer, "get_my_review_queue", ());
+        queue
+            .unwrap()
+            .into_iter()
+            .map(|submission| submission.entry.principal_id)
+            .collect()
+    };
+    let approve = || -> OperationResponse {
+        let (response,): (OperationResponse,) =
+            env.update(reviewer, "approve_submission", (input.principal_id,));
+        response
+    };
+
+    assign("ICRC-1");
+    assert_eq!(queue(), vec![]);
+    assert!(approve().is_err());
+
+    assign("dip20");
+    assert_eq!(queue(), vec![input.principal_id]);
+    approve().unwrap();
+    assert_eq!(get(&env, input.principal_id).unwrap().submitter, user());
+}
+
+#[test]
+fn test_symbol_collisions() {
+    let env = setup();
+    let original = token(&env);
+    let mut impostor = token(&env);
+    impostor.details[0].1 = DetailValue::Text(String::from("wicp"));
+    impostor.details[3].1 = DetailValue::False;
+    let mut other = token(&env);
+    other.details[0].1 = DetailValue::Text(String::from("XTC"));
+    add(&env, admin(), original.clone()).unwrap();
+    add(&env, admin(), impostor.clone()).unwrap();
+    add(&env, admin(), other.clone()).unwrap();
+
+    assert_eq!(
+        get(&env, original.principal_id).unwrap().conflicted,
+        Some(false)
+    );
+    assert_eq!(
+        get(&env, impostor.principal_id).unwrap().conflicted,
+        Some(true)
+    );
+    assert_eq!(
+        get(&env, other.principal_id).unwrap().conflicted,
+        Some(false)
+    );
+
+    let (collisions,): (Vec<SymbolCollision>,) = env.query(user(), "get_symbol_collisions", ());
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].symbol, "WICP");
+    assert_eq!(collisions[0].principals.len(), 2);
+}
+
+#[test]
+fn test_usage_stats() {
+    let env = setup();
+    let stats = || -> UsageStats {
+        let (stats,): (Result<UsageStats, OperationError>,) =
+            env.query(admin(), "get_usage_stats", ());
+        stats.unwrap()
+    };
+
+    add(&env, admin(), token(&env)).unwrap();
+    assert_eq!(stats().methods, vec![]);
+
+    let (response,): (OperationResponse,) = env.update(admin(), "set_usage_tracking", (true,));
+    assert_eq!(response, Ok(()));
+    add(&env, admin(), token(&env)).unwrap();
+    add(&env, user(), token(&env)).unwrap_err();
+
+    let stats = stats();
+    assert!(stats.enabled);
+    assert_eq!(stats.methods, vec![(String::from("add"), 2)]);
+    assert_eq!(stats.callers.len(), 2);
+    assert!(stats.callers.contains(&(admin(), 1)));
+    assert!(stats.callers.contains(&(user(), 1)));
+}
+
+#[test]
+fn test_protected_entries_need_a_second_admin() {
+    let env = setup();
+    let second_admin = Principal::from_slice(&[7; 29]);
+    let (response,): (OperationResponse,) = env.update(admin(), "add_admin", (second_admin,));
+    response.unwrap();
+
+    let mut input = token(&env);
+    add(&env, admin(), input.clone()).unwrap();
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "protect_entry", (input.principal_id,));
+    assert_eq!(response, Ok(()));
+
+    input.name = String::from("Renamed");
+    assert_eq!(
+        add(&env, admin(), input.clone()),
+        Err(OperationError::PendingApproval(0))
+    );
+    assert_eq!(get(&env, input.principal_id).unwrap().name, "Wrapped ICP");
+
+    let confirm = |sender: Principal| -> OperationResponse {
+        let (response,): (OperationResponse,) = env.update(sender, "confirm_change", (0u64,));
+        response
+    };
+    assert_eq!(confirm(admin()), Err(OperationError::BadParameters));
+    assert_eq!(confirm(second_admin), Ok(()));
+    assert_eq!(get(&env, input.principal_id).unwrap().name, "Renamed");
+}
+
+#[test]
+fn test_get_all_by_standard() {
+    let env = setup();
+    let dip20 = token(&env);
+    let mut icrc1 = token(&env);
+    icrc1.details[1].1 = DetailValue::Text(String::from("ICRC1"));
+    add(&env, admin(), dip20.clone()).unwrap();
+    add(&env, admin(), icrc1.clone()).unwrap();
+
+    let (entries,): (Vec<Entry>,) = env.query(
+        user(),
+        "get_all",
+        (Some(String::from("ICRC1")), None::<Page>),
+    );
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].principal_id, icrc1.principal_id);
+
+    let page = Page {
+        offset: 1,
+        limit: 10,
+    };
+    let (entries,): (Vec<Entry>,) = env.query(user(), "get_all", (None::<String>, Some(page)));
+    assert_eq!(entries.len(), 1);
+}
+
+#[test]
+fn test_get_all_by_origin_chain() {
+    let env = setup();
+    let mut orphan = token(&env);
+    orphan.details.push((
+        String::from("bridge"),
+        DetailValue::Principal(env.pic.create_canister()),
+    ));
+    assert_eq!(
+        add(&env, admin(), orphan),
+        Err(OperationError::Validation(vec![FieldError {
+            field: String::from("details.bridge"),
+            reason: String::from("requires details.origin_chain"),
+        }]))
+    );
+
+    let native = token(&env);
+    let mut wrapped = token(&env);
+    wrapped.details.push((
+        String::from("origin_chain"),
+        DetailValue::Text(String::from("ethereum")),
+    ));
+    wrapped.details.push((
+        String::from("origin_contract"),
+        DetailValue::Text(String::from("0xdac17f958d2ee523a2206206994597c13d831ec7")),
+    ));
+    add(&env, admin(), native).unwrap();
+    add(&env, admin(), wrapped.clone()).unwrap();
+
+    let (entries,): (Vec<Entry>,) = env.query(
+        user(),
+        "get_all_by_origin_chain",
+        (String::from("Ethereum"),),
+    );
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].principal_id, wrapped.principal_id);
+}
+
+#[test]
+fn test_get_principals_follows_get_all() {
+    let env = setup();
+    for _ in 0..3 {
+        add(&env, admin(), token(&env)).unwrap();
+    }
+
+    let (all,): (Vec<Entry>,) = env.query(user(), "get_all", (None::<String>, None::<Page>));
+    let expected: Vec<Principal> = all.iter().map(|entry| entry.principal_id).collect();
+    let (principals,): (Vec<Principal>,) = env.query(user(), "get_all_principals", ());
+    assert_eq!(principals, expected);
+
+    let page = Page {
+        offset: 1,
+        limit: 1,
+    };
+    let (principals,): (Vec<Principal>,) = env.query(user(), "get_principals", (page,));
+    assert_eq!(principals, expected[1..2]);
+}
+
+#[test]
+fn test_emergency_delist_skips_locks() {
+    let env = setup();
+    let input = token(&env);
+    add(&env, admin(), input.clone()).unwrap();
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "lock_entry", (input.principal_id,));
+    response.unwrap();
+
+    let delist = |sender: Principal| -> OperationResponse {
+        let reason = String::from("Phishing site in the frontend");
+        let (response,): (OperationResponse,) =
+            env.update(sender, "emergency_delist", (input.principal_id, reason));
+        response
+    };
+    assert!(delist(user()).is_err());
+    assert_eq!(delist(admin()), Ok(()));
+    assert_eq!(get(&env, input.principal_id), None);
+
+    let (delistings,): (Vec<Delisting>,) = env.query(user(), "get_delistings", ());
+    assert_eq!(delistings.len(), 1);
+    assert_eq!(delistings[0].reason, "Phishing site in the frontend");
+    assert_eq!(delist(admin()), Err(OperationError::NonExistentItem));
+}
+
+#[test]
+fn test_blocked_principals_are_delisted_and_rejected() {
+    let env = setup();
+    let input = token(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let block = |sender: Principal| -> OperationResponse {
+        let reason = String::from("Exploited ledger");
+        let (response,): (OperationResponse,) =
+            env.update(sender, "block_principal", (input.principal_id, reason));
+        response
+    };
+    assert!(block(user()).is_err());
+    assert_eq!(block(admin()), Ok(()));
+    assert_eq!(get(&env, input.principal_id), None);
+    let (blocked,): (bool,) = env.query(user(), "is_blocked", (input.principal_id,));
+    assert!(blocked);
+    let (delistings,): (Vec<Delisting>,) = env.query(user(), "get_delistings", ());
+    assert_eq!(delistings[0].reason, "Exploited ledger");
+
+    let rejected = Err(OperationError::Validation(vec![FieldError {
+        field: String::from("principal_id"),
+        reason: String::from("principal is blocked"),
+    }]));
+    assert_eq!(add(&env, admin(), input.clone()), rejected);
+    let (response,): (OperationResponse,) = env.update(user(), "submit", (input.clone(),));
+    assert_eq!(response, rejected);
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "unblock_principal", (input.principal_id,));
+    assert_eq!(response, Ok(()));
+    add(&env, admin(), input).unwrap();
+}
+
+#[test]
+fn test_create_child_registry() {
+    let env = setup();
+    let input = token(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let config = ChildRegistryConfig {
+        name: String::from("Ecosystem tokens"),
+        cycles: 500_000_000_000,
+        admins: vec![user()],
+        controllers: vec![admin()],
+        entries: vec![input.principal_id],
+        wasm: None,
+    };
+    let create = |sender: Principal, config: ChildRegistryConfig| {
+        let (response,): (Result<Principal, OperationError>,) =
+            env.update(sender, "create_child_registry", (config,));
+        response
+    };
+    assert!(create(user(), config.clone()).is_err());
+    assert_eq!(
+        create(admin(), config.clone()),
+        Err(OperationError::BadParameters)
+    );
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "set_registry_wasm", (wasm("tokens"),));
+    response.unwrap();
+    let child = create(admin(), config).unwrap();
+
+    let (entry,): (Option<Entry>,) = env.query_at(child, user(), "get", (input.principal_id,));
+    assert_eq!(entry.map(|entry| entry.name), Some(input.name));
+    let (children,): (Vec<ChildRegistry>,) = env.query(user(), "get_child_registries", ());
+    assert_eq!(children.len(), 1);
+    assert_eq!((children[0].canister_id, children[0].entries), (child, 1));
+}
+
+#[test]
+fn test_get_all_projected() {
+    let env = setup();
+    let input = token(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let project = |fields: Vec<&str>| {
+        let fields: Vec<String> = fields.into_iter().map(String::from).collect();
+        let (response,): (Result<Vec<PartialEntry>, OperationError>,) = env.query(
+            user(),
+            "get_all_projected",
+            (fields, None::<String>, None::<Page>),
+        );
+        response
+    };
+    assert_eq!(
+        project(vec!["name", "details.symbol", "details.missing"]),
+        Ok(vec![PartialEntry {
+            principal_id: input.principal_id,
+            fields: vec![
+                (String::from("name"), DetailValue::Text(input.name)),
+                (
+                    String::from("details.symbol"),
+                    DetailValue::Text(String::from("WICP")),
+                ),
+            ],
+        }])
+    );
+    assert_eq!(project(vec!["owner"]), Err(OperationError::BadParameters));
+}
+
+#[test]
+fn test_add_from_manifest() {
+    let env = setup();
+    let principal_id = env.pic.create_canister();
+    let manifest = |principal_id: &str, fee: &str| {
+        format!(
+            r#"{{
+                "canisters": {{
+                    "wicp": {{
+                        "principal_id": "{}",
+                        "description": "Wrapped ICP on the DIP20 standard.",
+                        "thumbnail": "https://logo_url.com",
+                        "details": {{
+                            "symbol": "WICP",
+                            "standard": "DIP20",
+                            "total_supply": 1000000,
+                            "verified": true,
+                            "decimals": 8,
+                            "fee": {}
+                        }}
+                    }}
+                }}
+            }}"#,
+            principal_id, fee
+        )
+    };
+    let add_from_manifest = |sender: Principal, json_text: String| {
+        let (response,): (Result<u64, OperationError>,) =
+            env.update(sender, "add_from_manifest", (json_text,));
+        response
+    };
+
+    assert_eq!(
+        add_from_manifest(admin(), manifest("not a principal", "null")),
+        Err(OperationError::Validation(vec![FieldError {
+            field: String::from("canisters.wicp.principal_id"),
+            reason: String::from("invalid principal"),
+        }]))
+    );
+    assert!(add_from_manifest(user(), manifest(&principal_id.to_text(), "0")).is_err());
+    assert_eq!(
+        add_from_manifest(admin(), manifest(&principal_id.to_text(), "0")),
+        Ok(1)
+    );
+    let entry = get(&env, principal_id).unwrap();
+    assert_eq!(entry.name, "wicp");
+    assert!(entry
+        .details
+        .contains(&(String::from("fee"), DetailValue::U64(0))));
+}
+
+#[test]
+fn test_entry_callbacks() {
+    let env = setup();
+    let input = token(&env);
+    let principal_id = input.principal_id;
+    let mut other = token(&env);
+    other.details[0].1 = DetailValue::Text(String::from("XTC"));
+    let target = other.principal_id;
+    add(&env, admin(), input).unwrap();
+    add(&env, admin(), other).unwrap();
+
+    let set_target = |sender: Principal, target: Principal, method: &str| {
+        let (response,): (OperationResponse,) = env.update(
+            sender,
+            "set_entry_callback",
+            (principal_id, target, method.to_string()),
+        );
+        response
+    };
+    let set_callback = |sender: Principal, method: &str| set_target(sender, target, method);
+    let callback = || {
+        let (callback,): (Option<(Principal, String)>,) =
+            env.query(user(), "get_entry_callback", (principal_id,));
+        callback
+    };
+
+    assert!(matches!(
+        set_callback(user(), "on_entry_status"),
+        Err(OperationError::NotAuthorized { .. })
+    ));
+    assert_eq!(
+        set_callback(admin(), ""),
+        Err(OperationError::BadParameters)
+    );
+    // Only the entry itself and entries with the same manager can be called
+    assert_eq!(
+        set_target(admin(), env.pic.create_canister(), "on_entry_status"),
+        Err(OperationError::BadParameters)
+    );
+    set_target(admin(), principal_id, "on_entry_status").unwrap();
+    assert_eq!(
+        callback(),
+        Some((principal_id, String::from("on_entry_status")))
+    );
+    set_callback(admin(), "on_entry_status").unwrap();
+    assert_eq!(callback(), Some((target, String::from("on_entry_status"))));
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "remove_entry_callback", (principal_id,));
+    response.unwrap();
+    assert_eq!(callback(), None);
+}
+
+#[test]
+fn test_get_v2_is_versioned() {
+    let env = setup();
+    let input = token(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let (envelope,): (Envelope<Option<Entry>>,) =
+        env.query(user(), "get_v2", (input.principal_id,));
+    assert_eq!(envelope.schema_version, 1);
+    assert_eq!(envelope.data, get(&env, input.principal_id));
+    assert!(envelope.data.is_some());
+}
+
+#[test]
+fn test_load_seed_is_idempotent() {
+    let env = setup();
+    let load_seed = |sender: Principal| -> Result<u64, OperationError> {
+        let (response,): (Result<u64, OperationError>,) = env.update(sender, "load_seed", ());
+        response
+    };
+
+    assert!(matches!(
+        load_seed(user()),
+        Err(OperationError::NotAuthorized { .. })
+    ));
+    assert_eq!(load_seed(admin()), Ok(5));
+    assert_eq!(load_seed(admin()), Ok(0));
+
+    let icp = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+    assert_eq!(get(&env, icp).unwrap().name, "Internet Computer");
+}
+
+#[test]
+fn test_get_display_info() {
+    let env = setup();
+    let mut wicp = token(&env);
+    wicp.details[5].1 = DetailValue::U64(10_000);
+    let mut text_fee = token(&env);
+    text_fee.details[4].1 = DetailValue::U64(0);
+    text_fee.details[5].1 = DetailValue::Text(String::from("25"));
+    add(&env, admin(), wicp.clone()).unwrap();
+    add(&env, admin(), text_fee.clone()).unwrap();
+
+    let display_info = |principal_id: Principal| -> Option<DisplayInfo> {
+        let (info,): (Option<DisplayInfo>,) =
+            env.query(user(), "get_display_info", (principal_id,));
+        info
+    };
+    assert_eq!(
+        display_info(wicp.principal_id),
+        Some(DisplayInfo {
+            symbol: String::from("WICP"),
+            decimals: 8,
+            fee: Some(10_000),
+            formatted_fee: Some(String::from("0.0001 WICP")),
+        })
+    );
+    assert_eq!(
+        display_info(text_fee.principal_id).and_then(|info| info.formatted_fee),
+        Some(String::from("25 WICP"))
+    );
+    assert_eq!(display_info(env.pic.create_canister()), None);
+}
+
+#[test]
+fn test_get_detail_schema() {
+    let env = setup();
+
+    let schema = |standard: &str| -> Result<Vec<DetailKeySpec>, OperationError> {
+        let (schema,): (Result<Vec<DetailKeySpec>, OperationError>,) =
+            env.query(user(), "get_detail_schema", (standard.to_string(),));
+        schema
+    };
+    let icrc1 = schema("ICRC-1").unwrap();
+    assert!(icrc1.contains(&DetailKeySpec {
+        key: String::from("symbol"),
+        value_type: DetailType::Text,
+        required: true,
+    }));
+    assert!(icrc1.contains(&DetailKeySpec {
+        key: String::from("minting_account"),
+        value_type: DetailType::Principal,
+        required: false,
+    }));
+    assert_eq!(schema("icrc1"), Ok(icrc1));
+    assert_eq!(schema("FOO"), Err(OperationError::BadParameters));
+
+    let fee = |standard: &str| -> Option<bool> {
+        let schema = schema(standard).unwrap();
+        let spec = schema.into_iter().find(|spec| spec.key == "fee")?;
+        Some(spec.required)
+    };
+    assert_eq!(fee("DIP20"), Some(true));
+    assert_eq!(fee("EXT"), Some(false));
+    assert!(schema("DIP20")
+        .unwrap()
+        .iter()
+        .all(|spec| spec.key != "minting_account"));
+}
+
+#[test]
+fn test_migrate_detail_key() {
+    let env = setup();
+    let (first, second) = (token(&env), token(&env));
+    add(&env, admin(), first.clone()).unwrap();
+    add(&env, admin(), second.clone()).unwrap();
+
+    let migrate = |sender: Principal, dry_run: bool| -> Result<u64, OperationError> {
+        let (response,): (Result<u64, OperationError>,) = env.update(
+            sender,
+            "migrate_detail_key",
+            (String::from("fee"), String::from("transfer_fee"), dry_run),
+        );
+        response
+    };
+    let keys = |principal_id: Principal| -> Vec<String> {
+        let entry = get(&env, principal_id).unwrap();
+        entry.details.into_iter().map(|(key, _)| key).collect()
+    };
+    assert!(migrate(user(), true).is_err());
+    assert_eq!(migrate(admin(), true), Ok(2));
+    assert!(keys(first.principal_id).contains(&String::from("fee")));
+
+    assert_eq!(migrate(admin(), false), Ok(2));
+    for principal_id in [first.principal_id, second.principal_id] {
+        assert!(keys(principal_id).contains(&String::from("transfer_fee")));
+        assert!(!keys(principal_id).contains(&String::from("fee")));
+    }
+    let (schema,): (Vec<DetailKeySpec>,) = env.query(user(), "get_required_details", ());
+    assert!(schema.iter().any(|spec| spec.key == "transfer_fee"));
+    assert_eq!(migrate(admin(), false), Ok(0));
+}
+
+#[test]
+fn test_large_slices_go_to_the_asset_store() {
+    let env = setup();
+    let (mut schema,): (Vec<DetailKeySpec>,) = env.query(user(), "get_required_details", ());
+    schema.push(DetailKeySpec {
+        key: String::from("logo"),
+        value_type: DetailType::Slice,
+        required: false,
+    });
+    let (response,): (OperationResponse,) = env.update(admin(), "set_required_details", (schema,));
+    response.unwrap();
+
+    let with_logo = |logo: Vec<u8>| -> AddInput {
+        let mut input = token(&env);
+        input
+            .details
+            .push((String::from("logo"), DetailValue::Slice(logo)));
+        input
+    };
+    let logo = vec![7; 2_000];
+    add(&env, admin(), with_logo(logo.clone())).unwrap();
+    assert!(add(&env, admin(), with_logo(vec![7; 5_000])).is_err());
+
+    let limits = SliceLimits {
+        max_size: 4_096,
+        asset_threshold: Some(1_024),
+    };
+    let (response,): (OperationResponse,) = env.update(admin(), "set_slice_limits", (limits,));
+    response.unwrap();
+    assert!(add(&env, admin(), with_logo(logo.clone())).is_err());
+
+    let (hash,): (Result<Vec<u8>, OperationError>,) =
+        env.update(user(), "upload_asset", (logo.clone(),));
+    let hash = hash.unwrap();
+    add(&env, admin(), with_logo(hash.clone())).unwrap();
+    let (asset,): (Option<Vec<u8>>,) = env.query(user(), "get_asset", (hash,));
+    assert_eq!(asset, Some(logo));
+}
+
+#[test]
+fn test_unreferenced_assets_are_collected() {
+    let env = setup();
+    let (mut schema,): (Vec<DetailKeySpec>,) = env.query(user(), "get_required_details", ());
+    schema.push(DetailKeySpec {
+        key: String::from("logo"),
+        value_type: DetailType::Slice,
+        required: false,
+    });
+    let (response,): (OperationResponse,) = env.update(admin(), "set_required_details", (schema,));
+    response.unwrap();
+
+    let upload = |content: Vec<u8>| -> Vec<u8> {
+        let (hash,): (Result<Vec<u8>, OperationError>,) =
+            env.update(user(), "upload_asset", (content,));
+        hash.unwrap()
+    };
+    let asset = |hash: &Vec<u8>| -> Option<Vec<u8>> {
+        let (asset,): (Option<Vec<u8>>,) = env.query(user(), "get_asset", (hash.clone(),));
+        asset
+    };
+    let gc_assets = |sender: Principal| -> Result<u64, OperationError> {
+        let (response,): (Result<u64, OperationError>,) = env.update(sender, "gc_assets", ());
+        response
+    };
+
+    let logo = upload(vec![7; 2_000]);
+    let unused = upload(vec![8; 3_000]);
+    let mut input = token(&env);
+    input
+        .details
+        .push((String::from("logo"), DetailValue::Slice(logo.clone())));
+    add(&env, admin(), input.clone()).unwrap();
+
+    assert!(matches!(
+        gc_assets(user()),
+        Err(OperationError::NotAuthorized { .. })
+    ));
+    // Fresh uploads may still be waiting for their entry
+    assert_eq!(gc_assets(admin()), Ok(0));
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "remove", (None::<Principal>, input.principal_id));
+    response.unwrap();
+    assert_eq!(asset(&logo), None);
+    assert!(asset(&unused).is_some());
+
+    env.pic
+        .advance_time(std::time::Duration::from_secs(2 * 24 * 60 * 60));
+    assert_eq!(gc_assets(admin()), Ok(3_000));
+    assert_eq!(asset(&unused), None);
+}
+
+#[test]
+fn test_asset_upload_quota() {
+    let env = setup();
+    let upload = |sender: Principal, byte: u8| -> Result<Vec<u8>, OperationError> {
+        let (hash,): (Result<Vec<u8>, OperationError>,) =
+            env.update(sender, "upload_asset", (vec![byte; 1024 * 1024],));
+        hash
+    };
+
+    // 2 MiB of unreferenced assets per caller
+    for byte in 0..2 {
+        upload(user(), byte).unwrap();
+    }
+    assert_eq!(upload(user(), 2), Err(OperationError::BadParameters));
+    // Content already stored doesn't count twice
+    assert!(upload(user(), 0).is_ok());
+    assert!(upload(admin(), 2).is_ok());
+}
+
+#[test]
+fn test_errors_are_translated_to_the_caller_language() {
+    let env = setup();
+    let catalog = vec![(
+        String::from("duplicated key"),
+        String::from("clé en double"),
+    )];
+    let (response,): (OperationResponse,) = env.update(
+        admin(),
+        "set_message_catalog",
+        (String::from("fr"), catalog),
+    );
+    response.unwrap();
+
+    let duplicated = || {
+        let mut input = token(&env);
+        input.details.push(input.details[0].clone());
+        add(&env, admin(), input)
+    };
+    let reason = |reason: &str| {
+        Err(OperationError::Validation(vec![FieldError {
+            field: String::from("details.symbol"),
+            reason: String::from(reason),
+        }]))
+    };
+    assert_eq!(duplicated(), reason("duplicated key"));
+
+    // Regional variants fall back to the catalog of their base language
+    let (response,): (OperationResponse,) = env.update(
+        admin(),
+        "set_error_language",
+        (Some(String::from("fr-CA")),),
+    );
+    response.unwrap();
+    assert_eq!(duplicated(), reason("clé en double"));
+
+    let (response,): (OperationResponse,) =
+        env.update(admin(), "set_error_language", (None::<String>,));
+    response.unwrap();
+    assert_eq!(duplicated(), reason("duplicated key"));
+}
+
+#[test]
+fn test_get_risk_flags() {
+    let env = setup();
+    let plain = token(&env);
+    let mut taxed = token(&env);
+    taxed
+        .details
+        .push((String::from("fee_on_transfer"), DetailValue::True));
+    taxed
+        .details
+        .push((String::from("pausable"), DetailValue::False));
+    add(&env, admin(), plain.clone()).unwrap();
+    add(&env, admin(), taxed.clone()).unwrap();
+
+    let risk_flags = |principal_id: Principal| -> Option<RiskFlags> {
+        let (flags,): (Option<RiskFlags>,) = env.query(user(), "get_risk_flags", (principal_id,));
+        flags
+    };
+    assert_eq!(risk_flags(plain.principal_id), Some(RiskFlags::default()));
+    assert_eq!(
+        risk_flags(taxed.principal_id),
+        Some(RiskFlags {
+            fee_on_transfer: true,
+            ..RiskFlags::default()
+        })
+    );
+    assert_eq!(risk_flags(env.pic.create_canister()), None);
+
+    let mut invalid = token(&env);
+    invalid
+        .details
+        .push((String::from("rebasing"), DetailValue::U64(1)));
+    assert!(matches!(
+        add(&env, admin(), invalid),
+        Err(OperationError::Validation(_))
+    ));
+}
+
+#[test]
+fn test_get_links() {
+    let env = setup();
+    let text = |url: &str| DetailValue::Text(String::from(url));
+
+    let mut input = token(&env);
+    input.frontend = Some(String::from("https://app.sonic.ooo"));
+    input.details.extend([
+        (String::from("website"), text("https://sonic.ooo")),
+        (
+            String::from("twitter"),
+            text("https://github.com/sonic-ooo"),
+        ),
+        (String::from("docs"), text("not a url")),
+    ]);
+    assert_eq!(
+        add(&env, admin(), input.clone()),
+        Err(OperationError::Validation(vec![
+            FieldError {
+                field: String::from("details.docs"),
+                reason: String::from("invalid URL"),
+            },
+            FieldError {
+                field: String::from("details.twitter"),
+                reason: String::from("expected a twitter.com or x.com URL"),
+            },
+        ]))
+    );
+
+    input.details.truncate(input.details.len() - 2);
+    input.details.extend([
+        (String::from("twitter"), text("https://www.x.com/sonic_ooo")),
+        (
+            String::from("frontends"),
+            DetailValue::Vec(vec![text("https://beta.sonic.ooo")]),
+        ),
+    ]);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let (links,): (Option<ProjectLinks>,) = env.query(user(), "get_links", (input.principal_id,));
+    assert_eq!(
+        links,
+        Some(ProjectLinks {
+            website: Some(String::from("https://sonic.ooo")),
+            twitter: Some(String::from("https://www.x.com/sonic_ooo")),
+            frontends: vec![
+                String::from("https://app.sonic.ooo"),
+                String::from("https://beta.sonic.ooo"),
+            ],
+            ..ProjectLinks::default()
+        })
+    );
+
+    let (links,): (Option<ProjectLinks>,) =
+        env.query(user(), "get_links", (env.pic.create_canister(),));
+    assert_eq!(links, None);
+}
+
+#[test]
+fn test_entry_limits() {
+    let env = setup();
+    let mut input = token(&env);
+    add(&env, admin(), input.clone()).unwrap();
+
+    let limits = EntryLimits {
+        max_size: 1,
+        max_details: input.details.len() as u64,
+    };
+    let (response,): (OperationResponse,) = env.update(admin(), "set_entry_limits", (limits,));
+    response.unwrap();
+    let (stored,): (EntryLimits,) = env.query(user(), "get_entry_limits", ());
+    assert_eq!(stored, limits);
+
+    // Growing past the limits is rejected
+    let mut grown = input.clone();
+    grown.details.push((
+        String::from("website"),
+        DetailValue::Text(String::from("https://xtc.io")),
+    ));
+    assert_eq!(
+        add(&env, admin(), grown),
+        Err(OperationError::Validation(vec![FieldError {
+            field: String::from("details"),
+            reason: String::from("too many"),
+        }]))
+    );
+    input
+        .description
+        .push_str(" Now with a longer description.");
+    assert!(matches!(
+        add(&env, admin(), input.clone()),
+        Err(OperationError::EntryTooLarge { limit: 1, .. })
+    ));
+
+    // Entries already over the limits can still shrink
+    input.description = String::from("Short.");
+    assert_eq!(add(&env, admin(), input), Ok(()));
+}
+
+#[test]
+fn test_query_entries() {
+    let env = setup();
+    let free = token(&env);
+    let mut paid = token(&env);
+    paid.details[5].1 = DetailValue::U64(10_000);
+    add(&env, admin(), free.clone()).unwrap();
+    add(&env, admin(), paid.clone()).unwrap();
+
+    let query = |filters: Vec<Filter>| -> Result<Vec<Principal>, OperationError> {
+        let (result,): (Result<Vec<Entry>, OperationError>,) =
+            env.query(user(), "query_entries", (filters,));
+        result.map(|entries| {
+            entries
+                .into_iter()
+                .map(|entry| entry.principal_id)
+                .collect()
+        })
+    };
+    let filter = |key: &str, op: FilterOp, value: DetailValue| Filter {
+        key: String::from(key),
+        op,
+        value,
+    };
+
+    let dip20 = filter(
+        "standard",
+        FilterOp::Equals,
+        DetailValue::Text(String::from("DIP20")),
+    );
+    assert_eq!(
+        query(vec![
+            dip20.clone(),
+            filter("fee", FilterOp::Equals, DetailValue::U64(0))
+        ]),
+        Ok(vec![free.principal_id])
+    );
+    assert_eq!(
+        query(vec![
+            dip20,
+            filter("fee", FilterOp::GreaterThan, DetailValue::U64(0))
+        ]),
+        Ok(vec![paid.principal_id])
+    );
+    assert_eq!(
+        query(vec![filter(
+            "symbol",
+            FilterOp::Contains,
+            DetailValue::Text(String::from("icp"))
+        )])
+        .map(|principals| principals.len()),
+        Ok(2)
+    );
+    assert_eq!(
+        query(vec![filter(
+            "fee",
+            FilterOp::Equals,
+            DetailValue::Float(f64::NAN)
+        )]),
+        Err(OperationError::BadParameters)
+    );
+}
+
+#[test]
+fn test_verify_integrity() {
+    let env = setup();
+    add(&env, admin(), token(&env)).unwrap();
+    add(&env, admin(), token(&env)).unwrap();
+
+    let (report,): (Result<IntegrityReport, OperationError>,) =
+        env.update(user(), "verify_integrity", ());
+    assert!(matches!(report, Err(OperationError::NotAuthorized { .. })));
+
+    let (report,): (Result<IntegrityReport, OperationError>,) =
+        env.update(admin(), "verify_integrity", ());
+    let report = report.unwrap();
+    assert_eq!(report.entries, 2);
+    assert_eq!(report.events, 2);
+    assert_eq!(report.repaired, Vec::<String>::new());
+    assert_eq!(report.issues, vec![]);
+}
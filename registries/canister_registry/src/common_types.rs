@@ -9,6 +9,16 @@ pub enum OperationError {
     Unknown(String),
 }
 
+// What kind of principal an entry labels
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EntryType {
+    Canister,
+    // A user or wallet principal
+    Account,
+    // An off-chain service acting on the IC through its own principal
+    Service,
+}
+
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
 pub struct AddCanisterInput {
     pub name: String,
@@ -17,6 +27,8 @@ pub struct AddCanisterInput {
     pub frontend: Option<String>,
     pub principal_id: Principal,
     pub details: Vec<(String, DetailValue)>,
+    // Defaults to `Canister`
+    pub entry_type: Option<EntryType>,
 }
 
 #[derive(Deserialize, CandidType, Clone, PartialEq, Debug)]
@@ -30,6 +42,8 @@ pub struct CanisterMetadata {
     pub last_updated_by: Principal,
     pub last_updated_at: u64,
     pub details: Vec<(String, DetailValue)>,
+    // `None` for entries registered before entry types existed, which are all canisters
+    pub entry_type: Option<EntryType>,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, PartialEq, Debug)]
@@ -48,3 +62,17 @@ pub enum DetailValue {
 
 pub const DESCRIPTION_LIMIT: usize = 1200;
 pub const NAME_LIMIT: usize = 24;
+
+// A method of an infrastructure canister, read from the `endpoints` detail of entries in
+// the `service` category so that tooling can build call UIs without fetching the candid
+#[derive(CandidType, Deserialize, Clone, PartialEq, Debug)]
+pub struct Endpoint {
+    pub method: String,
+    // Candid argument and result types as text, e.g. "(principal)" and "(opt nat)"
+    pub args: String,
+    pub returns: String,
+    pub query: bool,
+}
+
+pub const SERVICE_CATEGORY: &str = "service";
+pub const ENDPOINTS_LIMIT: usize = 100;
@@ -1,7 +1,7 @@
 use ic_cdk::export::candid::Principal;
 use ic_kit::macros::*;
 use ic_kit::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use validator::validate_url;
 
 use crate::common_types::*;
@@ -47,6 +47,7 @@ impl CanisterDB {
                 last_updated_by: *caller,
                 last_updated_at: ic::time(),
                 details: metadata.details.clone(),
+                entry_type: Some(metadata.entry_type.unwrap_or(EntryType::Canister)),
             };
 
             self.0.insert(metadata.principal_id, updated_canister);
@@ -63,6 +64,7 @@ impl CanisterDB {
                 last_updated_by: *caller,
                 last_updated_at: ic::time(),
                 details: metadata.details.clone(),
+                entry_type: Some(metadata.entry_type.unwrap_or(EntryType::Canister)),
             };
 
             self.0.insert(metadata.principal_id, new_canister);
@@ -96,6 +98,123 @@ impl CanisterDB {
     }
 }
 
+// Principal classes, given by the last byte of the principal
+const OPAQUE_ID: u8 = 0x01;
+const SELF_AUTHENTICATING_ID: u8 = 0x02;
+
+pub(crate) fn validate_entry_type(metadata: &AddCanisterInput) -> Result<(), OperationError> {
+    let class = metadata.principal_id.as_slice().last().copied();
+
+    let valid = match metadata.entry_type.unwrap_or(EntryType::Canister) {
+        EntryType::Canister => class == Some(OPAQUE_ID),
+        // Users and wallets sign their calls, so they have self-authenticating ids
+        EntryType::Account => class == Some(SELF_AUTHENTICATING_ID),
+        // Services are labelled with the site they run
+        EntryType::Service => {
+            metadata.principal_id != Principal::anonymous() && metadata.frontend.is_some()
+        }
+    };
+
+    match valid {
+        true => Ok(()),
+        false => Err(OperationError::BadParameters),
+    }
+}
+
+fn detail<'a>(details: &'a [(String, DetailValue)], key: &str) -> Option<&'a DetailValue> {
+    details
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value)
+}
+
+// Every entry has a `category`, entries in the `service` category also describe their
+// endpoints
+pub(crate) fn validate_details(details: &[(String, DetailValue)]) -> Result<(), OperationError> {
+    let is_service = match detail(details, "category") {
+        Some(DetailValue::Text(category)) => category == SERVICE_CATEGORY,
+        _ => return Err(OperationError::BadParameters),
+    };
+
+    let valid = match details {
+        [_] => !is_service,
+        [_, _] => is_service && endpoints(detail(details, "endpoints")).is_some(),
+        _ => false,
+    };
+
+    match valid {
+        true => Ok(()),
+        false => Err(OperationError::BadParameters),
+    }
+}
+
+// A balanced, parenthesized candid tuple such as "(principal, opt record { nat; text })"
+fn is_candid_tuple(text: &str) -> bool {
+    let text = text.trim();
+    if !text.starts_with('(') || !text.ends_with(')') {
+        return false;
+    }
+
+    let mut depth: usize = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '{' => depth += 1,
+            ')' | '}' => match depth.checked_sub(1) {
+                // The opening parenthesis only closes at the very end
+                Some(0) if i != text.len() - 1 => return false,
+                Some(d) => depth = d,
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+// Reads an `endpoints` detail, a non empty list of
+// vec { text method; text args; text returns; text "query" | "update" }
+pub(crate) fn endpoints(value: Option<&DetailValue>) -> Option<Vec<Endpoint>> {
+    use DetailValue::Text;
+
+    let values = match value? {
+        DetailValue::Vec(values) if !values.is_empty() && values.len() <= ENDPOINTS_LIMIT => values,
+        _ => return None,
+    };
+
+    let mut methods = HashSet::new();
+    let mut endpoints = Vec::with_capacity(values.len());
+    for value in values {
+        let fields = match value {
+            DetailValue::Vec(fields) => fields.as_slice(),
+            _ => return None,
+        };
+        let endpoint = match fields {
+            [Text(method), Text(args), Text(returns), Text(mode)] => Endpoint {
+                method: method.clone(),
+                args: args.clone(),
+                returns: returns.clone(),
+                query: match mode.as_str() {
+                    "query" => true,
+                    "update" => false,
+                    _ => return None,
+                },
+            },
+            _ => return None,
+        };
+
+        if endpoint.method.is_empty()
+            || !methods.insert(endpoint.method.clone())
+            || !is_candid_tuple(&endpoint.args)
+            || !is_candid_tuple(&endpoint.returns)
+        {
+            return None;
+        }
+        endpoints.push(endpoint);
+    }
+
+    Some(endpoints)
+}
+
 #[init]
 pub fn init() {
     ic::store(Admins(vec![ic::caller()]));
@@ -125,11 +244,14 @@ pub fn add(
         || &metadata.description.len() > &DESCRIPTION_LIMIT
         || !validate_url(&metadata.thumbnail)
         || !metadata.clone().frontend.map(validate_url).unwrap_or(true)
-        || (metadata.details.len() != 1 && metadata.details[0].0 != String::from("category"))
     {
         return Err(OperationError::BadParameters);
     }
 
+    validate_details(&metadata.details)?;
+
+    validate_entry_type(&metadata)?;
+
     let canister_db = ic::get_mut::<CanisterDB>();
     canister_db.add_canister(&trusted_source.unwrap_or(caller), metadata)
 }
@@ -152,3 +274,12 @@ pub fn get_all() -> Vec<&'static CanisterMetadata> {
     let canister_db = ic::get_mut::<CanisterDB>();
     canister_db.get_all()
 }
+
+// The endpoints of a registered infrastructure canister, empty for any other entry
+#[query]
+pub fn get_endpoints(canister: Principal) -> Vec<Endpoint> {
+    ic::get_mut::<CanisterDB>()
+        .get_info(canister)
+        .and_then(|metadata| endpoints(detail(&metadata.details, "endpoints")))
+        .unwrap_or_default()
+}
@@ -494,3 +494,222 @@
 //         assert_eq!(remove_operation.err().unwrap(), Failure::NotAuthorized);
 //     }
 // }
+
+#[cfg(test)]
+mod entry_type_tests {
+    use ic_kit::Principal;
+
+    use crate::common_types::*;
+    use crate::registry::validate_entry_type;
+
+    // A principal of the given class, the class being its last byte
+    fn principal(class: u8) -> Principal {
+        let mut bytes = vec![7; 28];
+        bytes.push(class);
+        Principal::from_slice(&bytes)
+    }
+
+    fn input(principal_id: Principal, entry_type: Option<EntryType>) -> AddCanisterInput {
+        AddCanisterInput {
+            name: String::from("name"),
+            description: String::from("description"),
+            thumbnail: String::from("https://logo_url.com"),
+            frontend: None,
+            principal_id,
+            details: vec![],
+            entry_type,
+        }
+    }
+
+    #[test]
+    fn test_canister_needs_an_opaque_id() {
+        let canister = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        assert_eq!(
+            validate_entry_type(&input(canister, Some(EntryType::Canister))),
+            Ok(())
+        );
+        assert_eq!(validate_entry_type(&input(principal(0x01), None)), Ok(()));
+
+        assert_eq!(
+            validate_entry_type(&input(principal(0x02), Some(EntryType::Canister))),
+            Err(OperationError::BadParameters)
+        );
+        assert_eq!(
+            validate_entry_type(&input(Principal::anonymous(), None)),
+            Err(OperationError::BadParameters)
+        );
+    }
+
+    #[test]
+    fn test_account_needs_a_self_authenticating_id() {
+        assert_eq!(
+            validate_entry_type(&input(principal(0x02), Some(EntryType::Account))),
+            Ok(())
+        );
+
+        assert_eq!(
+            validate_entry_type(&input(principal(0x01), Some(EntryType::Account))),
+            Err(OperationError::BadParameters)
+        );
+        assert_eq!(
+            validate_entry_type(&input(Principal::anonymous(), Some(EntryType::Account))),
+            Err(OperationError::BadParameters)
+        );
+    }
+
+    #[test]
+    fn test_service_needs_a_frontend() {
+        let mut service = input(principal(0x02), Some(EntryType::Service));
+        assert_eq!(
+            validate_entry_type(&service),
+            Err(OperationError::BadParameters)
+        );
+
+        service.frontend = Some(String::from("https://service.com"));
+        assert_eq!(validate_entry_type(&service), Ok(()));
+
+        service.principal_id = Principal::anonymous();
+        assert_eq!(
+            validate_entry_type(&service),
+            Err(OperationError::BadParameters)
+        );
+    }
+}
+
+#[cfg(test)]
+mod endpoint_tests {
+    use ic_kit::*;
+
+    use crate::common_types::*;
+    use crate::registry::{add, endpoints, get_endpoints, init, validate_details};
+
+    fn text(value: &str) -> DetailValue {
+        DetailValue::Text(String::from(value))
+    }
+
+    fn endpoint(method: &str, args: &str, returns: &str, mode: &str) -> DetailValue {
+        DetailValue::Vec(vec![text(method), text(args), text(returns), text(mode)])
+    }
+
+    fn service_details(endpoints: Vec<DetailValue>) -> Vec<(String, DetailValue)> {
+        vec![
+            (String::from("category"), text(SERVICE_CATEGORY)),
+            (String::from("endpoints"), DetailValue::Vec(endpoints)),
+        ]
+    }
+
+    #[test]
+    fn test_valid_endpoints() {
+        let value = DetailValue::Vec(vec![
+            endpoint("balance", "(principal)", "(nat)", "query"),
+            endpoint(
+                "transfer",
+                "(record { to: principal; amount: nat })",
+                "(variant { Ok: nat; Err: text })",
+                "update",
+            ),
+        ]);
+
+        let parsed = endpoints(Some(&value)).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].method, "balance");
+        assert!(parsed[0].query);
+        assert!(!parsed[1].query);
+    }
+
+    #[test]
+    fn test_malformed_method_names() {
+        let empty = DetailValue::Vec(vec![endpoint("", "()", "()", "query")]);
+        assert_eq!(endpoints(Some(&empty)), None);
+
+        let duplicated = DetailValue::Vec(vec![
+            endpoint("balance", "()", "(nat)", "query"),
+            endpoint("balance", "(principal)", "(nat)", "query"),
+        ]);
+        assert_eq!(endpoints(Some(&duplicated)), None);
+
+        let bad_mode = DetailValue::Vec(vec![endpoint("balance", "()", "(nat)", "oneway")]);
+        assert_eq!(endpoints(Some(&bad_mode)), None);
+    }
+
+    #[test]
+    fn test_malformed_candid_text() {
+        for args in &[
+            "",
+            "nat",
+            "(nat",
+            "nat)",
+            "(nat))",
+            "(nat)(text)",
+            "(record { nat)",
+        ] {
+            let value = DetailValue::Vec(vec![endpoint("balance", args, "(nat)", "query")]);
+            assert_eq!(endpoints(Some(&value)), None, "args {:?}", args);
+
+            let value = DetailValue::Vec(vec![endpoint("balance", "()", args, "query")]);
+            assert_eq!(endpoints(Some(&value)), None, "returns {:?}", args);
+        }
+
+        let short = DetailValue::Vec(vec![DetailValue::Vec(vec![text("balance"), text("()")])]);
+        assert_eq!(endpoints(Some(&short)), None);
+        assert_eq!(endpoints(Some(&DetailValue::Vec(vec![]))), None);
+        assert_eq!(endpoints(Some(&text("balance"))), None);
+    }
+
+    #[test]
+    fn test_missing_schema() {
+        assert_eq!(endpoints(None), None);
+
+        // A service has to describe its endpoints
+        let service = vec![(String::from("category"), text(SERVICE_CATEGORY))];
+        assert_eq!(
+            validate_details(&service),
+            Err(OperationError::BadParameters)
+        );
+
+        // While other entries can't
+        let mut canister = service_details(vec![endpoint("balance", "()", "(nat)", "query")]);
+        canister[0].1 = text("defi");
+        assert_eq!(
+            validate_details(&canister),
+            Err(OperationError::BadParameters)
+        );
+        canister.pop();
+        assert_eq!(validate_details(&canister), Ok(()));
+
+        assert_eq!(validate_details(&[]), Err(OperationError::BadParameters));
+    }
+
+    #[test]
+    fn test_get_endpoints() {
+        MockContext::new()
+            .with_caller(mock_principals::alice())
+            .inject();
+
+        init();
+
+        let service = AddCanisterInput {
+            name: String::from("XTC"),
+            description: String::from("Cycles token"),
+            thumbnail: String::from("https://logo_url.com"),
+            frontend: Some(String::from("https://frontend_url.com")),
+            principal_id: mock_principals::xtc(),
+            details: service_details(vec![endpoint("balance", "(principal)", "(nat)", "query")]),
+            entry_type: Some(EntryType::Service),
+        };
+        assert_eq!(add(None, service.clone()), Ok(()));
+
+        let found = get_endpoints(mock_principals::xtc());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].method, "balance");
+
+        // Entries without endpoints, and unknown ones, have none
+        let missing = AddCanisterInput {
+            principal_id: mock_principals::bob(),
+            details: vec![(String::from("category"), text(SERVICE_CATEGORY))],
+            ..service
+        };
+        assert_eq!(add(None, missing), Err(OperationError::BadParameters));
+        assert!(get_endpoints(mock_principals::bob()).is_empty());
+    }
+}
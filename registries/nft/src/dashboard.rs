@@ -0,0 +1,63 @@
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::alerts::AlertSink;
+use registry_framework::curation::Curation;
+use registry_framework::logger::Logger;
+use registry_framework::maintenance::{CyclesThreshold, Maintenance};
+use registry_framework::management::*;
+use registry_framework::mirror::Mirror;
+use registry_framework::reports::Reports;
+use registry_framework::retries::Retries;
+use registry_framework::subscribers::Subscribers;
+
+use crate::cap::Cap;
+use crate::common_types::*;
+use crate::history::History;
+use crate::metadata::DetailSchema;
+use crate::nft::{registry_info, ConflictRouter, ResponseBudget};
+use crate::submissions::{DepositConfig, SubmissionTtl, Submissions};
+use crate::thumbnails::Thumbnails;
+use crate::validation::ExternalValidator;
+
+const RECENT_EVENTS: usize = 50;
+
+fn config() -> RegistryConfig {
+    RegistryConfig {
+        maintenance: ic::get::<Maintenance>().0,
+        mirror: ic::get::<Mirror>().status.clone(),
+        conflict_router: ic::get::<ConflictRouter>().0,
+        alert_sink: ic::get::<AlertSink>().sink.clone(),
+        log_sink: ic::get::<Logger>().sink.clone(),
+        deposit: ic::get::<DepositConfig>().0.clone(),
+        vote_threshold: ic::get::<Curation>().threshold(),
+        required_details: ic::get::<DetailSchema>().0.clone(),
+        detail_limits: *ic::get::<DetailLimits>(),
+        submission_ttl: ic::get::<SubmissionTtl>().0,
+        cycles_threshold: ic::get::<CyclesThreshold>().0,
+        external_validator: ic::get::<ExternalValidator>().0,
+        response_budget: ic::get::<ResponseBudget>().0,
+        thumbnail_policy: ic::get::<Thumbnails>().0.clone(),
+        report_threshold: ic::get::<Reports>().threshold(),
+    }
+}
+
+// Everything the admin control panel shows, in one call
+#[query]
+pub fn dashboard() -> Result<Dashboard, OperationError> {
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    let history = &ic::get::<History>().0;
+    let recent_events = history.iter().rev().take(RECENT_EVENTS).cloned().collect();
+
+    Ok(Dashboard {
+        info: registry_info(),
+        pending_submissions: ic::get::<Submissions>().get_all().len() as u64,
+        recent_events,
+        pending_notifications: ic::get::<Subscribers>().get_pending(),
+        pending_refunds: ic::get::<Retries>().find(|kind| matches!(kind, TaskKind::Refund { .. })),
+        cap_root: ic::get::<Cap>().root,
+        config: config(),
+    })
+}
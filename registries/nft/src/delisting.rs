@@ -0,0 +1,28 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::delisting::Delistings;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+
+use crate::common_types::*;
+use crate::nft::Registry;
+
+// Takes a scam or phishing entry down at once. Unlike `remove` it skips locks and the
+// second admin of protected entries. Subscribers get a `Delist` event right away, and the
+// router drops the entry from its cache like for any other change.
+#[update]
+pub fn emergency_delist(principal_id: Principal, reason: String) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    let caller = ic::caller();
+    if !is_admin(&caller) {
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
+    } else if reason.trim().is_empty() {
+        return Err(OperationError::BadParameters);
+    }
+
+    let removed = ic::get_mut::<Registry>().delist(&principal_id)?;
+    ic::get_mut::<Delistings>().record(principal_id, removed.name, reason, caller);
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use ic_kit::macros::*;
+use ic_kit::*;
+use std::cmp::Ordering;
+
+use crate::common_types::*;
+use crate::nft::Registry;
+
+fn compare(value: &DetailValue, other: &DetailValue) -> Option<Ordering> {
+    match (value, other) {
+        (DetailValue::U64(value), DetailValue::U64(other)) => Some(value.cmp(other)),
+        (DetailValue::I64(value), DetailValue::I64(other)) => Some(value.cmp(other)),
+        (DetailValue::Float(value), DetailValue::Float(other)) => value.partial_cmp(other),
+        _ => None,
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, details: &[(String, DetailValue)]) -> bool {
+        let value = match details.iter().find(|(key, _)| *key == self.key) {
+            Some((_, value)) => value,
+            None => return false,
+        };
+
+        match self.op {
+            FilterOp::Equals => *value == self.value,
+            FilterOp::Contains => match (value, &self.value) {
+                (DetailValue::Text(text), DetailValue::Text(needle)) => {
+                    text.to_lowercase().contains(&needle.to_lowercase())
+                }
+                (DetailValue::Vec(values), needle) => values.contains(needle),
+                _ => false,
+            },
+            FilterOp::GreaterThan => compare(value, &self.value) == Some(Ordering::Greater),
+            FilterOp::LessThan => compare(value, &self.value) == Some(Ordering::Less),
+        }
+    }
+}
+
+// Entries matching every filter, at most `FILTER_RESULTS_LIMIT` of them
+#[query]
+pub fn query_entries(filters: Vec<Filter>) -> Result<Vec<&'static NftCanister>, OperationError> {
+    if filters.len() > FILTERS_LIMIT || filters.iter().any(|filter| !filter.value.is_finite()) {
+        return Err(OperationError::BadParameters);
+    }
+
+    Ok(ic::get::<Registry>()
+        .get_all()
+        .into_iter()
+        .filter(|entry| filters.iter().all(|filter| filter.matches(&entry.details)))
+        .take(FILTER_RESULTS_LIMIT)
+        .collect())
+}
@@ -0,0 +1,23 @@
+use ic_cdk_macros::heartbeat;
+use ic_kit::*;
+use registry_framework::metrics::Metrics;
+use registry_framework::mirror::Mirror;
+use registry_framework::retries::Retries;
+use registry_framework::subscribers::Subscribers;
+
+use crate::common_types::*;
+use crate::memory::report;
+use crate::nft::Registry;
+use crate::retries::run;
+use crate::stats::Stats;
+use crate::submissions::Expired;
+
+#[heartbeat]
+fn heartbeat() {
+    ic::get_mut::<Subscribers>().retry::<NftCanister>();
+    ic::get_mut::<Retries>().tick(run);
+    ic::get_mut::<Mirror>().tick::<Registry>();
+    ic::get_mut::<Expired>().tick();
+    ic::get_mut::<Metrics>().tick(report);
+    ic::get_mut::<Stats>().tick();
+}
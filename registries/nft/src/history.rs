@@ -0,0 +1,28 @@
+use ic_cdk::api::call::notify;
+use ic_kit::*;
+use registry_framework::retries::Retries;
+use registry_framework::subscribers::Subscribers;
+
+use crate::cap::Cap;
+use crate::common_types::*;
+use crate::nft::ConflictRouter;
+
+// See `registry_framework::history`
+pub type History = registry_framework::history::History<NftCanister>;
+
+// Logs a change to the registry and tells its subscribers and the conflict router about it
+pub fn store_event(kind: EventKind, entry: &NftCanister) {
+    ic::get_mut::<Cap>().ensure_root();
+    let event = ic::get_mut::<History>().push(kind, entry);
+    ic::get_mut::<Subscribers>().publish(event);
+
+    // Lets the router drop its cached answers for the entry
+    if let Some(router) = ic::get::<ConflictRouter>().0 {
+        if let Err(code) = notify(router, "invalidate", (entry.principal_id,)) {
+            let kind = TaskKind::Invalidate {
+                principal_id: entry.principal_id,
+            };
+            ic::get_mut::<Retries>().schedule(kind, format!("{:?}", code));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use ic_kit::candid::{CandidType, Deserialize, Int, Nat, Principal};
+use ic_kit::*;
+
+use crate::common_types::*;
+
+// Collection metadata keys copied into the `collection_metadata` detail. The
+// others, like the logo, can be too large to fit in the details of an entry.
+const STORED_KEYS: [&str; 5] = [
+    "icrc7:name",
+    "icrc7:symbol",
+    "icrc7:description",
+    "icrc7:total_supply",
+    "icrc7:supply_cap",
+];
+
+// The generic value of ICRC-3, which ICRC-7 metadata is made of
+#[derive(CandidType, Deserialize)]
+enum Value {
+    Blob(#[serde(with = "serde_bytes")] Vec<u8>),
+    Text(String),
+    Nat(Nat),
+    Int(Int),
+    Array(Vec<Value>),
+    Map(Vec<(String, Value)>),
+}
+
+// ICRC-37 only adds approvals on top of ICRC-7, such collections are checked the same way
+pub fn is_icrc7(standard: &str) -> bool {
+    matches!(standard, "ICRC-7" | "ICRC-37")
+}
+
+fn detail_value(value: Value) -> Option<DetailValue> {
+    match value {
+        Value::Text(text) => Some(DetailValue::Text(text)),
+        Value::Nat(nat) => Some(match u64::try_from(&nat.0) {
+            Ok(nat) => DetailValue::U64(nat),
+            Err(_) => DetailValue::Text(nat.to_string()),
+        }),
+        Value::Int(int) => Some(match i64::try_from(&int.0) {
+            Ok(int) => DetailValue::I64(int),
+            Err(_) => DetailValue::Text(int.to_string()),
+        }),
+        _ => None,
+    }
+}
+
+// Reads the metadata of an ICRC-7 collection as a vector of `vec { key; value }`
+// pairs. A collection that can't answer doesn't implement the standard.
+pub async fn collection_metadata(collection: Principal) -> Result<DetailValue, OperationError> {
+    let metadata: Vec<(String, Value)> =
+        match ic::call(collection, "icrc7_collection_metadata", ()).await {
+            Ok((metadata,)) => metadata,
+            Err(_) => {
+                return Err(OperationError::Validation(vec![FieldError {
+                    field: String::from("details.standard"),
+                    reason: String::from("the collection doesn't implement ICRC-7"),
+                }]))
+            }
+        };
+
+    Ok(DetailValue::Vec(
+        metadata
+            .into_iter()
+            .filter(|(key, _)| STORED_KEYS.contains(&key.as_str()))
+            .filter_map(|(key, value)| {
+                let value = detail_value(value)?;
+                Some(DetailValue::Vec(vec![DetailValue::Text(key), value]))
+            })
+            .collect(),
+    ))
+}
@@ -0,0 +1,260 @@
+use ic_kit::candid::Principal;
+use ic_kit::*;
+use registry_framework::interner::{StrId, StringPool};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::common_types::*;
+
+// Sorted views over the registry, kept up to date on every write so list
+// queries don't have to sort the whole registry on each call.
+#[derive(Default, PartialEq)]
+pub struct SortIndex {
+    by_name: BTreeSet<(String, Principal)>,
+    by_added_at: BTreeSet<(u64, Principal)>,
+    by_last_modified: BTreeSet<(u64, Principal)>,
+    // (name key, added at, last modified) of every indexed entry
+    keys: HashMap<Principal, (String, u64, u64)>,
+}
+
+impl SortIndex {
+    pub fn archive(&self) -> Vec<(Principal, u64)> {
+        self.keys
+            .iter()
+            .map(|(principal_id, (_, added_at, _))| (*principal_id, *added_at))
+            .collect()
+    }
+
+    // Rebuilds the index from the loaded entries. Entries without a known
+    // insertion time fall back to their last update.
+    pub fn load(&mut self, added_at: Vec<(Principal, u64)>, entries: Vec<&NftCanister>) {
+        let added_at: HashMap<Principal, u64> = added_at.into_iter().collect();
+        *self = SortIndex::default();
+
+        for entry in entries {
+            let time = added_at
+                .get(&entry.principal_id)
+                .copied()
+                .unwrap_or(entry.last_updated_at);
+            self.insert_with_time(entry, time);
+        }
+    }
+
+    pub fn insert(&mut self, entry: &NftCanister) {
+        let added_at = match self.keys.get(&entry.principal_id) {
+            Some((_, added_at, _)) => *added_at,
+            None => entry.last_updated_at,
+        };
+
+        self.insert_with_time(entry, added_at);
+    }
+
+    fn insert_with_time(&mut self, entry: &NftCanister, added_at: u64) {
+        self.remove(&entry.principal_id);
+
+        let name = entry.name.to_lowercase();
+        self.by_name.insert((name.clone(), entry.principal_id));
+        self.by_added_at.insert((added_at, entry.principal_id));
+        self.by_last_modified
+            .insert((entry.last_updated_at, entry.principal_id));
+        self.keys
+            .insert(entry.principal_id, (name, added_at, entry.last_updated_at));
+    }
+
+    pub fn remove(&mut self, principal_id: &Principal) {
+        if let Some((name, added_at, last_modified)) = self.keys.remove(principal_id) {
+            self.by_name.remove(&(name, *principal_id));
+            self.by_added_at.remove(&(added_at, *principal_id));
+            self.by_last_modified
+                .remove(&(last_modified, *principal_id));
+        }
+    }
+
+    pub fn added_at(&self, principal_id: &Principal) -> Option<u64> {
+        self.keys
+            .get(principal_id)
+            .map(|(_, added_at, _)| *added_at)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn sorted(
+        &self,
+        sort_by: SortBy,
+        direction: SortDirection,
+    ) -> Box<dyn Iterator<Item = Principal> + '_> {
+        let principals: Box<dyn DoubleEndedIterator<Item = Principal> + '_> = match sort_by {
+            SortBy::Name => Box::new(self.by_name.iter().map(|(_, p)| *p)),
+            SortBy::AddedAt => Box::new(self.by_added_at.iter().map(|(_, p)| *p)),
+            SortBy::LastModified => Box::new(self.by_last_modified.iter().map(|(_, p)| *p)),
+        };
+
+        match direction {
+            SortDirection::Ascending => Box::new(principals),
+            SortDirection::Descending => Box::new(principals.rev()),
+        }
+    }
+}
+
+// Lowercased host of a URL, without port, credentials or a leading "www."
+pub fn url_host(url: &str) -> Option<String> {
+    let rest = url.split("://").nth(1).unwrap_or(url);
+    let authority = rest.split(|c| c == '/' || c == '?' || c == '#').next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    let host = host.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+
+    match host.is_empty() {
+        true => None,
+        false => Some(host.to_string()),
+    }
+}
+
+// Entries by the host of their frontend URL, so a visited domain can be matched to its project
+#[derive(Default)]
+pub struct FrontendIndex {
+    by_host: HashMap<StrId, BTreeSet<Principal>>,
+    hosts: HashMap<Principal, StrId>,
+    pool: StringPool,
+}
+
+// Indexes are equal when they map the same entries to the same hosts, whatever ids their
+// pools gave the hosts
+impl PartialEq for FrontendIndex {
+    fn eq(&self, other: &Self) -> bool {
+        self.hosts.len() == other.hosts.len()
+            && self
+                .hosts
+                .keys()
+                .all(|principal_id| self.host(principal_id) == other.host(principal_id))
+    }
+}
+
+impl FrontendIndex {
+    pub fn load(&mut self, entries: Vec<&NftCanister>) {
+        *self = FrontendIndex::default();
+        for entry in entries {
+            self.insert(entry);
+        }
+    }
+
+    pub fn insert(&mut self, entry: &NftCanister) {
+        self.remove(&entry.principal_id);
+
+        if let Some(host) = entry.frontend.as_deref().and_then(url_host) {
+            let host = self.pool.intern(&host);
+            self.by_host
+                .entry(host)
+                .or_default()
+                .insert(entry.principal_id);
+            self.hosts.insert(entry.principal_id, host);
+        }
+    }
+
+    pub fn remove(&mut self, principal_id: &Principal) {
+        if let Some(host) = self.hosts.remove(principal_id) {
+            if let Some(entries) = self.by_host.get_mut(&host) {
+                entries.remove(principal_id);
+                if entries.is_empty() {
+                    self.by_host.remove(&host);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, host: &str) -> Vec<Principal> {
+        self.pool
+            .id(host)
+            .and_then(|host| self.by_host.get(&host))
+            .map(|entries| entries.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    fn host(&self, principal_id: &Principal) -> Option<&str> {
+        self.hosts
+            .get(principal_id)
+            .map(|host| self.pool.get(*host))
+    }
+
+    pub fn interned(&self) -> usize {
+        self.pool.len()
+    }
+}
+
+// Entries by the principal managing them, its `managed_by` or else its submitter
+#[derive(Default, PartialEq)]
+pub struct ManagerIndex {
+    by_manager: HashMap<Principal, BTreeSet<Principal>>,
+    managers: HashMap<Principal, Principal>,
+}
+
+impl ManagerIndex {
+    pub fn load(&mut self, entries: Vec<&NftCanister>) {
+        *self = ManagerIndex::default();
+        for entry in entries {
+            self.insert(entry);
+        }
+    }
+
+    pub fn insert(&mut self, entry: &NftCanister) {
+        self.remove(&entry.principal_id);
+
+        let manager = entry.manager();
+        self.by_manager
+            .entry(manager)
+            .or_default()
+            .insert(entry.principal_id);
+        self.managers.insert(entry.principal_id, manager);
+    }
+
+    pub fn remove(&mut self, principal_id: &Principal) {
+        if let Some(manager) = self.managers.remove(principal_id) {
+            if let Some(entries) = self.by_manager.get_mut(&manager) {
+                entries.remove(principal_id);
+                if entries.is_empty() {
+                    self.by_manager.remove(&manager);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, manager: &Principal) -> Vec<Principal> {
+        self.by_manager
+            .get(manager)
+            .map(|entries| entries.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+// Entries by the textual encoding of their principal, for type-ahead search by prefix
+#[derive(Default, PartialEq)]
+pub struct PrincipalIndex(BTreeMap<String, Principal>);
+
+impl PrincipalIndex {
+    pub fn load(&mut self, entries: Vec<&NftCanister>) {
+        *self = PrincipalIndex::default();
+        for entry in entries {
+            self.insert(entry);
+        }
+    }
+
+    pub fn insert(&mut self, entry: &NftCanister) {
+        self.0
+            .insert(entry.principal_id.to_text(), entry.principal_id);
+    }
+
+    pub fn remove(&mut self, principal_id: &Principal) {
+        self.0.remove(&principal_id.to_text());
+    }
+
+    // The first `limit` principals, in textual order, whose encoding starts with `prefix`
+    pub fn search(&self, prefix: &str, limit: usize) -> Vec<Principal> {
+        self.0
+            .range(prefix.to_string()..)
+            .take_while(|(text, _)| text.starts_with(prefix))
+            .take(limit)
+            .map(|(_, principal_id)| *principal_id)
+            .collect()
+    }
+}
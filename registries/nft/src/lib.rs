@@ -1,5 +1,62 @@
+mod cap;
 mod common_types;
-mod management;
+mod consistency;
+mod dashboard;
+mod delisting;
+mod factory;
+mod filters;
+mod heartbeat;
+mod history;
+mod icrc7;
+mod index;
+mod manifest;
+mod memory;
+mod metadata;
+mod migrations;
+mod namespaces;
 mod nft;
+mod projection;
+mod protection;
+mod retries;
+mod reviewers;
+mod seed;
+mod stats;
+mod submissions;
+mod taxonomy;
 mod tests;
+mod thumbnails;
 mod upgrade;
+mod validation;
+
+// Methods of the stores shared with the other registries, see `registry_framework`
+registry_framework::alerts_methods!();
+registry_framework::aliases_methods!(crate::nft::Registry);
+registry_framework::assets_methods!(crate::submissions::Submissions);
+registry_framework::callbacks_methods!(crate::nft::Registry, crate::submissions::Submissions);
+registry_framework::curation_methods!(
+    crate::submissions::Submissions,
+    crate::submissions::promote,
+    crate::submissions::dismiss
+);
+registry_framework::delisting_methods!();
+registry_framework::integrity_methods!();
+registry_framework::locks_methods!(crate::nft::Registry);
+registry_framework::logger_methods!();
+registry_framework::maintenance_methods!();
+registry_framework::management_methods!();
+registry_framework::messages_methods!();
+registry_framework::metrics_methods!(crate::memory::report);
+registry_framework::mirror_methods!(crate::nft::Registry);
+registry_framework::pinning_methods!(crate::nft::Registry);
+registry_framework::protection_methods!(
+    crate::nft::Registry,
+    crate::common_types::AddNftInput,
+    crate::protection::apply
+);
+registry_framework::reports_methods!(crate::nft::Registry);
+registry_framework::reserved_methods!();
+registry_framework::retries_methods!(crate::retries::run);
+registry_framework::sponsors_methods!(crate::nft::Registry);
+registry_framework::subscribers_methods!();
+registry_framework::translations_methods!(crate::nft::Registry);
+registry_framework::usage_methods!();
@@ -0,0 +1,396 @@
+use ic_kit::candid::{encode_one, Principal};
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+use std::collections::{BTreeMap, HashSet};
+use validator::validate_url;
+
+use crate::common_types::*;
+use crate::icrc7::is_icrc7;
+use crate::index::url_host;
+use crate::nft::Registry;
+use crate::validation::Validator;
+
+// Detail keys accepted by the registry, editable by admins without an upgrade
+pub struct DetailSchema(pub Vec<DetailKeySpec>);
+
+fn spec(key: &str, value_type: DetailType) -> DetailKeySpec {
+    DetailKeySpec {
+        key: String::from(key),
+        value_type,
+        required: true,
+    }
+}
+
+pub fn optional(key: &str, value_type: DetailType) -> DetailKeySpec {
+    DetailKeySpec {
+        required: false,
+        ..spec(key, value_type)
+    }
+}
+
+impl Default for DetailSchema {
+    fn default() -> Self {
+        DetailSchema(vec![
+            spec("standard", DetailType::Text),
+            // vec { vec { text key; value } }, filled from the collection, see `icrc7`
+            DetailKeySpec {
+                key: String::from("collection_metadata"),
+                value_type: DetailType::Vec,
+                required: false,
+            },
+            // vec { vec { text name; text url } }, see `marketplaces`
+            DetailKeySpec {
+                key: String::from("marketplaces"),
+                value_type: DetailType::Vec,
+                required: false,
+            },
+            // Root bucket of the collection's history on CAP, see `link_cap_root`
+            DetailKeySpec {
+                key: String::from("cap_root"),
+                value_type: DetailType::Principal,
+                required: false,
+            },
+            // Project profile links, see `validate_links`
+            optional("website", DetailType::Text),
+            optional("docs", DetailType::Text),
+            optional("twitter", DetailType::Text),
+            optional("discord", DetailType::Text),
+            optional("github", DetailType::Text),
+            // vec { text url }, frontends besides the `frontend` of the entry
+            optional("frontends", DetailType::Vec),
+        ])
+    }
+}
+
+// The details of a collection keyed by name, checked against the `DetailSchema`
+pub struct Metadata(BTreeMap<String, DetailValue>);
+
+impl Metadata {
+    pub fn parse(details: &[(String, DetailValue)]) -> Result<Self, OperationError> {
+        let mut validator = Validator::default();
+        let metadata = Metadata::check(details, &mut validator);
+        validator.finish()?;
+        Ok(metadata)
+    }
+
+    // Keeps the valid details, reporting every invalid or missing one to `validator`
+    pub fn check(details: &[(String, DetailValue)], validator: &mut Validator) -> Self {
+        let schema = &ic::get::<DetailSchema>().0;
+        let limits = ic::get::<DetailLimits>();
+        let slices = ic::get::<SliceLimits>();
+
+        let size = encode_one(details).map_or(u64::MAX, |bytes| bytes.len() as u64);
+        validator.check(size <= limits.max_size, "details", "too large");
+
+        let mut map = BTreeMap::new();
+        for (key, value) in details.iter() {
+            let field = format!("details.{}", key);
+            let spec = match schema.iter().find(|spec| spec.key == *key) {
+                Some(spec) => spec,
+                None => {
+                    validator.check(false, &field, "unknown key");
+                    continue;
+                }
+            };
+
+            if map.contains_key(key) {
+                validator.check(false, &field, "duplicated key");
+            } else if !spec.value_type.matches(value) {
+                validator.check(false, &field, &format!("expected {:?}", spec.value_type));
+            } else if !value.is_finite() {
+                validator.check(false, &field, "floats must be finite");
+            } else if value.depth() > limits.max_depth {
+                validator.check(false, &field, "nested too deeply");
+            } else if slices
+                .asset_threshold
+                .map_or(false, |threshold| value.largest_slice() > threshold)
+            {
+                validator.check(false, &field, "store large slices as assets");
+            } else if value.largest_slice() > slices.max_size {
+                validator.check(false, &field, "slice too large");
+            } else {
+                map.insert(key.clone(), value.clone());
+            }
+        }
+
+        for spec in schema.iter() {
+            if spec.required && !details.iter().any(|(key, _)| *key == spec.key) {
+                validator.check(false, &format!("details.{}", spec.key), "missing");
+            }
+        }
+
+        Metadata(map)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&DetailValue> {
+        self.0.get(key)
+    }
+
+    pub fn text(&self, key: &str) -> Option<&str> {
+        self.0.get(key)?.as_text()
+    }
+
+    pub fn standard(&self) -> &str {
+        self.text("standard").unwrap_or_default()
+    }
+
+    // The `frontend` of the entry comes first among the frontends
+    pub fn links(&self, frontend: Option<&String>) -> ProjectLinks {
+        let link = |key| self.text(key).map(String::from);
+        let frontends = self
+            .get("frontends")
+            .and_then(frontends)
+            .unwrap_or_default();
+        ProjectLinks {
+            website: link("website"),
+            docs: link("docs"),
+            twitter: link("twitter"),
+            discord: link("discord"),
+            github: link("github"),
+            frontends: frontend.into_iter().cloned().chain(frontends).collect(),
+        }
+    }
+}
+
+// Reads a `marketplaces` detail, a list of marketplace names each followed by the URL of the
+// collection on it, e.g. vec { vec { "Entrepot"; "https://entrepot.app/marketplace/btcflower" } }
+pub fn marketplaces(value: &DetailValue) -> Option<Vec<(String, String)>> {
+    value
+        .as_vec()?
+        .iter()
+        .map(|marketplace| match marketplace.as_vec()? {
+            [DetailValue::Text(name), DetailValue::Text(url)] if !name.is_empty() => {
+                Some((name.clone(), url.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// Hosts each link detail must point to, any host when empty
+const LINK_HOSTS: [(&str, &[&str]); 5] = [
+    ("website", &[]),
+    ("docs", &[]),
+    ("twitter", &["twitter.com", "x.com"]),
+    ("discord", &["discord.gg", "discord.com"]),
+    ("github", &["github.com"]),
+];
+
+// Reads a `frontends` detail, a list of URLs, e.g. vec { "https://entrepot.app" }
+pub fn frontends(value: &DetailValue) -> Option<Vec<String>> {
+    value
+        .as_vec()?
+        .iter()
+        .map(|url| url.as_text().map(String::from))
+        .collect()
+}
+
+// Links must be URLs, on the site of the service they're named after
+pub fn validate_links(metadata: &Metadata, validator: &mut Validator) {
+    for (key, hosts) in LINK_HOSTS {
+        let url = match metadata.text(key) {
+            Some(url) => url,
+            None => continue,
+        };
+        let field = format!("details.{}", key);
+        if !validate_url(url) {
+            validator.check(false, &field, "invalid URL");
+        } else if !hosts.is_empty() {
+            let valid = url_host(url).map_or(false, |host| hosts.contains(&host.as_str()));
+            validator.check(
+                valid,
+                &field,
+                &format!("expected a {} URL", hosts.join(" or ")),
+            );
+        }
+    }
+
+    if let Some(value) = metadata.get("frontends") {
+        match frontends(value) {
+            Some(urls) => {
+                validator.check(
+                    urls.len() <= FRONTENDS_LIMIT,
+                    "details.frontends",
+                    "too many",
+                );
+                for url in urls.iter() {
+                    validator.check(validate_url(url), "details.frontends", "invalid URL");
+                }
+            }
+            None => validator.check(false, "details.frontends", "expected a vec of URLs"),
+        }
+    }
+}
+
+#[update]
+pub fn set_required_details(keys: Vec<DetailKeySpec>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    let mut seen = HashSet::new();
+    if keys
+        .iter()
+        .any(|spec| spec.key.is_empty() || !seen.insert(spec.key.as_str()))
+    {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::store(DetailSchema(keys));
+    Ok(())
+}
+
+// Renames the `old` detail key across all entries, e.g. `fee` to `transfer_fee`, and returns
+// how many entries hold it. The key is renamed in the detail schema too, unless `new` is
+// already in it. `dry_run` only counts the entries.
+#[update]
+pub fn migrate_detail_key(old: String, new: String, dry_run: bool) -> Result<u64, OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if old.is_empty() || new.is_empty() || old == new {
+        return Err(OperationError::BadParameters);
+    }
+
+    let touched =
+        ic::get_mut::<Registry>().rename_detail_key(&ic::caller(), &old, &new, dry_run)?;
+    if dry_run {
+        return Ok(touched);
+    }
+
+    let schema = &mut ic::get_mut::<DetailSchema>().0;
+    if schema.iter().any(|spec| spec.key == new) {
+        schema.retain(|spec| spec.key != old);
+    } else if let Some(spec) = schema.iter_mut().find(|spec| spec.key == old) {
+        spec.key = new;
+    }
+    Ok(touched)
+}
+
+#[update]
+pub fn set_detail_limits(limits: DetailLimits) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if limits.max_depth == 0 || limits.max_size == 0 {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::store(limits);
+    Ok(())
+}
+
+#[query]
+pub fn get_detail_limits() -> DetailLimits {
+    *ic::get::<DetailLimits>()
+}
+
+#[update]
+pub fn set_entry_limits(limits: EntryLimits) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if limits.max_size == 0 || limits.max_details == 0 {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::store(limits);
+    Ok(())
+}
+
+#[query]
+pub fn get_entry_limits() -> EntryLimits {
+    *ic::get::<EntryLimits>()
+}
+
+// Rejects an entry over the `EntryLimits`, unless it's an edit shrinking the `current` one
+// so that entries listed before a limit was lowered can still be trimmed
+pub fn check_entry_limits(
+    current: Option<&NftCanister>,
+    updated: &NftCanister,
+) -> Result<(), OperationError> {
+    let limits = ic::get::<EntryLimits>();
+    let size = |nft: &NftCanister| encode_one(nft).map_or(u64::MAX, |bytes| bytes.len() as u64);
+
+    let count = updated.details.len();
+    let grows = current.map_or(true, |current| count > current.details.len());
+    let mut validator = Validator::default();
+    validator.check(
+        count as u64 <= limits.max_details || !grows,
+        "details",
+        "too many",
+    );
+    validator.finish()?;
+
+    let bytes = size(updated);
+    if bytes > limits.max_size && current.map_or(true, |current| bytes > size(current)) {
+        return Err(OperationError::EntryTooLarge {
+            bytes,
+            limit: limits.max_size,
+        });
+    }
+    Ok(())
+}
+
+#[update]
+pub fn set_slice_limits(limits: SliceLimits) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if limits.max_size == 0 || limits.asset_threshold == Some(0) {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::store(limits);
+    Ok(())
+}
+
+#[query]
+pub fn get_slice_limits() -> SliceLimits {
+    *ic::get::<SliceLimits>()
+}
+
+// Marketplaces the collection trades on, as (name, URL) pairs
+#[query]
+pub fn get_marketplaces(principal_id: Principal) -> Vec<(String, String)> {
+    ic::get::<Registry>()
+        .get(&principal_id)
+        .and_then(|entry| marketplaces(detail(&entry.details, "marketplaces")?))
+        .unwrap_or_default()
+}
+
+// Website, docs, socials and frontends of the project, so explorers can show its profile
+#[query]
+pub fn get_links(principal_id: Principal) -> Option<ProjectLinks> {
+    let collection = ic::get::<Registry>().get(&principal_id)?;
+    let metadata = Metadata::check(&collection.details, &mut Validator::default());
+    Some(metadata.links(collection.frontend.as_ref()))
+}
+
+#[query]
+pub fn get_required_details() -> Vec<DetailKeySpec> {
+    ic::get::<DetailSchema>().0.clone()
+}
+
+// Detail keys and value types `validate` checks a collection of `standard` against. The
+// registry reads `collection_metadata` from ICRC-7 collections itself, so it's left out.
+#[query]
+pub fn get_detail_schema(standard: String) -> Result<Vec<DetailKeySpec>, OperationError> {
+    if !SUPPORTED_STANDARDS.contains(&standard.as_str()) {
+        return Err(OperationError::BadParameters);
+    }
+
+    let mut schema = ic::get::<DetailSchema>().0.clone();
+    if is_icrc7(&standard) {
+        schema.retain(|spec| spec.key != "collection_metadata");
+    }
+    Ok(schema)
+}
@@ -0,0 +1,34 @@
+use ic_kit::ic::trap;
+
+use crate::upgrade::StableStorage;
+
+// Version of the layout of the stable data. Every change to the stored types
+// that needs existing data to be rewritten bumps it and appends a migration.
+pub const SCHEMA_VERSION: u32 = 1;
+
+type Migration = fn(&mut StableStorage);
+
+// `MIGRATIONS[n]` moves the stable data from version `n` to version `n + 1`
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [v0_to_v1];
+
+// Data saved before the schema was versioned only holds the entries and admins. Every
+// field added since then is optional, so it decodes as is and the stores it didn't save,
+// the detail schema among them, start from their defaults.
+fn v0_to_v1(_stable: &mut StableStorage) {}
+
+// Applies, in order, every migration the stored data hasn't gone through yet
+pub fn migrate(stable: &mut StableStorage) {
+    let version = stable.schema_version.unwrap_or(0);
+    if version > SCHEMA_VERSION {
+        trap(&format!(
+            "Stable data has schema version {} but this canister only supports up to {}",
+            version, SCHEMA_VERSION
+        ));
+    }
+
+    for migration in MIGRATIONS[version as usize..].iter() {
+        migration(stable);
+    }
+
+    stable.schema_version = Some(SCHEMA_VERSION);
+}
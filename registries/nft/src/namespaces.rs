@@ -0,0 +1,233 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::guard::check_principal;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+use std::collections::BTreeMap;
+
+use crate::common_types::*;
+use crate::validation::validate;
+
+// Separate listings hosted next to the main registry, e.g. the collections of a staging
+// network. Their entries never reach the main registry, its indexes, history or mirror.
+#[derive(Default)]
+pub struct Namespaces(BTreeMap<String, Namespace>);
+
+pub struct Namespace {
+    config: NamespaceConfig,
+    entries: BTreeMap<Principal, NftCanister>,
+}
+
+// Lowercase identifiers such as "staging" or "testnet"
+fn is_namespace_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= NAMESPACE_NAME_LIMIT
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+impl Namespace {
+    fn can_write(&self, caller: &Principal) -> bool {
+        is_admin(caller) || self.config.writers.contains(caller)
+    }
+}
+
+impl Namespaces {
+    pub fn archive(&mut self) -> Vec<NamespaceArchive> {
+        std::mem::take(&mut self.0)
+            .into_iter()
+            .map(|(name, namespace)| NamespaceArchive {
+                name,
+                config: namespace.config,
+                entries: namespace.entries.into_values().collect(),
+            })
+            .collect()
+    }
+
+    pub fn load(&mut self, archive: Vec<NamespaceArchive>) {
+        self.0 = archive
+            .into_iter()
+            .map(|namespace| {
+                let entries = namespace
+                    .entries
+                    .into_iter()
+                    .map(|entry| (entry.principal_id, entry))
+                    .collect();
+                let config = namespace.config;
+                (namespace.name, Namespace { config, entries })
+            })
+            .collect();
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Namespace> {
+        self.0.get(name)
+    }
+
+    fn get_writable(
+        &mut self,
+        name: &str,
+        caller: &Principal,
+    ) -> Result<&mut Namespace, OperationError> {
+        let namespace = self
+            .0
+            .get_mut(name)
+            .ok_or(OperationError::NonExistentItem)?;
+        match namespace.can_write(caller) {
+            true => Ok(namespace),
+            false => Err(OperationError::not_authorized(Role::Submitter, caller)),
+        }
+    }
+
+    pub fn add(
+        &mut self,
+        name: &str,
+        caller: &Principal,
+        input: AddNftInput,
+    ) -> Result<(), OperationError> {
+        let namespace = self.get_writable(name, caller)?;
+        if namespace.config.validate {
+            validate(&input)?;
+        }
+
+        let previous = namespace.entries.get(&input.principal_id);
+        let entry = NftCanister {
+            name: input.name,
+            description: input.description,
+            thumbnail: input.thumbnail,
+            frontend: input.frontend,
+            principal_id: input.principal_id,
+            submitter: previous.map_or(*caller, |entry| entry.submitter),
+            last_updated_by: *caller,
+            last_updated_at: ic::time(),
+            details: input.details,
+            resolved_from: None,
+            managed_by: None,
+        };
+
+        namespace.entries.insert(entry.principal_id, entry);
+        Ok(())
+    }
+
+    pub fn remove(
+        &mut self,
+        name: &str,
+        caller: &Principal,
+        principal_id: &Principal,
+    ) -> Result<(), OperationError> {
+        let namespace = self.get_writable(name, caller)?;
+        match namespace.entries.remove(principal_id) {
+            Some(_) => Ok(()),
+            None => Err(OperationError::NonExistentItem),
+        }
+    }
+}
+
+#[update]
+pub fn create_namespace(name: String, config: NamespaceConfig) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    config.writers.iter().try_for_each(check_principal)?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if !is_namespace_name(&name) || ic::get::<Namespaces>().get(&name).is_some() {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::get_mut::<Namespaces>().0.insert(
+        name,
+        Namespace {
+            config,
+            entries: BTreeMap::new(),
+        },
+    );
+    Ok(())
+}
+
+#[update]
+pub fn set_namespace_config(name: String, config: NamespaceConfig) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    config.writers.iter().try_for_each(check_principal)?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    match ic::get_mut::<Namespaces>().0.get_mut(&name) {
+        Some(namespace) => {
+            namespace.config = config;
+            Ok(())
+        }
+        None => Err(OperationError::NonExistentItem),
+    }
+}
+
+// Drops the namespace along with every entry in it
+#[update]
+pub fn delete_namespace(name: String) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    match ic::get_mut::<Namespaces>().0.remove(&name) {
+        Some(_) => Ok(()),
+        None => Err(OperationError::NonExistentItem),
+    }
+}
+
+#[query]
+pub fn get_namespaces() -> Vec<NamespaceInfo> {
+    ic::get::<Namespaces>()
+        .0
+        .iter()
+        .map(|(name, namespace)| NamespaceInfo {
+            name: name.clone(),
+            config: namespace.config.clone(),
+            entry_count: namespace.entries.len() as u64,
+        })
+        .collect()
+}
+
+// Admins and the writers of the namespace can add and update its entries
+#[update]
+pub fn add_in_namespace(namespace: String, entry: AddNftInput) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    ic::get_mut::<Namespaces>().add(&namespace, &ic::caller(), entry)
+}
+
+#[update]
+pub fn remove_in_namespace(
+    namespace: String,
+    principal_id: Principal,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    ic::get_mut::<Namespaces>().remove(&namespace, &ic::caller(), &principal_id)
+}
+
+#[query]
+pub fn get_in_namespace(
+    namespace: String,
+    principal_id: Principal,
+) -> Option<&'static NftCanister> {
+    ic::get::<Namespaces>()
+        .get(&namespace)?
+        .entries
+        .get(&principal_id)
+}
+
+// Entries of the namespace ordered by principal
+#[query]
+pub fn list_namespace(namespace: String, offset: u64, limit: u16) -> Vec<&'static NftCanister> {
+    match ic::get::<Namespaces>().get(&namespace) {
+        Some(namespace) => namespace
+            .entries
+            .values()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect(),
+        None => vec![],
+    }
+}
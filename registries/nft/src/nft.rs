@@ -1,31 +1,62 @@
-use ic_kit::candid::Principal;
+use ic_cdk::api::call::RejectionCode;
+use ic_kit::candid::{encode_one, Principal};
 use ic_kit::macros::*;
 use ic_kit::*;
-use std::{collections::HashMap, str::FromStr};
-use validator::validate_url;
+use registry_framework::alerts::*;
+use registry_framework::aliases::*;
+use registry_framework::assets::{release, AssetRefs};
+use registry_framework::callbacks::{notify_owner, EntryCallbacks};
+use registry_framework::entries::EntryStore;
+use registry_framework::guard::check_principal;
+use registry_framework::integrity::Integrity;
+use registry_framework::locks::*;
+use registry_framework::logger::log_result;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+use registry_framework::messages::localize;
+use registry_framework::mirror::*;
+use registry_framework::pinning::Pins;
+use registry_framework::reports::Reports;
+use registry_framework::requests::Requests;
+use registry_framework::reserved::*;
+use registry_framework::translations::*;
+use registry_framework::usage::Usage;
+use std::{collections::BTreeMap, str::FromStr};
 
+use crate::cap::link_cap_root;
 use crate::common_types::*;
-use crate::management::*;
+use crate::history::*;
+use crate::icrc7::{collection_metadata, is_icrc7};
+use crate::index::*;
+use crate::metadata::{check_entry_limits, Metadata};
+use crate::protection::Protection;
+use crate::stats::Stats;
+use crate::submissions::Submissions;
+use crate::taxonomy::*;
+use crate::thumbnails::check_thumbnail;
+use crate::validation::{check_external, validate};
 
 #[init]
 pub fn init(canister_registry: Option<Principal>) {
     ic::store(Admins(vec![ic::caller()]));
     if let Some(canister_registry) = canister_registry {
-        ic::store(Registry(HashMap::new(), canister_registry));
+        ic::store(Registry(BTreeMap::new(), canister_registry));
     }
+    ic::get::<Integrity>().certify();
 }
 
 // (registry map, canister registry id)
-pub struct Registry(HashMap<Principal, NftCanister>, Principal);
+// Entries are kept ordered by principal so `get_all` is stable across calls and upgrades.
+pub struct Registry(BTreeMap<Principal, NftCanister>, Principal);
 impl Default for Registry {
     fn default() -> Self {
-        Registry(HashMap::new(), CANISTER_REGISTRY_ID.try_into().unwrap())
+        Registry(BTreeMap::new(), CANISTER_REGISTRY_ID.try_into().unwrap())
     }
 }
 
 impl Registry {
     pub fn archive(&mut self) -> Vec<(Principal, NftCanister)> {
-        let map = std::mem::replace(&mut self.0, HashMap::new());
+        let map = std::mem::replace(&mut self.0, BTreeMap::new());
         map.into_iter().collect()
     }
 
@@ -34,16 +65,33 @@ impl Registry {
         self.0 = archive.into_iter().collect();
     }
 
+    // Stores the entry, releasing the assets its previous version was the last to reference
+    fn insert(&mut self, nft: NftCanister) {
+        let previous = self
+            .0
+            .get(&nft.principal_id)
+            .map_or(&[][..], |previous| &previous.details);
+        let orphans = ic::get_mut::<AssetRefs>().retrack(nft.principal_id, previous, &nft.details);
+        self.0.insert(nft.principal_id, nft);
+        release::<Submissions>(orphans);
+    }
+
     pub fn add(
         &mut self,
         caller: &Principal,
         canister_info: AddNftInput,
     ) -> Result<(), OperationError> {
+        ic::get::<Mirror>().check_writable()?;
+
         let nft = self.0.get(&canister_info.principal_id);
 
-        // If its an update, check if the caller matches the submitter or if its an admin
-        if nft.is_some() && !is_admin(caller) && nft.unwrap().submitter != *caller {
-            return Err(OperationError::NotAuthorized);
+        if nft.is_some() {
+            ic::get::<Locks>().check(&canister_info.principal_id)?;
+        }
+
+        // If its an update, check if the caller manages the entry or if its an admin
+        if nft.is_some() && !is_admin(caller) && nft.unwrap().manager() != *caller {
+            return Err(OperationError::not_authorized(Role::Submitter, caller));
         }
 
         // An admin can update any entry
@@ -58,27 +106,50 @@ impl Registry {
                 last_updated_by: *caller,
                 last_updated_at: ic::time(),
                 details: canister_info.details.clone(),
+                resolved_from: None,
+                managed_by: nft.unwrap().managed_by,
             };
+            check_entry_limits(nft, &updated_nft)?;
 
-            self.0.insert(canister_info.principal_id, updated_nft);
+            store_event(EventKind::Update, &updated_nft);
+            ic::get_mut::<SortIndex>().insert(&updated_nft);
+            ic::get_mut::<FrontendIndex>().insert(&updated_nft);
+            ic::get_mut::<ManagerIndex>().insert(&updated_nft);
+            ic::get_mut::<PrincipalIndex>().insert(&updated_nft);
+            ic::get_mut::<Integrity>().insert(&updated_nft);
+            self.insert(updated_nft);
         }
         // Its a new entry
         else {
+            let kind = match nft {
+                Some(_) => EventKind::Update,
+                None => EventKind::Add,
+            };
             let new_nft = NftCanister {
                 name: canister_info.name,
                 description: canister_info.description,
                 thumbnail: canister_info.thumbnail,
                 frontend: canister_info.frontend,
                 principal_id: canister_info.principal_id,
-                submitter: *caller,
+                submitter: nft.map_or(*caller, |nft| nft.submitter),
                 last_updated_by: *caller,
                 last_updated_at: ic::time(),
                 details: canister_info.details.clone(),
+                resolved_from: None,
+                managed_by: nft.and_then(|nft| nft.managed_by),
             };
+            check_entry_limits(nft, &new_nft)?;
 
-            self.0.insert(canister_info.principal_id, new_nft);
+            store_event(kind, &new_nft);
+            ic::get_mut::<SortIndex>().insert(&new_nft);
+            ic::get_mut::<FrontendIndex>().insert(&new_nft);
+            ic::get_mut::<ManagerIndex>().insert(&new_nft);
+            ic::get_mut::<PrincipalIndex>().insert(&new_nft);
+            ic::get_mut::<Integrity>().insert(&new_nft);
+            self.insert(new_nft);
         }
 
+        ic::get_mut::<AlertSink>().check_capacity();
         Ok(())
     }
 
@@ -87,28 +158,262 @@ impl Registry {
         caller: &Principal,
         principal_id: &Principal,
     ) -> Result<(), OperationError> {
+        ic::get::<Mirror>().check_writable()?;
+
         if !self.0.contains_key(principal_id) {
             return Err(OperationError::NonExistentItem);
         }
 
+        ic::get::<Locks>().check(principal_id)?;
+
         let nft = self.0.get(principal_id).unwrap();
 
-        if nft.submitter != *caller && !is_admin(caller) {
-            return Err(OperationError::NotAuthorized);
+        if nft.manager() != *caller && !is_admin(caller) {
+            return Err(OperationError::not_authorized(Role::Submitter, caller));
         }
 
-        self.0.remove(principal_id);
+        let removed = self.0.remove(principal_id).unwrap();
+        store_event(EventKind::Remove, &removed);
+        raise(AlertKind::EntryRemoved {
+            principal_id: *principal_id,
+            by: *caller,
+        });
+        ic::get_mut::<SortIndex>().remove(principal_id);
+        ic::get_mut::<FrontendIndex>().remove(principal_id);
+        ic::get_mut::<ManagerIndex>().remove(principal_id);
+        ic::get_mut::<PrincipalIndex>().remove(principal_id);
+        ic::get_mut::<Integrity>().remove(principal_id);
+        ic::get_mut::<Translations>().remove(principal_id);
+        ic::get_mut::<Reports>().clear(principal_id);
+        ic::get_mut::<Pins>().unpin(principal_id);
+        ic::get_mut::<Taxonomy>().remove(principal_id);
+        ic::get_mut::<Stats>().remove(principal_id);
+        ic::get_mut::<EntryCallbacks>().0.remove(principal_id);
+        release::<Submissions>(ic::get_mut::<AssetRefs>().untrack(principal_id, &removed.details));
 
         return Ok(());
     }
 
+    // Hands the management of an entry over to `new_owner`
+    pub fn transfer_ownership(
+        &mut self,
+        caller: &Principal,
+        principal_id: &Principal,
+        new_owner: Principal,
+    ) -> Result<(), OperationError> {
+        ic::get::<Mirror>().check_writable()?;
+
+        check_principal(&new_owner)?;
+
+        let nft = match self.0.get_mut(principal_id) {
+            Some(nft) => nft,
+            None => return Err(OperationError::NonExistentItem),
+        };
+
+        ic::get::<Locks>().check(principal_id)?;
+
+        if nft.manager() != *caller && !is_admin(caller) {
+            return Err(OperationError::not_authorized(Role::Submitter, caller));
+        }
+
+        nft.managed_by = Some(new_owner);
+        nft.last_updated_by = *caller;
+        nft.last_updated_at = ic::time();
+
+        store_event(EventKind::Update, nft);
+        ic::get_mut::<SortIndex>().insert(nft);
+        ic::get_mut::<ManagerIndex>().insert(nft);
+        ic::get_mut::<PrincipalIndex>().insert(nft);
+        ic::get_mut::<Integrity>().insert(nft);
+        Ok(())
+    }
+
+    // Removes an entry regardless of its locks and manager, see `emergency_delist`
+    pub fn delist(&mut self, principal_id: &Principal) -> Result<NftCanister, OperationError> {
+        ic::get::<Mirror>().check_writable()?;
+
+        let removed = self
+            .0
+            .remove(principal_id)
+            .ok_or(OperationError::NonExistentItem)?;
+        store_event(EventKind::Delist, &removed);
+        ic::get_mut::<SortIndex>().remove(principal_id);
+        ic::get_mut::<FrontendIndex>().remove(principal_id);
+        ic::get_mut::<ManagerIndex>().remove(principal_id);
+        ic::get_mut::<PrincipalIndex>().remove(principal_id);
+        ic::get_mut::<Integrity>().remove(principal_id);
+        ic::get_mut::<Translations>().remove(principal_id);
+        ic::get_mut::<Reports>().clear(principal_id);
+        ic::get_mut::<Pins>().unpin(principal_id);
+        ic::get_mut::<Taxonomy>().remove(principal_id);
+        ic::get_mut::<Stats>().remove(principal_id);
+        release::<Submissions>(ic::get_mut::<AssetRefs>().untrack(principal_id, &removed.details));
+        Ok(removed)
+    }
+
     pub fn get(&self, principal_id: &Principal) -> Option<&NftCanister> {
         self.0.get(principal_id)
     }
 
+    pub fn canister_registry(&self) -> Principal {
+        self.1
+    }
+
+    // Replays an event of the upstream registry, skipping validation and authorization
+    pub fn apply(&mut self, event: Event) {
+        let principal_id = event.entry.principal_id;
+        store_event(event.kind, &event.entry);
+
+        match event.kind {
+            EventKind::Remove | EventKind::Delist => {
+                if let Some(removed) = self.0.remove(&principal_id) {
+                    release::<Submissions>(
+                        ic::get_mut::<AssetRefs>().untrack(&principal_id, &removed.details),
+                    );
+                }
+                ic::get_mut::<SortIndex>().remove(&principal_id);
+                ic::get_mut::<FrontendIndex>().remove(&principal_id);
+                ic::get_mut::<ManagerIndex>().remove(&principal_id);
+                ic::get_mut::<PrincipalIndex>().remove(&principal_id);
+                ic::get_mut::<Integrity>().remove(&principal_id);
+                ic::get_mut::<Translations>().remove(&principal_id);
+                ic::get_mut::<Reports>().clear(&principal_id);
+                ic::get_mut::<Pins>().unpin(&principal_id);
+                ic::get_mut::<Taxonomy>().remove(&principal_id);
+                ic::get_mut::<Stats>().remove(&principal_id);
+            }
+            EventKind::Add | EventKind::Update => {
+                ic::get_mut::<SortIndex>().insert(&event.entry);
+                ic::get_mut::<FrontendIndex>().insert(&event.entry);
+                ic::get_mut::<ManagerIndex>().insert(&event.entry);
+                ic::get_mut::<PrincipalIndex>().insert(&event.entry);
+                ic::get_mut::<Integrity>().insert(&event.entry);
+                self.insert(event.entry);
+            }
+        }
+    }
+
+    // Renames the `old` detail of every entry to `new` and returns how many entries hold it.
+    // Nothing is written on a dry run, which fails the same way the rename would.
+    pub fn rename_detail_key(
+        &mut self,
+        caller: &Principal,
+        old: &str,
+        new: &str,
+        dry_run: bool,
+    ) -> Result<u64, OperationError> {
+        ic::get::<Mirror>().check_writable()?;
+
+        let touched: Vec<Principal> = self
+            .0
+            .values()
+            .filter(|nft| detail(&nft.details, old).is_some())
+            .map(|nft| nft.principal_id)
+            .collect();
+
+        for principal_id in touched.iter() {
+            ic::get::<Locks>().check(principal_id)?;
+            // Entries holding both keys would lose one of the values
+            if detail(&self.0[principal_id].details, new).is_some() {
+                return Err(OperationError::BadParameters);
+            }
+        }
+
+        if dry_run {
+            return Ok(touched.len() as u64);
+        }
+
+        for principal_id in touched.iter() {
+            let nft = self.0.get_mut(principal_id).unwrap();
+            for (key, _) in nft.details.iter_mut().filter(|(key, _)| key == old) {
+                *key = String::from(new);
+            }
+            nft.last_updated_by = *caller;
+            nft.last_updated_at = ic::time();
+
+            store_event(EventKind::Update, nft);
+            ic::get_mut::<SortIndex>().insert(nft);
+            ic::get_mut::<FrontendIndex>().insert(nft);
+            ic::get_mut::<ManagerIndex>().insert(nft);
+            ic::get_mut::<PrincipalIndex>().insert(nft);
+            ic::get_mut::<Integrity>().insert(nft);
+        }
+
+        Ok(touched.len() as u64)
+    }
+
     pub fn get_all(&self) -> Vec<&NftCanister> {
         self.0.values().collect()
     }
+
+    pub fn list(&self, options: ListOptions) -> ListPage {
+        let entries = ic::get::<SortIndex>()
+            .sorted(options.sort_by, options.direction)
+            .skip(options.offset as usize)
+            .take(options.limit as usize)
+            .filter_map(|principal_id| self.0.get(&principal_id).cloned())
+            .collect();
+
+        ListPage {
+            entries,
+            total: self.0.len() as u64,
+        }
+    }
+}
+
+impl EntryStore for Registry {
+    type Entry = NftCanister;
+
+    fn entry(&self, principal_id: &Principal) -> Option<&NftCanister> {
+        self.get(principal_id)
+    }
+
+    fn principals(&self) -> Vec<Principal> {
+        self.0.keys().cloned().collect()
+    }
+
+    fn replay(&mut self, event: Event) {
+        self.apply(event)
+    }
+}
+
+// Router consulted for cross-registry conflicts before a new principal is accepted.
+// Enforcement stays disabled while no router is set.
+#[derive(Default)]
+pub struct ConflictRouter(pub Option<Principal>);
+
+// Probes `principal_id` with a method no canister implements. Only a call to a principal
+// that isn't an existing canister is rejected as an invalid destination.
+async fn check_live_canister(principal_id: Principal) -> Result<(), OperationError> {
+    let result: Result<(), _> = ic::call(principal_id, "dab_liveness_probe", ()).await;
+    match result {
+        // The principal is not a live canister
+        Err((RejectionCode::DestinationInvalid, _)) => Err(OperationError::BadParameters),
+        _ => Ok(()),
+    }
+}
+
+async fn check_conflicts(principal_id: Principal) -> Result<(), OperationError> {
+    let router = match ic::get::<ConflictRouter>().0 {
+        Some(router) => router,
+        None => return Ok(()),
+    };
+
+    let conflicts: Vec<Conflict> = match ic::call(router, "check_conflicts", (principal_id,)).await
+    {
+        Ok((x,)) => x,
+        Err((_code, msg)) => {
+            return Err(OperationError::Unknown(msg));
+        }
+    };
+
+    match conflicts
+        .iter()
+        .find(|conflict| conflict.registry != ic::id())
+    {
+        Some(conflict) => Err(OperationError::Conflict(conflict.registry)),
+        None => Ok(()),
+    }
 }
 
 #[query]
@@ -116,78 +421,364 @@ fn name() -> String {
     String::from("NFT Registry Canister")
 }
 
+#[query]
+pub fn registry_info() -> RegistryInfo {
+    let db = ic::get::<Registry>();
+    let entries = db.get_all();
+
+    // Removals only show up in the history, entries stored before it existed only in the db
+    let last_modified_at = std::cmp::max(
+        ic::get::<History>()
+            .0
+            .last()
+            .map(|event| event.time)
+            .unwrap_or_default(),
+        entries
+            .iter()
+            .map(|entry| entry.last_updated_at)
+            .max()
+            .unwrap_or_default(),
+    );
+
+    RegistryInfo {
+        name: name(),
+        version: String::from(env!("CARGO_PKG_VERSION")),
+        entry_count: entries.len() as u64,
+        supported_standards: SUPPORTED_STANDARDS.iter().map(|s| s.to_string()).collect(),
+        admins: ic::get::<Admins>().0.clone(),
+        last_modified_at,
+    }
+}
+
+// Lets clients detect optional endpoints instead of assuming them from the registry
+#[query]
+pub fn supported_features() -> Vec<String> {
+    SUPPORTED_FEATURES.iter().map(|s| s.to_string()).collect()
+}
+
+// Calls retried with the same `request_id` return the result of the first one
 #[update]
 pub async fn add(
     trusted_source: Option<Principal>,
     canister_info: AddNftInput,
+    request_id: Option<Vec<u8>>,
 ) -> Result<(), OperationError> {
+    ic::get_mut::<Usage>().record("add");
+    ic::get::<Maintenance>().check()?;
+
     let caller = ic::caller();
     if !is_admin(&caller) {
-        return Err(OperationError::NotAuthorized);
-    } else if !validate_url(&canister_info.thumbnail) {
-        return Err(OperationError::BadParameters);
-    } else if canister_info.frontend.is_some()
-        && !validate_url(&canister_info.frontend.clone().unwrap())
-    {
-        return Err(OperationError::BadParameters);
-    } else if canister_info.details[0].0 != String::from("standard") {
-        return Err(OperationError::BadParameters);
-    } else if canister_info.details.len() != 1 {
-        return Err(OperationError::BadParameters);
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
     }
+    trusted_source.as_ref().map_or(Ok(()), check_principal)?;
 
-    let name = canister_info.name.clone();
-    if name.len() <= NAME_LIMIT && &canister_info.description.len() <= &DESCRIPTION_LIMIT {
-        // Add the collection to the canister registry
-        let mut call_arg = canister_info.clone();
-        call_arg.details = vec![("category".to_string(), DetailValue::Text("NFT".to_string()))];
-        let canister_registry = ic::get::<Registry>().1;
+    if let Some(result) = ic::get_mut::<Requests>().start(&caller, &request_id) {
+        return localize(result);
+    }
 
-        // set canister registry to `aaaaa-aa` to skip canister registry insertion
-        if canister_registry != Principal::management_canister() {
-            let _registry_add_response: RegistryResponse = match ic::call(
-                canister_registry,
-                "add",
-                (trusted_source.unwrap_or(ic::id()), call_arg),
+    let principal_id = canister_info.principal_id;
+    let listed = ic::get::<Registry>().get(&principal_id).is_some();
+    let protection = ic::get_mut::<Protection>();
+    let result = match protection.is_protected(&principal_id) {
+        true => Err(protection.defer(
+            principal_id,
+            trusted_source,
+            ProposedChange::Update(canister_info),
+        )),
+        false => {
+            add_entry(
+                trusted_source.unwrap_or(caller),
+                trusted_source,
+                canister_info,
             )
             .await
-            {
-                Ok((x,)) => x,
-                Err((_code, msg)) => {
-                    return Err(OperationError::Unknown(msg));
-                }
-            };
         }
+    };
+    if listed && result.is_ok() {
+        notify_owner(principal_id, EntryStatus::EditedByAdmin);
+    }
+    ic::get_mut::<Requests>().finish(&caller, &request_id, &result);
+    log_result("add", caller, &result);
+    localize(result)
+}
+
+// Validates and stores an entry on behalf of `submitter`.
+// Authorizing the call is up to the caller of this function.
+pub async fn add_entry(
+    submitter: Principal,
+    trusted_source: Option<Principal>,
+    mut canister_info: AddNftInput,
+) -> Result<(), OperationError> {
+    ic::get::<ReservedNames>().check(
+        &canister_info.name,
+        &submitter,
+        &canister_info.principal_id,
+    )?;
+    ic::get::<Mirror>().check_writable()?;
+    ic::get::<Locks>().check(&canister_info.principal_id)?;
+
+    validate(&canister_info)?;
+    check_external(&canister_info).await?;
+
+    if ic::get::<Registry>()
+        .get(&canister_info.principal_id)
+        .is_none()
+    {
+        check_thumbnail(&canister_info.thumbnail).await?;
+        check_live_canister(canister_info.principal_id).await?;
+        check_conflicts(canister_info.principal_id).await?;
+    }
+
+    // The metadata is fetched again on updates so it follows the collection
+    if is_icrc7(Metadata::parse(&canister_info.details)?.standard()) {
+        let metadata = collection_metadata(canister_info.principal_id).await?;
+        canister_info
+            .details
+            .retain(|(key, _)| key != "collection_metadata");
+        canister_info
+            .details
+            .push((String::from("collection_metadata"), metadata));
+    }
+    link_cap_root(canister_info.principal_id, &mut canister_info.details).await?;
+
+    // Add the collection to the canister registry
+    let mut call_arg = canister_info.clone();
+    call_arg.details = vec![("category".to_string(), DetailValue::Text("NFT".to_string()))];
+    let canister_registry = ic::get::<Registry>().1;
 
-        let db = ic::get_mut::<Registry>();
-        return db.add(&trusted_source.unwrap_or(caller), canister_info);
+    // set canister registry to `aaaaa-aa` to skip canister registry insertion
+    if canister_registry != Principal::management_canister() {
+        let _registry_add_response: RegistryResponse = match ic::call(
+            canister_registry,
+            "add",
+            (trusted_source.unwrap_or(ic::id()), call_arg),
+        )
+        .await
+        {
+            Ok((x,)) => x,
+            Err((_code, msg)) => {
+                return Err(OperationError::Unknown(msg));
+            }
+        };
     }
 
-    Err(OperationError::BadParameters)
+    let db = ic::get_mut::<Registry>();
+    return db.add(&submitter, canister_info);
 }
 
 #[update]
 pub fn remove(
     trusted_source: Option<Principal>,
     principal_id: Principal,
+    request_id: Option<Vec<u8>>,
 ) -> Result<(), OperationError> {
+    ic::get_mut::<Usage>().record("remove");
+    ic::get::<Maintenance>().check()?;
+
     let caller = ic::caller();
     if !is_admin(&caller) {
-        return Err(OperationError::NotAuthorized);
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
     }
+    trusted_source.as_ref().map_or(Ok(()), check_principal)?;
 
-    let db = ic::get_mut::<Registry>();
-    db.remove(&trusted_source.unwrap_or(caller), &principal_id)
+    if let Some(result) = ic::get_mut::<Requests>().start(&caller, &request_id) {
+        return localize(result);
+    }
+
+    let protection = ic::get_mut::<Protection>();
+    let result = match protection.is_protected(&principal_id) {
+        true => Err(protection.defer(principal_id, trusted_source, ProposedChange::Remove)),
+        false => {
+            let db = ic::get_mut::<Registry>();
+            db.remove(&trusted_source.unwrap_or(caller), &principal_id)
+        }
+    };
+    ic::get_mut::<Requests>().finish(&caller, &request_id, &result);
+    log_result("remove", caller, &result);
+    localize(result)
+}
+
+#[update]
+pub fn transfer_entry_ownership(
+    principal_id: Principal,
+    new_owner: Principal,
+) -> Result<(), OperationError> {
+    ic::get_mut::<Usage>().record("transfer_entry_ownership");
+    ic::get::<Maintenance>().check()?;
+
+    localize(ic::get_mut::<Registry>().transfer_ownership(&ic::caller(), &principal_id, new_owner))
 }
 
 #[query]
-pub fn get(principal_id: Principal) -> Option<&'static NftCanister> {
-    let db = ic::get_mut::<Registry>();
-    db.get(&principal_id)
+pub fn get(principal_id: Principal) -> Option<NftCanister> {
+    let db = ic::get::<Registry>();
+    if let Some(entry) = db.get(&principal_id) {
+        return Some(entry.clone());
+    }
+
+    // Legacy principals resolve to the entry they were redeployed as
+    let new = ic::get::<Aliases>().resolve(&principal_id)?;
+    let mut entry = db.get(&new)?.clone();
+    entry.resolved_from = Some(principal_id);
+    Some(entry)
+}
+
+// Standard of the entry, or `None` when `principal_id` isn't listed. Legacy principals
+// resolve like in `get`, without the cost of cloning the whole entry.
+#[query]
+pub fn exists(principal_id: Principal) -> Option<String> {
+    let db = ic::get::<Registry>();
+    let entry = db
+        .get(&principal_id)
+        .or_else(|| db.get(&ic::get::<Aliases>().resolve(&principal_id)?))?;
+    let standard = detail(&entry.details, "standard").and_then(DetailValue::as_text);
+    Some(standard.unwrap_or_default().to_string())
 }
 
 #[query]
 pub fn get_all() -> Vec<&'static NftCanister> {
-    let db = ic::get_mut::<Registry>();
+    let db = ic::get::<Registry>();
     db.get_all()
 }
+
+// Principals of every entry in the order of `get_all`, for clients that only check
+// membership or `get` entries one by one
+#[query]
+pub fn get_all_principals() -> Vec<Principal> {
+    ic::get::<Registry>().0.keys().copied().collect()
+}
+
+#[query]
+pub fn get_principals(page: Page) -> Vec<Principal> {
+    ic::get::<Registry>()
+        .0
+        .keys()
+        .skip(page.offset as usize)
+        .take(page.limit as usize)
+        .copied()
+        .collect()
+}
+
+// Encoded size of the replies of `get_all_v2`, editable by admins without an upgrade
+pub struct ResponseBudget(pub u64);
+
+impl Default for ResponseBudget {
+    fn default() -> Self {
+        ResponseBudget(DEFAULT_RESPONSE_BUDGET)
+    }
+}
+
+// `get_all` cut to the response budget, so large registries don't fail to reply
+#[query]
+pub fn get_all_v2() -> GetAllResponse {
+    let entries = ic::get::<Registry>().get_all();
+    let budget = ic::get::<ResponseBudget>().0;
+
+    let mut size = 0u64;
+    let mut response = GetAllResponse {
+        entries: Vec::with_capacity(entries.len()),
+        truncated: false,
+    };
+    for entry in entries {
+        let bytes = encode_one(entry).map_or(u64::MAX, |bytes| bytes.len() as u64);
+        size = size.saturating_add(bytes);
+        if size > budget {
+            response.truncated = true;
+            break;
+        }
+        response.entries.push(entry.clone());
+    }
+
+    response
+}
+
+#[update]
+pub fn set_response_budget(bytes: u64) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if bytes == 0 {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::store(ResponseBudget(bytes));
+    Ok(())
+}
+
+#[query]
+pub fn get_response_budget() -> u64 {
+    ic::get::<ResponseBudget>().0
+}
+
+#[update]
+pub fn set_conflict_router(router: Option<Principal>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    ic::get_mut::<ConflictRouter>().0 = router;
+    Ok(())
+}
+
+#[query]
+pub fn list(options: ListOptions) -> ListPage {
+    ic::get::<Registry>().list(options)
+}
+
+// `get` and `list` tagged with the version of the entry shape
+#[query]
+pub fn get_v2(principal_id: Principal) -> Envelope<Option<NftCanister>> {
+    Envelope::new(ENTRY_SCHEMA_VERSION, get(principal_id))
+}
+
+#[query]
+pub fn list_v2(options: ListOptions) -> Envelope<ListPage> {
+    Envelope::new(ENTRY_SCHEMA_VERSION, list(options))
+}
+
+// Entries whose frontend is served from the host of `host`, which may be given as a full URL
+#[query]
+pub fn get_by_frontend_host(host: String) -> Vec<&'static NftCanister> {
+    let db = ic::get::<Registry>();
+    let host = url_host(&host).unwrap_or_default();
+    ic::get::<FrontendIndex>()
+        .get(&host)
+        .iter()
+        .filter_map(|principal_id| db.get(principal_id))
+        .collect()
+}
+
+// Entries managed by `owner`, through an ownership transfer or as their submitter
+#[query]
+pub fn get_entries_managed_by(owner: Principal) -> Vec<&'static NftCanister> {
+    let db = ic::get::<Registry>();
+    ic::get::<ManagerIndex>()
+        .get(&owner)
+        .iter()
+        .filter_map(|principal_id| db.get(principal_id))
+        .collect()
+}
+
+// Up to `SEARCH_LIMIT` entries whose textual principal starts with `prefix`
+#[query]
+pub fn search_principal(prefix: String) -> Vec<&'static NftCanister> {
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.is_empty() {
+        return vec![];
+    }
+
+    let db = ic::get::<Registry>();
+    ic::get::<PrincipalIndex>()
+        .search(&prefix, SEARCH_LIMIT)
+        .iter()
+        .filter_map(|principal_id| db.get(principal_id))
+        .collect()
+}
+
+#[query]
+pub fn get_changes(since_event_id: u64, limit: u16) -> Result<Vec<&'static Event>, OperationError> {
+    ic::get::<History>().get_changes(since_event_id, limit)
+}
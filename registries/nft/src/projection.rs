@@ -0,0 +1,94 @@
+use ic_kit::macros::*;
+use ic_kit::*;
+
+use crate::common_types::*;
+use crate::nft::Registry;
+
+// Fields of an entry a projection can select besides its details
+const ENTRY_FIELDS: [&str; 8] = [
+    "name",
+    "description",
+    "thumbnail",
+    "frontend",
+    "submitter",
+    "last_updated_by",
+    "last_updated_at",
+    "managed_by",
+];
+
+fn check_fields(fields: &[String]) -> Result<(), OperationError> {
+    let known = |field: &String| {
+        ENTRY_FIELDS.contains(&field.as_str())
+            || field
+                .strip_prefix("details.")
+                .map_or(false, |key| !key.is_empty())
+    };
+
+    match fields.iter().all(known) {
+        true => Ok(()),
+        false => Err(OperationError::BadParameters),
+    }
+}
+
+fn field(entry: &NftCanister, field: &str) -> Option<DetailValue> {
+    let text = |text: &String| DetailValue::Text(text.clone());
+    match field {
+        "name" => Some(text(&entry.name)),
+        "description" => Some(text(&entry.description)),
+        "thumbnail" => Some(text(&entry.thumbnail)),
+        "frontend" => entry.frontend.as_ref().map(text),
+        "submitter" => Some(DetailValue::Principal(entry.submitter)),
+        "last_updated_by" => Some(DetailValue::Principal(entry.last_updated_by)),
+        "last_updated_at" => Some(DetailValue::U64(entry.last_updated_at)),
+        "managed_by" => entry.managed_by.map(DetailValue::Principal),
+        field => {
+            let key = field.strip_prefix("details.")?;
+            entry
+                .details
+                .iter()
+                .find(|(detail, _)| detail == key)
+                .map(|(_, value)| value.clone())
+        }
+    }
+}
+
+pub fn project(entry: &NftCanister, fields: &[String]) -> PartialEntry {
+    PartialEntry {
+        principal_id: entry.principal_id,
+        fields: fields
+            .iter()
+            .filter_map(|name| Some((name.clone(), field(entry, name)?)))
+            .collect(),
+    }
+}
+
+// `get_all` with only the requested fields of every entry, for clients rendering long
+// lists. Fields are named as in `NftCanister`, details as "details.<key>".
+#[query]
+pub fn get_all_projected(fields: Vec<String>) -> Result<Vec<PartialEntry>, OperationError> {
+    check_fields(&fields)?;
+
+    Ok(ic::get::<Registry>()
+        .get_all()
+        .into_iter()
+        .map(|entry| project(entry, &fields))
+        .collect())
+}
+
+#[query]
+pub fn list_projected(
+    options: ListOptions,
+    fields: Vec<String>,
+) -> Result<PartialListPage, OperationError> {
+    check_fields(&fields)?;
+
+    let page = ic::get::<Registry>().list(options);
+    Ok(PartialListPage {
+        entries: page
+            .entries
+            .iter()
+            .map(|entry| project(entry, &fields))
+            .collect(),
+        total: page.total,
+    })
+}
@@ -0,0 +1,55 @@
+use ic_cdk::api::call::notify;
+use ic_kit::*;
+use registry_framework::ledger::retry_refund;
+use registry_framework::retries::Retries;
+use registry_framework::subscribers::Subscribers;
+
+use crate::cap::{handshake, Cap};
+use crate::common_types::*;
+use crate::history::History;
+use crate::nft::ConflictRouter;
+
+// Runs the side effect of `task`, putting it back in the queue when it fails again
+pub fn run(task: RetryTask) {
+    match task.kind {
+        TaskKind::Deliver {
+            subscriber,
+            event_id,
+        } => {
+            let sent = match ic::get::<History>().get(event_id) {
+                Some(event) => ic::get::<Subscribers>().send(&subscriber, event),
+                None => true,
+            };
+            if !sent {
+                let error = String::from("the notification couldn't be sent");
+                ic::get_mut::<Retries>().reschedule(task, error);
+            }
+        }
+        TaskKind::Invalidate { principal_id } => {
+            // Nothing to invalidate once the router is unset
+            if let Some(router) = ic::get::<ConflictRouter>().0 {
+                if let Err(code) = notify(router, "invalidate", (principal_id,)) {
+                    ic::get_mut::<Retries>().reschedule(task, format!("{:?}", code));
+                }
+            }
+        }
+        TaskKind::CapHandshake => {
+            let cap = ic::get_mut::<Cap>();
+            // A handshake in flight queues its own retry when it fails
+            if cap.root.is_none() && !cap.handshaking {
+                cap.handshaking = true;
+                ic_cdk::spawn(handshake(Some(task)));
+            }
+        }
+        TaskKind::Refund { .. } => ic_cdk::spawn(retry_refund(task)),
+        TaskKind::EntryCallback {
+            target,
+            ref method,
+            ref notice,
+        } => {
+            if let Err(code) = notify(target, method, (notice.clone(),)) {
+                ic::get_mut::<Retries>().reschedule(task, format!("{:?}", code));
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+
+use crate::common_types::*;
+use crate::nft::Registry;
+use crate::validation::validate;
+
+fn seed_input(collection: &::seed::SeedNft) -> AddNftInput {
+    AddNftInput {
+        name: collection.name.to_string(),
+        description: collection.description.to_string(),
+        thumbnail: collection.thumbnail.to_string(),
+        frontend: collection.frontend.map(String::from),
+        principal_id: Principal::from_text(collection.principal_id).unwrap(),
+        details: vec![(
+            String::from("standard"),
+            DetailValue::from(collection.standard),
+        )],
+    }
+}
+
+// Adds the canonical mainnet collections of the `seed` crate that aren't listed yet,
+// returning how many were added. Calling it again is a no-op.
+#[update]
+pub fn load_seed() -> Result<u64, OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    let caller = ic::caller();
+    if !is_admin(&caller) {
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
+    }
+
+    let db = ic::get_mut::<Registry>();
+    let inputs = ::seed::NFT_COLLECTIONS
+        .iter()
+        .map(seed_input)
+        .filter(|input| db.get(&input.principal_id).is_none())
+        .collect::<Vec<_>>();
+    inputs.iter().try_for_each(validate)?;
+
+    let added = inputs.len() as u64;
+    for input in inputs {
+        db.add(&caller, input)?;
+    }
+    Ok(added)
+}
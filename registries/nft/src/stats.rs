@@ -0,0 +1,122 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::guard::check_caller;
+use registry_framework::management::*;
+use std::collections::HashMap;
+
+use crate::common_types::*;
+use crate::nft::Registry;
+
+// Minimum time between two automatic collections of the stats
+const REFRESH_INTERVAL: u64 = 6 * 60 * 60 * 1_000_000_000;
+
+// Supply and holder count of every collection, read from the collections themselves
+// by a background job so explorers don't have to query each collection canister.
+#[derive(Default)]
+pub struct Stats {
+    stats: HashMap<Principal, CollectionStats>,
+    last_run_at: u64,
+    running: bool,
+}
+
+impl Stats {
+    pub fn archive(&mut self) -> Vec<(Principal, CollectionStats)> {
+        std::mem::take(&mut self.stats).into_iter().collect()
+    }
+
+    pub fn load(&mut self, archive: Vec<(Principal, CollectionStats)>) {
+        self.stats = archive.into_iter().collect();
+    }
+
+    pub fn get(&self, principal_id: &Principal) -> Option<&CollectionStats> {
+        self.stats.get(principal_id)
+    }
+
+    pub fn remove(&mut self, principal_id: &Principal) {
+        self.stats.remove(principal_id);
+    }
+
+    // Starts a collection unless one is already running. Returns whether it started.
+    fn start(&mut self) -> bool {
+        if self.running {
+            return false;
+        }
+
+        self.running = true;
+        self.last_run_at = ic::time();
+        ic_cdk::spawn(collect());
+        true
+    }
+
+    // Starts a collection if one is due. Called from the heartbeat.
+    pub fn tick(&mut self) {
+        if ic::time() >= self.last_run_at + REFRESH_INTERVAL {
+            self.start();
+        }
+    }
+}
+
+async fn collect() {
+    let collections: Vec<(Principal, String)> = ic::get::<Registry>()
+        .get_all()
+        .into_iter()
+        .filter_map(|entry| {
+            let standard = detail(&entry.details, "standard")?.as_text()?;
+            Some((entry.principal_id, standard.to_string()))
+        })
+        .collect();
+
+    for (principal_id, standard) in collections {
+        let result = standards::get_collection_supply(&standard, principal_id).await;
+
+        // The collection may have been removed while the call was in flight
+        if ic::get::<Registry>().get(&principal_id).is_none() {
+            continue;
+        }
+
+        let stats = ic::get_mut::<Stats>();
+        match result {
+            Ok(supply) => {
+                stats.stats.insert(
+                    principal_id,
+                    CollectionStats {
+                        total_supply: supply.total_supply,
+                        holders: supply.holders,
+                        updated_at: ic::time(),
+                        last_error: None,
+                    },
+                );
+            }
+            // Keeps the last known values, they are still the best estimate
+            Err(err) => {
+                if let Some(entry) = stats.stats.get_mut(&principal_id) {
+                    entry.last_error = Some(format!("{:?}", err));
+                }
+            }
+        }
+    }
+
+    ic::get_mut::<Stats>().running = false;
+}
+
+#[update]
+pub fn refresh_collection_stats() -> Result<(), OperationError> {
+    check_caller()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    match ic::get_mut::<Stats>().start() {
+        true => Ok(()),
+        false => Err(OperationError::Unknown(String::from(
+            "A stats collection is already running",
+        ))),
+    }
+}
+
+#[query]
+pub fn get_collection_stats(principal_id: Principal) -> Option<CollectionStats> {
+    ic::get::<Stats>().get(&principal_id).cloned()
+}
@@ -0,0 +1,362 @@
+use ic_kit::candid::{Nat, Principal};
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::callbacks::notify_owner;
+use registry_framework::curation::Curation;
+use registry_framework::entries::SubmissionStore;
+use registry_framework::guard::check_caller;
+use registry_framework::ledger::*;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+use registry_framework::messages::localize;
+use registry_framework::reserved::ReservedNames;
+use registry_framework::usage::Usage;
+use std::collections::{HashMap, VecDeque};
+
+use crate::common_types::*;
+use crate::nft::add_entry;
+use crate::reviewers::check_reviewer;
+use crate::validation::{check_external, validate};
+
+// Pending submissions expire after this long unless admins set another TTL
+const DEFAULT_SUBMISSION_TTL: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+
+// Minimum time between two sweeps of the pending submissions
+const SWEEP_INTERVAL: u64 = 60 * 60 * 1_000_000_000;
+
+// Expired submissions kept for admins to look at, the oldest are dropped first
+const EXPIRED_LIMIT: usize = 500;
+
+// Entries proposed by non-admins, keyed by the principal of the proposed entry.
+// They only reach the registry once an admin approves them.
+#[derive(Default)]
+pub struct Submissions(HashMap<Principal, Submission>);
+
+impl Submissions {
+    pub fn archive(&mut self) -> Vec<(Principal, Submission)> {
+        let map = std::mem::replace(&mut self.0, HashMap::new());
+        map.into_iter().collect()
+    }
+
+    pub fn load(&mut self, archive: Vec<(Principal, Submission)>) {
+        self.0 = archive.into_iter().collect();
+    }
+
+    pub fn submit(&mut self, submission: Submission) -> Result<(), OperationError> {
+        if self.0.contains_key(&submission.entry.principal_id) {
+            return Err(OperationError::BadParameters);
+        }
+
+        self.0.insert(submission.entry.principal_id, submission);
+        Ok(())
+    }
+
+    pub fn take(&mut self, principal_id: &Principal) -> Result<Submission, OperationError> {
+        self.0
+            .remove(principal_id)
+            .ok_or(OperationError::NonExistentItem)
+    }
+
+    pub fn get(&self, principal_id: &Principal) -> Option<&Submission> {
+        self.0.get(principal_id)
+    }
+
+    pub fn get_all(&self) -> Vec<&Submission> {
+        self.0.values().collect()
+    }
+
+    pub fn count_by(&self, submitter: &Principal) -> usize {
+        self.0
+            .values()
+            .filter(|submission| submission.submitter == *submitter)
+            .count()
+    }
+
+    // Removes and returns the submissions made before `deadline`
+    fn take_older_than(&mut self, deadline: u64) -> Vec<Submission> {
+        let stale: Vec<Principal> = self
+            .0
+            .values()
+            .filter(|submission| submission.submitted_at < deadline)
+            .map(|submission| submission.entry.principal_id)
+            .collect();
+
+        stale
+            .iter()
+            .filter_map(|principal_id| self.0.remove(principal_id))
+            .collect()
+    }
+}
+
+impl SubmissionStore for Submissions {
+    fn submitter(&self, principal_id: &Principal) -> Option<Principal> {
+        self.get(principal_id)
+            .map(|submission| submission.submitter)
+    }
+
+    fn pending_details(&self) -> Vec<&[(String, DetailValue)]> {
+        self.get_all()
+            .into_iter()
+            .map(|submission| &submission.entry.details[..])
+            .collect()
+    }
+}
+
+// Time in nanoseconds after which a pending submission expires
+pub struct SubmissionTtl(pub u64);
+
+impl Default for SubmissionTtl {
+    fn default() -> Self {
+        SubmissionTtl(DEFAULT_SUBMISSION_TTL)
+    }
+}
+
+// Submissions nobody reviewed before their TTL ran out, oldest first.
+// A sweep moves them here from the heartbeat and refunds their deposits.
+#[derive(Default)]
+pub struct Expired {
+    submissions: VecDeque<ExpiredSubmission>,
+    last_run_at: u64,
+    running: bool,
+}
+
+impl Expired {
+    pub fn archive(&mut self) -> Vec<ExpiredSubmission> {
+        std::mem::take(&mut self.submissions).into()
+    }
+
+    pub fn load(&mut self, archive: Vec<ExpiredSubmission>) {
+        self.submissions = archive.into();
+    }
+
+    pub fn get_all(&self) -> Vec<&ExpiredSubmission> {
+        self.submissions.iter().collect()
+    }
+
+    fn push(&mut self, expired: ExpiredSubmission) {
+        if self.submissions.len() >= EXPIRED_LIMIT {
+            self.submissions.pop_front();
+        }
+        self.submissions.push_back(expired);
+    }
+
+    // Starts a sweep if one is due. Called from the heartbeat.
+    pub fn tick(&mut self) {
+        // Expiring submissions writes to a frozen registry
+        if self.running
+            || ic::get::<Maintenance>().0
+            || ic::time() < self.last_run_at + SWEEP_INTERVAL
+        {
+            return;
+        }
+
+        self.running = true;
+        self.last_run_at = ic::time();
+        ic_cdk::spawn(sweep());
+    }
+}
+
+async fn sweep() {
+    let deadline = ic::time().saturating_sub(ic::get::<SubmissionTtl>().0);
+    let stale = ic::get_mut::<Submissions>().take_older_than(deadline);
+
+    for submission in stale {
+        ic::get_mut::<Curation>().clear(&submission.entry.principal_id);
+        let refund_error = match &submission.deposit {
+            Some(deposit) => {
+                let principal_id = submission.entry.principal_id;
+                refund_or_retry(deposit, &submission.submitter, principal_id)
+                    .await
+                    .err()
+                    .map(|err| format!("{:?}", err))
+            }
+            None => None,
+        };
+
+        ic::get_mut::<Expired>().push(ExpiredSubmission {
+            submission,
+            expired_at: ic::time(),
+            refund_error,
+        });
+    }
+
+    ic::get_mut::<Expired>().running = false;
+}
+
+// Deposit required from submitters. Submissions are free while none is set.
+#[derive(Default)]
+pub struct DepositConfig(pub Option<Deposit>);
+
+// Deposit balance checks waiting on the ledger, by submitter. Each holds one deposit of the
+// submitter until it's done, so concurrent submissions can't be covered by the same deposit.
+#[derive(Default)]
+pub struct DepositChecks(HashMap<Principal, u64>);
+
+impl DepositChecks {
+    // Starts a check for `submitter`, returns how many of theirs were already running
+    fn start(&mut self, submitter: Principal) -> u64 {
+        let running = self.0.entry(submitter).or_default();
+        *running += 1;
+        *running - 1
+    }
+
+    fn finish(&mut self, submitter: &Principal) {
+        if let Some(running) = self.0.get_mut(submitter) {
+            *running -= 1;
+            if *running == 0 {
+                self.0.remove(submitter);
+            }
+        }
+    }
+}
+
+#[update]
+pub async fn submit(entry: AddNftInput) -> Result<(), OperationError> {
+    localize(submit_entry(entry).await)
+}
+
+async fn submit_entry(entry: AddNftInput) -> Result<(), OperationError> {
+    check_caller()?;
+    ic::get_mut::<Usage>().record("submit");
+    ic::get::<Maintenance>().check()?;
+
+    let caller = ic::caller();
+    ic::get::<ReservedNames>().check(&entry.name, &caller, &entry.principal_id)?;
+    validate(&entry)?;
+    check_external(&entry).await?;
+
+    let deposit = ic::get::<DepositConfig>().0.clone();
+
+    // Every pending submission of the caller locks one deposit, and so does every other
+    // submission of theirs still waiting for its balance check. Nothing awaits between the
+    // end of the check and the submission being recorded.
+    if let Some(deposit) = &deposit {
+        let pending = ic::get::<Submissions>().count_by(&caller) as u64
+            + ic::get_mut::<DepositChecks>().start(caller);
+        let balance = deposit_balance(deposit.ledger, &caller).await;
+        ic::get_mut::<DepositChecks>().finish(&caller);
+
+        let required = deposit
+            .amount
+            .checked_mul(pending + 1)
+            .ok_or(OperationError::InsufficientDeposit)?;
+        if balance? < Nat::from(required) {
+            return Err(OperationError::InsufficientDeposit);
+        }
+    }
+
+    ic::get_mut::<Submissions>().submit(Submission {
+        submitter: caller,
+        submitted_at: ic::time(),
+        deposit,
+        entry,
+    })
+}
+
+#[query]
+pub fn get_submissions() -> Vec<&'static Submission> {
+    ic::get::<Submissions>().get_all()
+}
+
+#[query]
+pub fn get_expired() -> Result<Vec<&'static ExpiredSubmission>, OperationError> {
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    Ok(ic::get::<Expired>().get_all())
+}
+
+// Adds the submitted entry on behalf of its submitter and refunds their deposit. Admins and
+// the reviewers assigned to the standard of the entry can approve it.
+#[update]
+pub async fn approve_submission(principal_id: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    check_reviewer(&ic::caller(), &principal_id)?;
+
+    promote(principal_id).await
+}
+
+// Adds a pending submission to the registry, refunding the submitter's deposit.
+// Authorizing the call is up to the caller of this function.
+pub async fn promote(principal_id: Principal) -> Result<(), OperationError> {
+    let submission = ic::get_mut::<Submissions>().take(&principal_id)?;
+    ic::get_mut::<Curation>().clear(&principal_id);
+    let submitter = submission.submitter;
+    if let Err(err) = add_entry(submitter, Some(submitter), submission.entry.clone()).await {
+        ic::get_mut::<Submissions>().submit(submission)?;
+        return Err(err);
+    }
+    notify_owner(principal_id, EntryStatus::Approved);
+
+    // The entry is listed either way, a failed refund is retried from the heartbeat
+    if let Some(deposit) = &submission.deposit {
+        let _ = refund_or_retry(deposit, &submission.submitter, principal_id).await;
+    }
+    Ok(())
+}
+
+// Drops the submission and burns its deposit
+#[update]
+pub async fn reject_submission(principal_id: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+    check_reviewer(&ic::caller(), &principal_id)?;
+
+    dismiss(principal_id).await
+}
+
+// Drops a pending submission, burning the submitter's deposit.
+// Authorizing the call is up to the caller of this function.
+pub async fn dismiss(principal_id: Principal) -> Result<(), OperationError> {
+    let submission = ic::get_mut::<Submissions>().take(&principal_id)?;
+    ic::get_mut::<Curation>().clear(&principal_id);
+    match &submission.deposit {
+        Some(deposit) => burn_deposit(deposit, &submission.submitter).await,
+        None => Ok(()),
+    }
+}
+
+#[update]
+pub fn set_deposit(deposit: Option<Deposit>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    ic::get_mut::<DepositConfig>().0 = deposit;
+    Ok(())
+}
+
+#[query]
+pub fn get_deposit() -> Option<Deposit> {
+    ic::get::<DepositConfig>().0.clone()
+}
+
+#[update]
+pub fn set_submission_ttl(ttl: u64) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if ttl == 0 {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::get_mut::<SubmissionTtl>().0 = ttl;
+    Ok(())
+}
+
+#[query]
+pub fn get_submission_ttl() -> u64 {
+    ic::get::<SubmissionTtl>().0
+}
+
+// Account the caller has to fund before submitting
+#[query]
+pub fn get_deposit_account() -> Account {
+    Account {
+        owner: ic::id(),
+        subaccount: Some(deposit_subaccount(&ic::caller())),
+    }
+}
@@ -0,0 +1,255 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::locks::Locks;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::common_types::*;
+use crate::nft::Registry;
+
+// Controller-managed categories and free-form tags of the collections, with
+// inverted indexes so browse pages don't have to scan the whole registry.
+#[derive(Default)]
+pub struct Taxonomy {
+    categories: BTreeSet<String>,
+    entry_categories: HashMap<Principal, String>,
+    entry_tags: HashMap<Principal, Vec<String>>,
+    by_category: HashMap<String, BTreeSet<Principal>>,
+    by_tag: HashMap<String, BTreeSet<Principal>>,
+}
+
+impl Taxonomy {
+    pub fn archive(&mut self) -> TaxonomyArchive {
+        let taxonomy = std::mem::replace(self, Taxonomy::default());
+        TaxonomyArchive {
+            categories: taxonomy.categories.into_iter().collect(),
+            entry_categories: taxonomy.entry_categories.into_iter().collect(),
+            entry_tags: taxonomy.entry_tags.into_iter().collect(),
+        }
+    }
+
+    pub fn load(&mut self, archive: TaxonomyArchive) {
+        *self = Taxonomy::default();
+        self.categories = archive.categories.into_iter().collect();
+        for (principal_id, category) in archive.entry_categories {
+            self.set_category(principal_id, Some(category));
+        }
+        for (principal_id, tags) in archive.entry_tags {
+            self.set_tags(principal_id, tags);
+        }
+    }
+
+    pub fn add_category(&mut self, category: String) {
+        self.categories.insert(category);
+    }
+
+    // Removing a category also removes it from every entry in it
+    pub fn remove_category(&mut self, category: &str) -> Result<(), OperationError> {
+        if !self.categories.remove(category) {
+            return Err(OperationError::NonExistentItem);
+        }
+
+        for principal_id in self.by_category.remove(category).unwrap_or_default() {
+            self.entry_categories.remove(&principal_id);
+        }
+        Ok(())
+    }
+
+    pub fn has_category(&self, category: &str) -> bool {
+        self.categories.contains(category)
+    }
+
+    pub fn get_categories(&self) -> Vec<String> {
+        self.categories.iter().cloned().collect()
+    }
+
+    pub fn set_category(&mut self, principal_id: Principal, category: Option<String>) {
+        if let Some(old) = self.entry_categories.remove(&principal_id) {
+            if let Some(entries) = self.by_category.get_mut(&old) {
+                entries.remove(&principal_id);
+            }
+        }
+
+        if let Some(category) = category {
+            self.by_category
+                .entry(category.clone())
+                .or_default()
+                .insert(principal_id);
+            self.entry_categories.insert(principal_id, category);
+        }
+    }
+
+    pub fn set_tags(&mut self, principal_id: Principal, tags: Vec<String>) {
+        for old in self.entry_tags.remove(&principal_id).unwrap_or_default() {
+            if let Some(entries) = self.by_tag.get_mut(&old) {
+                entries.remove(&principal_id);
+                if entries.is_empty() {
+                    self.by_tag.remove(&old);
+                }
+            }
+        }
+
+        if tags.is_empty() {
+            return;
+        }
+
+        for tag in tags.iter() {
+            self.by_tag
+                .entry(tag.clone())
+                .or_default()
+                .insert(principal_id);
+        }
+        self.entry_tags.insert(principal_id, tags);
+    }
+
+    pub fn remove(&mut self, principal_id: &Principal) {
+        self.set_category(*principal_id, None);
+        self.set_tags(*principal_id, vec![]);
+    }
+
+    pub fn get_category(&self, principal_id: &Principal) -> Option<&String> {
+        self.entry_categories.get(principal_id)
+    }
+
+    pub fn get_tags(&self, principal_id: &Principal) -> Vec<String> {
+        self.entry_tags
+            .get(principal_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn by_category(&self, category: &str) -> Vec<Principal> {
+        self.by_category
+            .get(category)
+            .map(|entries| entries.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn by_tag(&self, tag: &str) -> Vec<Principal> {
+        self.by_tag
+            .get(tag)
+            .map(|entries| entries.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+// Tags are matched case-insensitively, so they're stored lowercased and de-duplicated
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, OperationError> {
+    let mut normalized: Vec<String> = vec![];
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || tag.len() > TAG_LIMIT {
+            return Err(OperationError::BadParameters);
+        }
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+
+    if normalized.len() > TAGS_PER_ENTRY_LIMIT {
+        return Err(OperationError::BadParameters);
+    }
+    Ok(normalized)
+}
+
+fn check_entry_access(principal_id: &Principal) -> Result<(), OperationError> {
+    let caller = ic::caller();
+    match ic::get::<Registry>().get(principal_id) {
+        None => Err(OperationError::NonExistentItem),
+        Some(nft) if nft.manager() != caller && !is_admin(&caller) => {
+            Err(OperationError::not_authorized(Role::Submitter, &caller))
+        }
+        Some(_) => ic::get::<Locks>().check(principal_id),
+    }
+}
+
+fn entries(principals: Vec<Principal>) -> Vec<&'static NftCanister> {
+    let db = ic::get::<Registry>();
+    principals
+        .iter()
+        .filter_map(|principal_id| db.get(principal_id))
+        .collect()
+}
+
+#[update]
+pub fn add_category(category: String) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if category.is_empty() || category.len() > TAG_LIMIT {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::get_mut::<Taxonomy>().add_category(category);
+    Ok(())
+}
+
+#[update]
+pub fn remove_category(category: String) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    ic::get_mut::<Taxonomy>().remove_category(&category)
+}
+
+#[query]
+pub fn get_categories() -> Vec<String> {
+    ic::get::<Taxonomy>().get_categories()
+}
+
+#[update]
+pub fn set_category(
+    principal_id: Principal,
+    category: Option<String>,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    check_entry_access(&principal_id)?;
+
+    let taxonomy = ic::get_mut::<Taxonomy>();
+    if let Some(category) = &category {
+        if !taxonomy.has_category(category) {
+            return Err(OperationError::NonExistentItem);
+        }
+    }
+
+    taxonomy.set_category(principal_id, category);
+    Ok(())
+}
+
+#[update]
+pub fn set_tags(principal_id: Principal, tags: Vec<String>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    check_entry_access(&principal_id)?;
+
+    let tags = normalize_tags(tags)?;
+    ic::get_mut::<Taxonomy>().set_tags(principal_id, tags);
+    Ok(())
+}
+
+#[query]
+pub fn get_category(principal_id: Principal) -> Option<String> {
+    ic::get::<Taxonomy>().get_category(&principal_id).cloned()
+}
+
+#[query]
+pub fn get_tags(principal_id: Principal) -> Vec<String> {
+    ic::get::<Taxonomy>().get_tags(&principal_id)
+}
+
+#[query]
+pub fn get_all_by_category(category: String) -> Vec<&'static NftCanister> {
+    entries(ic::get::<Taxonomy>().by_category(&category))
+}
+
+#[query]
+pub fn get_all_by_tag(tag: String) -> Vec<&'static NftCanister> {
+    entries(ic::get::<Taxonomy>().by_tag(&tag.to_lowercase()))
+}
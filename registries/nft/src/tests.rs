@@ -389,3 +389,99 @@
 //         assert_eq!(get_all_result.len(), 0);
 //     }
 // }
+
+// Archive/load and Candid round trips of random entries. Entries go through both on every
+// upgrade, so a change that loses or alters stored data fails here first.
+#[cfg(test)]
+mod round_trip_tests {
+    use ic_kit::candid::{Decode, Encode, Principal};
+    use proptest::collection::vec;
+    use proptest::option;
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+
+    use crate::common_types::*;
+    use crate::nft::Registry;
+
+    fn principal() -> impl Strategy<Value = Principal> {
+        vec(any::<u8>(), 0..=29).prop_map(|bytes| Principal::from_slice(&bytes))
+    }
+
+    // Floats are kept finite, NaN isn't equal to itself
+    fn detail_value() -> impl Strategy<Value = DetailValue> {
+        let leaf = prop_oneof![
+            Just(DetailValue::True),
+            Just(DetailValue::False),
+            any::<u64>().prop_map(DetailValue::U64),
+            any::<i64>().prop_map(DetailValue::I64),
+            (-1e15f64..1e15).prop_map(DetailValue::Float),
+            ".*".prop_map(DetailValue::Text),
+            principal().prop_map(DetailValue::Principal),
+            vec(any::<u8>(), 0..64).prop_map(DetailValue::Slice),
+        ];
+        leaf.prop_recursive(3, 32, 4, |inner| {
+            vec(inner, 0..4).prop_map(DetailValue::Vec)
+        })
+    }
+
+    fn nft_canister() -> impl Strategy<Value = NftCanister> {
+        (
+            (".*", ".*", ".*", option::of(".*")),
+            (principal(), principal(), principal(), any::<u64>()),
+            vec((".*", detail_value()), 0..8),
+            (option::of(principal()), option::of(principal())),
+        )
+            .prop_map(
+                |(
+                    (name, description, thumbnail, frontend),
+                    (principal_id, submitter, last_updated_by, last_updated_at),
+                    details,
+                    (resolved_from, managed_by),
+                )| NftCanister {
+                    name,
+                    description,
+                    thumbnail,
+                    frontend,
+                    principal_id,
+                    submitter,
+                    last_updated_by,
+                    last_updated_at,
+                    details,
+                    resolved_from,
+                    managed_by,
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn test_archive_load_round_trip(entries in vec(nft_canister(), 0..16)) {
+            let expected: Vec<(Principal, NftCanister)> = entries
+                .into_iter()
+                .map(|entry| (entry.principal_id, entry))
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .collect();
+
+            let mut registry = Registry::default();
+            registry.load(expected.clone());
+            prop_assert_eq!(registry.get_all().len(), expected.len());
+
+            let archive = registry.archive();
+            prop_assert!(registry.get_all().is_empty());
+            prop_assert_eq!(archive, expected);
+        }
+
+        #[test]
+        fn test_candid_round_trip(entries in vec(nft_canister(), 0..16)) {
+            let archive: Vec<(Principal, NftCanister)> = entries
+                .into_iter()
+                .map(|entry| (entry.principal_id, entry))
+                .collect();
+
+            let bytes = Encode!(&archive).unwrap();
+            let decoded = Decode!(&bytes, Vec<(Principal, NftCanister)>).unwrap();
+            prop_assert_eq!(decoded, archive);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use ic_kit::candid::{CandidType, Deserialize, Func, Nat};
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+
+use crate::common_types::*;
+
+// Enough for an HTTPS outcall on a 13 node subnet, unused cycles are refunded
+const HTTP_REQUEST_CYCLES: u64 = 200_000_000;
+const MAX_RESPONSE_BYTES: u64 = 4_096;
+
+// Optional check of the thumbnail URL of new entries. When enabled, the registry sends a HEAD
+// request through an HTTP outcall and rejects thumbnails that aren't images or are too large.
+#[derive(Default)]
+pub struct Thumbnails(pub Option<ThumbnailPolicy>);
+
+// Only the method the registry sends, the management canister also accepts get and post
+#[derive(CandidType, Deserialize)]
+enum HttpMethod {
+    #[serde(rename = "head")]
+    Head,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct HttpHeader {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(CandidType, Deserialize)]
+struct TransformContext {
+    function: Func,
+    context: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize)]
+struct CanisterHttpRequestArgument {
+    url: String,
+    max_response_bytes: Option<u64>,
+    method: HttpMethod,
+    headers: Vec<HttpHeader>,
+    body: Option<Vec<u8>>,
+    transform: Option<TransformContext>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct CanisterHttpResponse {
+    pub status: Nat,
+    pub headers: Vec<HttpHeader>,
+    pub body: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct TransformArgs {
+    pub response: CanisterHttpResponse,
+    pub context: Vec<u8>,
+}
+
+fn header<'a>(response: &'a CanisterHttpResponse, name: &str) -> Option<&'a str> {
+    response
+        .headers
+        .iter()
+        .find(|header| header.name.eq_ignore_ascii_case(name))
+        .map(|header| header.value.as_str())
+}
+
+pub async fn check_thumbnail(url: &str) -> Result<(), OperationError> {
+    let policy = match &ic::get::<Thumbnails>().0 {
+        Some(policy) => policy.clone(),
+        None => return Ok(()),
+    };
+
+    let request = CanisterHttpRequestArgument {
+        url: url.to_string(),
+        max_response_bytes: Some(MAX_RESPONSE_BYTES),
+        method: HttpMethod::Head,
+        headers: vec![],
+        body: None,
+        transform: Some(TransformContext {
+            function: Func {
+                principal: ic::id(),
+                method: String::from("transform_thumbnail_head"),
+            },
+            context: vec![],
+        }),
+    };
+
+    let response: CanisterHttpResponse = match ic::call_with_payment(
+        Principal::management_canister(),
+        "http_request",
+        (request,),
+        HTTP_REQUEST_CYCLES,
+    )
+    .await
+    {
+        Ok((x,)) => x,
+        Err((_code, msg)) => {
+            return Err(OperationError::Unknown(msg));
+        }
+    };
+
+    if response.status < Nat::from(200) || response.status >= Nat::from(300) {
+        return Err(OperationError::BadParameters);
+    }
+
+    let is_image = header(&response, "content-type")
+        .map(|content_type| content_type.trim().starts_with("image/"))
+        .unwrap_or(false);
+    // Servers that don't report a size are given the benefit of the doubt
+    let size = header(&response, "content-length").and_then(|size| size.trim().parse::<u64>().ok());
+
+    if !is_image || size.map(|size| size > policy.max_size).unwrap_or(false) {
+        return Err(OperationError::BadParameters);
+    }
+
+    Ok(())
+}
+
+// Replicas must agree on the response, so only the headers the policy reads are kept
+#[query]
+fn transform_thumbnail_head(args: TransformArgs) -> CanisterHttpResponse {
+    let headers = ["content-type", "content-length"]
+        .iter()
+        .filter_map(|name| {
+            header(&args.response, name).map(|value| HttpHeader {
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+        })
+        .collect();
+
+    CanisterHttpResponse {
+        status: args.response.status,
+        headers,
+        body: vec![],
+    }
+}
+
+// `None` disables the check
+#[update]
+pub fn set_thumbnail_policy(policy: Option<ThumbnailPolicy>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    ic::get_mut::<Thumbnails>().0 = policy;
+    Ok(())
+}
+
+#[query]
+pub fn get_thumbnail_policy() -> Option<ThumbnailPolicy> {
+    ic::get::<Thumbnails>().0.clone()
+}
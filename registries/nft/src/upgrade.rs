@@ -1,6 +1,43 @@
-use crate::common_types::NftCanister;
-use crate::management::Admins;
-use crate::nft::Registry;
+use registry_framework::alerts::AlertSink;
+use registry_framework::aliases::Aliases;
+use registry_framework::assets::{AssetRefs, Assets};
+use registry_framework::callbacks::EntryCallbacks;
+use registry_framework::curation::Curation;
+use registry_framework::delisting::Delistings;
+use registry_framework::integrity::Integrity;
+use registry_framework::locks::Locks;
+use registry_framework::logger::Logger;
+use registry_framework::maintenance::{CyclesThreshold, Maintenance};
+use registry_framework::management::{Admins, PendingController};
+use registry_framework::messages::Messages;
+use registry_framework::metrics::Metrics;
+use registry_framework::mirror::Mirror;
+use registry_framework::pinning::Pins;
+use registry_framework::reports::Reports;
+use registry_framework::requests::Requests;
+use registry_framework::reserved::ReservedNames;
+use registry_framework::retries::Retries;
+use registry_framework::sponsors::Sponsors;
+use registry_framework::subscribers::Subscribers;
+use registry_framework::translations::Translations;
+use registry_framework::usage::Usage;
+
+use crate::cap::Cap;
+use crate::common_types::*;
+use crate::factory::Factory;
+use crate::history::History;
+use crate::index::{FrontendIndex, ManagerIndex, PrincipalIndex, SortIndex};
+use crate::metadata::DetailSchema;
+use crate::migrations::{migrate, SCHEMA_VERSION};
+use crate::namespaces::Namespaces;
+use crate::nft::{ConflictRouter, Registry, ResponseBudget};
+use crate::protection::Protection;
+use crate::reviewers::Reviewers;
+use crate::stats::Stats;
+use crate::submissions::{DepositConfig, Expired, SubmissionTtl, Submissions};
+use crate::taxonomy::Taxonomy;
+use crate::thumbnails::Thumbnails;
+use crate::validation::ExternalValidator;
 
 use ic_kit::candid::{CandidType, Deserialize, Principal};
 use ic_kit::ic::*;
@@ -8,9 +45,58 @@ use ic_kit::macros::*;
 use ic_kit::*;
 
 #[derive(CandidType, Deserialize)]
-struct StableStorage {
-    db: Vec<(Principal, NftCanister)>,
-    admins: Vec<Principal>,
+pub struct StableStorage {
+    pub(crate) db: Vec<(Principal, NftCanister)>,
+    pub(crate) admins: Vec<Principal>,
+    pub(crate) conflict_router: Option<Principal>,
+    pub(crate) history: Option<Vec<Event>>,
+    pub(crate) submissions: Option<Vec<(Principal, Submission)>>,
+    pub(crate) deposit: Option<Deposit>,
+    pub(crate) added_at: Option<Vec<(Principal, u64)>>,
+    pub(crate) aliases: Option<Vec<(Principal, Principal)>>,
+    pub(crate) translations: Option<Vec<(Principal, Vec<(String, LocalizedText)>)>>,
+    pub(crate) curation: Option<CurationArchive>,
+    pub(crate) subscribers: Option<SubscribersArchive>,
+    pub(crate) reserved_names: Option<Vec<ReservedName>>,
+    pub(crate) sponsors: Option<Vec<Sponsor>>,
+    pub(crate) locks: Option<Vec<Principal>>,
+    pub(crate) mirror: Option<MirrorStatus>,
+    pub(crate) required_details: Option<Vec<DetailKeySpec>>,
+    pub(crate) requests: Option<Vec<ProcessedRequest>>,
+    pub(crate) alert_sink: Option<(Principal, String)>,
+    pub(crate) thumbnail_policy: Option<ThumbnailPolicy>,
+    pub(crate) taxonomy: Option<TaxonomyArchive>,
+    pub(crate) collection_stats: Option<Vec<(Principal, CollectionStats)>>,
+    pub(crate) maintenance: Option<bool>,
+    pub(crate) cap_root: Option<Principal>,
+    pub(crate) detail_limits: Option<DetailLimits>,
+    pub(crate) slice_limits: Option<SliceLimits>,
+    pub(crate) entry_limits: Option<EntryLimits>,
+    pub(crate) submission_ttl: Option<u64>,
+    pub(crate) expired_submissions: Option<Vec<ExpiredSubmission>>,
+    pub(crate) usage_tracking: Option<bool>,
+    pub(crate) cycles_threshold: Option<u64>,
+    pub(crate) external_validator: Option<Principal>,
+    pub(crate) controller_proposal: Option<ControllerProposal>,
+    pub(crate) protection: Option<ProtectionArchive>,
+    pub(crate) response_budget: Option<u64>,
+    pub(crate) namespaces: Option<Vec<NamespaceArchive>>,
+    pub(crate) reports: Option<ReportsArchive>,
+    pub(crate) delistings: Option<Vec<Delisting>>,
+    pub(crate) log_sink: Option<(Principal, String)>,
+    pub(crate) logs: Option<Vec<LogRecord>>,
+    pub(crate) retries: Option<RetriesArchive>,
+    pub(crate) pins: Option<Vec<Principal>>,
+    pub(crate) reviewers: Option<Vec<(String, Vec<Principal>)>>,
+    pub(crate) assets: Option<Vec<(Vec<u8>, Vec<u8>)>>,
+    pub(crate) metrics: Option<Vec<(String, u64)>>,
+    pub(crate) messages: Option<MessagesArchive>,
+    pub(crate) registry_wasm: Option<Vec<u8>>,
+    pub(crate) child_registries: Option<Vec<ChildRegistry>>,
+    pub(crate) entry_callbacks: Option<Vec<(Principal, Principal, String)>>,
+    pub(crate) asset_uploads: Option<Vec<(Vec<u8>, u64)>>,
+    pub(crate) asset_uploaders: Option<Vec<(Vec<u8>, Principal)>>,
+    pub(crate) schema_version: Option<u32>,
 }
 
 #[pre_upgrade]
@@ -18,7 +104,108 @@ pub fn pre_upgrade() {
     let db = ic::get_mut::<Registry>().archive();
     let admins = ic::get_mut::<Admins>().0.clone();
 
-    let stable = StableStorage { db, admins };
+    let conflict_router = ic::get::<ConflictRouter>().0;
+    let history = Some(ic::get_mut::<History>().archive());
+    let submissions = Some(ic::get_mut::<Submissions>().archive());
+    let deposit = ic::get::<DepositConfig>().0.clone();
+    let added_at = Some(ic::get::<SortIndex>().archive());
+    let aliases = Some(ic::get_mut::<Aliases>().archive());
+    let translations = Some(ic::get_mut::<Translations>().archive());
+    let curation = Some(ic::get_mut::<Curation>().archive());
+    let subscribers = Some(ic::get_mut::<Subscribers>().archive());
+    let reserved_names = Some(ic::get::<ReservedNames>().0.clone());
+    let sponsors = Some(ic::get_mut::<Sponsors>().archive());
+    let locks = Some(ic::get::<Locks>().0.iter().cloned().collect());
+    let mirror = Some(ic::get::<Mirror>().status.clone());
+    let required_details = Some(ic::get::<DetailSchema>().0.clone());
+    let requests = Some(ic::get_mut::<Requests>().archive());
+    let alert_sink = ic::get::<AlertSink>().sink.clone();
+    let thumbnail_policy = ic::get::<Thumbnails>().0.clone();
+    let taxonomy = Some(ic::get_mut::<Taxonomy>().archive());
+    let collection_stats = Some(ic::get_mut::<Stats>().archive());
+    let maintenance = Some(ic::get::<Maintenance>().0);
+    let cap_root = ic::get::<Cap>().root;
+    let detail_limits = Some(*ic::get::<DetailLimits>());
+    let slice_limits = Some(*ic::get::<SliceLimits>());
+    let entry_limits = Some(*ic::get::<EntryLimits>());
+    let submission_ttl = Some(ic::get::<SubmissionTtl>().0);
+    let expired_submissions = Some(ic::get_mut::<Expired>().archive());
+    let usage_tracking = Some(ic::get::<Usage>().enabled);
+    let cycles_threshold = Some(ic::get::<CyclesThreshold>().0);
+    let external_validator = ic::get::<ExternalValidator>().0;
+    let controller_proposal = ic::get::<PendingController>().0.clone();
+    let protection = Some(ic::get_mut::<Protection>().archive());
+    let response_budget = Some(ic::get::<ResponseBudget>().0);
+    let namespaces = Some(ic::get_mut::<Namespaces>().archive());
+    let reports = Some(ic::get_mut::<Reports>().archive());
+    let delistings = Some(ic::get::<Delistings>().0.clone());
+    let log_sink = ic::get::<Logger>().sink.clone();
+    let logs = Some(ic::get::<Logger>().archive());
+    let retries = Some(ic::get_mut::<Retries>().archive());
+    let pins = Some(ic::get::<Pins>().0.clone());
+    let reviewers = Some(ic::get::<Reviewers>().0.clone().into_iter().collect());
+    let assets = Some(ic::get_mut::<Assets>().archive());
+    let asset_uploads = Some(ic::get_mut::<Assets>().archive_uploads());
+    let asset_uploaders = Some(ic::get_mut::<Assets>().archive_uploaders());
+    let metrics = Some(ic::get::<Metrics>().archive());
+    let messages = Some(ic::get_mut::<Messages>().archive());
+    let registry_wasm = ic::get_mut::<Factory>().wasm.take();
+    let child_registries = Some(ic::get::<Factory>().children.clone());
+    let entry_callbacks = Some(ic::get::<EntryCallbacks>().archive());
+
+    let stable = StableStorage {
+        schema_version: Some(SCHEMA_VERSION),
+        db,
+        admins,
+        conflict_router,
+        history,
+        submissions,
+        deposit,
+        added_at,
+        aliases,
+        translations,
+        curation,
+        subscribers,
+        reserved_names,
+        sponsors,
+        locks,
+        mirror,
+        required_details,
+        requests,
+        alert_sink,
+        thumbnail_policy,
+        taxonomy,
+        collection_stats,
+        maintenance,
+        cap_root,
+        detail_limits,
+        slice_limits,
+        entry_limits,
+        submission_ttl,
+        expired_submissions,
+        usage_tracking,
+        cycles_threshold,
+        external_validator,
+        controller_proposal,
+        protection,
+        response_budget,
+        namespaces,
+        reports,
+        delistings,
+        log_sink,
+        logs,
+        retries,
+        pins,
+        reviewers,
+        assets,
+        metrics,
+        messages,
+        registry_wasm,
+        child_registries,
+        entry_callbacks,
+        asset_uploads,
+        asset_uploaders,
+    };
 
     match ic::stable_store((stable,)) {
         Ok(_) => (),
@@ -33,8 +220,93 @@ pub fn pre_upgrade() {
 
 #[post_upgrade]
 pub fn post_upgrade() {
-    if let Ok((stable,)) = ic::stable_restore::<(StableStorage,)>() {
-        ic::get_mut::<Registry>().load(stable.db);
-        ic::store(Admins(stable.admins));
+    // Failing the upgrade keeps the previous state, an empty registry would have no admins
+    let (mut stable,) = ic::stable_restore::<(StableStorage,)>().expect("decoding stable storage");
+    migrate(&mut stable);
+
+    ic::get_mut::<Registry>().load(stable.db);
+    ic::get_mut::<SortIndex>().load(
+        stable.added_at.unwrap_or_default(),
+        ic::get::<Registry>().get_all(),
+    );
+    ic::get_mut::<FrontendIndex>().load(ic::get::<Registry>().get_all());
+    ic::get_mut::<ManagerIndex>().load(ic::get::<Registry>().get_all());
+    ic::get_mut::<PrincipalIndex>().load(ic::get::<Registry>().get_all());
+    ic::get_mut::<Integrity>().load(ic::get::<Registry>().get_all());
+    let refs = ic::get_mut::<AssetRefs>();
+    for entry in ic::get::<Registry>().get_all() {
+        refs.track(entry.principal_id, &entry.details);
+    }
+    ic::store(Admins(stable.admins));
+    ic::store(ConflictRouter(stable.conflict_router));
+    ic::get_mut::<History>().load(stable.history.unwrap_or_default());
+    ic::get_mut::<Aliases>().load(stable.aliases.unwrap_or_default());
+    ic::get_mut::<Translations>().load(stable.translations.unwrap_or_default());
+    ic::get_mut::<Curation>().load(stable.curation.unwrap_or_default());
+    ic::get_mut::<Subscribers>().load(stable.subscribers.unwrap_or_default());
+    ic::store(ReservedNames(stable.reserved_names.unwrap_or_default()));
+    ic::get_mut::<Sponsors>().load(stable.sponsors.unwrap_or_default());
+    ic::store(Locks(
+        stable.locks.unwrap_or_default().into_iter().collect(),
+    ));
+    ic::get_mut::<Mirror>().status = stable.mirror.unwrap_or_default();
+    if let Some(required_details) = stable.required_details {
+        ic::store(DetailSchema(required_details));
+    }
+    ic::get_mut::<Requests>().load(stable.requests.unwrap_or_default());
+    ic::get_mut::<AlertSink>().sink = stable.alert_sink;
+    ic::store(Thumbnails(stable.thumbnail_policy));
+    ic::get_mut::<Taxonomy>().load(stable.taxonomy.unwrap_or_default());
+    ic::get_mut::<Stats>().load(stable.collection_stats.unwrap_or_default());
+    ic::get_mut::<Submissions>().load(stable.submissions.unwrap_or_default());
+    ic::store(DepositConfig(stable.deposit));
+    ic::store(Maintenance(stable.maintenance.unwrap_or_default()));
+    ic::get_mut::<Cap>().root = stable.cap_root;
+    ic::store(stable.detail_limits.unwrap_or_default());
+    ic::store(stable.slice_limits.unwrap_or_default());
+    ic::store(stable.entry_limits.unwrap_or_default());
+    ic::store(
+        stable
+            .submission_ttl
+            .map_or_else(SubmissionTtl::default, SubmissionTtl),
+    );
+    ic::get_mut::<Expired>().load(stable.expired_submissions.unwrap_or_default());
+    ic::get_mut::<Usage>().enabled = stable.usage_tracking.unwrap_or_default();
+    ic::store(
+        stable
+            .cycles_threshold
+            .map_or_else(CyclesThreshold::default, CyclesThreshold),
+    );
+    ic::store(ExternalValidator(stable.external_validator));
+    ic::store(PendingController(stable.controller_proposal));
+    ic::get_mut::<Protection>().load(stable.protection.unwrap_or_default());
+    ic::store(
+        stable
+            .response_budget
+            .map_or_else(ResponseBudget::default, ResponseBudget),
+    );
+    ic::get_mut::<Namespaces>().load(stable.namespaces.unwrap_or_default());
+    if let Some(reports) = stable.reports {
+        ic::get_mut::<Reports>().load(reports);
     }
+    ic::store(Delistings(stable.delistings.unwrap_or_default()));
+    ic::get_mut::<Logger>().sink = stable.log_sink;
+    ic::get_mut::<Logger>().load(stable.logs.unwrap_or_default());
+    ic::get_mut::<Retries>().load(stable.retries.unwrap_or_default());
+    ic::store(Pins(stable.pins.unwrap_or_default()));
+    ic::store(Reviewers(
+        stable.reviewers.unwrap_or_default().into_iter().collect(),
+    ));
+    ic::get_mut::<Assets>().load(
+        stable.assets.unwrap_or_default(),
+        stable.asset_uploads.unwrap_or_default(),
+        stable.asset_uploaders.unwrap_or_default(),
+    );
+    ic::get_mut::<Metrics>().load(stable.metrics.unwrap_or_default());
+    ic::get_mut::<Messages>().load(stable.messages.unwrap_or_default());
+    ic::store(Factory {
+        wasm: stable.registry_wasm,
+        children: stable.child_registries.unwrap_or_default(),
+    });
+    ic::get_mut::<EntryCallbacks>().load(stable.entry_callbacks.unwrap_or_default());
 }
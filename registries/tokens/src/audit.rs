@@ -0,0 +1,119 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::guard::check_caller;
+use registry_framework::management::*;
+
+use crate::common_types::*;
+use crate::rules::standard_key;
+use crate::tokens::TokenRegistry;
+
+#[derive(Default)]
+pub struct LastAudit(pub Option<AuditReport>);
+
+// Queries the ledger's symbol and decimals using the methods of its standard
+async fn ledger_metadata(
+    principal_id: Principal,
+    standard: &str,
+) -> Result<(String, u8), AuditFinding> {
+    let (symbol_method, decimals_method) = match standard_key(standard).as_str() {
+        "DIP20" => ("symbol", "decimals"),
+        "ICRC1" => ("icrc1_symbol", "icrc1_decimals"),
+        _ => {
+            return Err(AuditFinding::UnsupportedStandard(standard.to_string()));
+        }
+    };
+
+    let (symbol,): (String,) = ic::call(principal_id, symbol_method, ())
+        .await
+        .map_err(|(_code, msg)| AuditFinding::Unreachable(msg))?;
+    let (decimals,): (u8,) = ic::call(principal_id, decimals_method, ())
+        .await
+        .map_err(|(_code, msg)| AuditFinding::Unreachable(msg))?;
+
+    Ok((symbol, decimals))
+}
+
+// Compares the symbol and decimals in `details` with the ones reported by the ledger
+pub async fn check_ledger(
+    principal_id: Principal,
+    details: &[(String, DetailValue)],
+) -> Vec<AuditFinding> {
+    let standard = match detail(details, "standard").and_then(DetailValue::as_text) {
+        Some(standard) => standard.to_string(),
+        None => return vec![AuditFinding::MissingDetail(String::from("standard"))],
+    };
+
+    let (symbol, decimals) = match ledger_metadata(principal_id, &standard).await {
+        Ok(metadata) => metadata,
+        Err(finding) => return vec![finding],
+    };
+
+    let mut findings = vec![];
+
+    match detail(details, "symbol").and_then(DetailValue::as_text) {
+        Some(registry) if registry != symbol => findings.push(AuditFinding::SymbolMismatch {
+            registry: registry.to_string(),
+            ledger: symbol,
+        }),
+        Some(_) => {}
+        None => findings.push(AuditFinding::MissingDetail(String::from("symbol"))),
+    }
+
+    match detail(details, "decimals").and_then(DetailValue::as_u64) {
+        Some(registry) if registry != decimals as u64 => {
+            findings.push(AuditFinding::DecimalsMismatch {
+                registry,
+                ledger: decimals as u64,
+            })
+        }
+        Some(_) => {}
+        None => findings.push(AuditFinding::MissingDetail(String::from("decimals"))),
+    }
+
+    findings
+}
+
+// Compares every registered token against its ledger and stores the report
+#[update]
+pub async fn audit_entries() -> Result<AuditReport, OperationError> {
+    check_caller()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    let started_at = ic::time();
+    let tokens: Vec<Token> = ic::get::<TokenRegistry>()
+        .get_all()
+        .into_iter()
+        .cloned()
+        .collect();
+
+    let mut entries = vec![];
+    for token in tokens.iter() {
+        let findings = check_ledger(token.principal_id, &token.details).await;
+        if !findings.is_empty() {
+            entries.push(AuditEntry {
+                principal_id: token.principal_id,
+                name: token.name.clone(),
+                findings,
+            });
+        }
+    }
+
+    let report = AuditReport {
+        started_at,
+        finished_at: ic::time(),
+        audited: tokens.len() as u64,
+        entries,
+    };
+
+    ic::get_mut::<LastAudit>().0 = Some(report.clone());
+    Ok(report)
+}
+
+#[query]
+pub fn get_audit_report() -> Option<&'static AuditReport> {
+    ic::get::<LastAudit>().0.as_ref()
+}
@@ -0,0 +1,121 @@
+use ic_kit::candid::{CandidType, Deserialize, Nat, Principal};
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::retries::Retries;
+use std::str::FromStr;
+
+use crate::common_types::*;
+
+// Cycles moved to the root bucket when the registry creates it
+const BUCKET_CREATION_CYCLES: u64 = 1_000_000_000_000;
+
+#[derive(CandidType)]
+struct GetTokenContractRootBucketArg {
+    canister: Principal,
+    witness: bool,
+}
+
+// The router also answers with a witness, unused as none is requested
+#[derive(CandidType, Deserialize)]
+struct GetTokenContractRootBucketResponse {
+    canister: Option<Principal>,
+}
+
+#[derive(CandidType)]
+pub(crate) struct CanisterSettings {
+    pub(crate) controllers: Option<Vec<Principal>>,
+    pub(crate) compute_allocation: Option<Nat>,
+    pub(crate) memory_allocation: Option<Nat>,
+    pub(crate) freezing_threshold: Option<Nat>,
+}
+
+#[derive(CandidType)]
+pub(crate) struct CreateCanisterArgument {
+    pub(crate) settings: Option<CanisterSettings>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub(crate) struct CreateCanisterResult {
+    pub(crate) canister_id: Principal,
+}
+
+// Root bucket of the registry's history on CAP. It's looked up, or created, on the
+// first write. A failed handshake is retried by the `Retries` queue, and on later writes.
+#[derive(Default)]
+pub struct Cap {
+    pub root: Option<Principal>,
+    pub handshaking: bool,
+}
+
+impl Cap {
+    pub fn ensure_root(&mut self) {
+        if self.root.is_some() || self.handshaking {
+            return;
+        }
+
+        self.handshaking = true;
+        ic_cdk::spawn(handshake(None));
+    }
+}
+
+// `task` is the retry being run, if any
+pub async fn handshake(task: Option<RetryTask>) {
+    let result = find_or_create_root().await;
+
+    let cap = ic::get_mut::<Cap>();
+    cap.handshaking = false;
+    match (result, task) {
+        (Ok(root), _) => cap.root = Some(root),
+        (Err(msg), Some(task)) => ic::get_mut::<Retries>().reschedule(task, msg),
+        (Err(msg), None) => ic::get_mut::<Retries>().schedule(TaskKind::CapHandshake, msg),
+    }
+}
+
+async fn find_or_create_root() -> Result<Principal, String> {
+    let router = Principal::from_str(CAP_ROUTER_ID).unwrap();
+    let arg = GetTokenContractRootBucketArg {
+        canister: ic::id(),
+        witness: false,
+    };
+
+    let response: GetTokenContractRootBucketResponse =
+        match ic::call(router, "get_token_contract_root_bucket", (arg,)).await {
+            Ok((response,)) => response,
+            Err((_code, msg)) => return Err(msg),
+        };
+
+    if let Some(root) = response.canister {
+        return Ok(root);
+    }
+
+    // The router installs the bucket code, so it has to control the new canister
+    let arg = CreateCanisterArgument {
+        settings: Some(CanisterSettings {
+            controllers: Some(vec![router, ic::id()]),
+            compute_allocation: None,
+            memory_allocation: None,
+            freezing_threshold: None,
+        }),
+    };
+    let root = match ic::call_with_payment(
+        Principal::management_canister(),
+        "create_canister",
+        (arg,),
+        BUCKET_CREATION_CYCLES,
+    )
+    .await
+    {
+        Ok((CreateCanisterResult { canister_id },)) => canister_id,
+        Err((_code, msg)) => return Err(msg),
+    };
+
+    match ic::call(router, "install_bucket_code", (root,)).await {
+        Ok(()) => Ok(root),
+        Err((_code, msg)) => Err(msg),
+    }
+}
+
+#[query]
+pub fn get_cap_root() -> Option<Principal> {
+    ic::get::<Cap>().root
+}
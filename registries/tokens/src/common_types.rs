@@ -1,18 +1,100 @@
-use ic_kit::{candid::CandidType, Principal};
-use serde::{Deserialize, Serialize};
-
-#[derive(CandidType, Serialize, Deserialize, Clone, PartialEq, Debug)]
-pub enum DetailValue {
-    True,
-    False,
-    U64(u64),
-    I64(i64),
-    Float(f64),
-    Text(String),
-    Principal(Principal),
-    #[serde(with = "serde_bytes")]
-    Slice(Vec<u8>),
-    Vec(Vec<DetailValue>),
+use ic_kit::candid::{CandidType, Func};
+use ic_kit::Principal;
+use registry_framework::entries::ListedEntry;
+use serde::Deserialize;
+
+// Shared with the other registries, see `get_v2`
+pub use registry_framework::{detail, DetailValue, Envelope};
+// Types of the stores shared with the other registries
+pub use registry_framework::types::*;
+
+// Type a detail value must have, `Any` accepts every value
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DetailType {
+    Any,
+    Bool,
+    U64,
+    I64,
+    Float,
+    Text,
+    Principal,
+    Slice,
+    Vec,
+}
+
+impl DetailType {
+    pub fn matches(&self, value: &DetailValue) -> bool {
+        match (self, value) {
+            (DetailType::Any, _) => true,
+            (DetailType::Bool, DetailValue::True | DetailValue::False) => true,
+            (DetailType::U64, DetailValue::U64(_)) => true,
+            (DetailType::I64, DetailValue::I64(_)) => true,
+            (DetailType::Float, DetailValue::Float(_)) => true,
+            (DetailType::Text, DetailValue::Text(_)) => true,
+            (DetailType::Principal, DetailValue::Principal(_)) => true,
+            (DetailType::Slice, DetailValue::Slice(_)) => true,
+            (DetailType::Vec, DetailValue::Vec(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+// Bounds on the details of an entry, `max_size` is the size of the candid encoded details
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DetailLimits {
+    pub max_depth: u32,
+    pub max_size: u64,
+}
+
+impl Default for DetailLimits {
+    fn default() -> Self {
+        DetailLimits {
+            max_depth: 4,
+            max_size: 16 * 1024,
+        }
+    }
+}
+
+// Bounds on a whole entry, checked on every write so edits can't keep growing it.
+// `max_size` is the size of the candid encoded entry, `max_details` the number of details.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct EntryLimits {
+    pub max_size: u64,
+    pub max_details: u64,
+}
+
+impl Default for EntryLimits {
+    fn default() -> Self {
+        EntryLimits {
+            max_size: 32 * 1024,
+            max_details: 64,
+        }
+    }
+}
+
+// Bounds on the `Slice` details of an entry. When `asset_threshold` is set, slices longer
+// than it have to be uploaded to the asset store and referenced by their hash instead.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SliceLimits {
+    pub max_size: u64,
+    pub asset_threshold: Option<u64>,
+}
+
+impl Default for SliceLimits {
+    fn default() -> Self {
+        SliceLimits {
+            max_size: 4 * 1024,
+            asset_threshold: None,
+        }
+    }
+}
+
+// A detail key entries may carry. Keys outside of the schema are rejected.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct DetailKeySpec {
+    pub key: String,
+    pub value_type: DetailType,
+    pub required: bool,
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
@@ -36,14 +118,359 @@ pub struct Token {
     pub last_updated_by: Principal,
     pub last_updated_at: u64,
     pub details: Vec<(String, DetailValue)>,
+    // Set when the entry was reached through an alias, to the principal that was requested
+    pub resolved_from: Option<Principal>,
+    // Principal the submitter handed the entry over to, `None` while the submitter manages it
+    pub managed_by: Option<Principal>,
+    // Set in `get` responses, true when a verified entry added earlier uses the same symbol
+    pub conflicted: Option<bool>,
+}
+
+impl Token {
+    pub fn manager(&self) -> Principal {
+        self.managed_by.unwrap_or(self.submitter)
+    }
+}
+
+impl ListedEntry for Token {
+    fn principal_id(&self) -> Principal {
+        self.principal_id
+    }
+
+    fn manager(&self) -> Principal {
+        Token::manager(self)
+    }
+
+    fn localize(&mut self, text: &LocalizedText) {
+        self.name = text.name.clone();
+        self.description = text.description.clone();
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Submission {
+    pub submitter: Principal,
+    pub submitted_at: u64,
+    pub deposit: Option<Deposit>,
+    pub entry: AddTokenInput,
+}
+
+// Changes to protected entries, see `registry_framework::protection`
+pub type ProposedChange = registry_framework::types::ProposedChange<AddTokenInput>;
+pub type ChangeProposal = registry_framework::types::ChangeProposal<AddTokenInput>;
+pub type ProtectionArchive = registry_framework::types::ProtectionArchive<AddTokenInput>;
+
+// `refund_error` is set when the deposit couldn't be refunded, it stays on the deposit account
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExpiredSubmission {
+    pub submission: Submission,
+    pub expired_at: u64,
+    pub refund_error: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum AuditFinding {
+    SymbolMismatch { registry: String, ledger: String },
+    DecimalsMismatch { registry: u64, ledger: u64 },
+    MissingDetail(String),
+    UnsupportedStandard(String),
+    Unreachable(String),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct AuditEntry {
+    pub principal_id: Principal,
+    pub name: String,
+    pub findings: Vec<AuditFinding>,
+}
+
+// Approves, as soon as they arrive, the submissions of `standard` made by one of
+// `submitters`. With `verify_ledger` the ledger must also report the submitted symbol
+// and decimals.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ApprovalRule {
+    pub standard: String,
+    pub submitters: Vec<Principal>,
+    pub verify_ledger: bool,
+}
+
+// Only tokens with at least one finding are listed in `entries`
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct AuditReport {
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub audited: u64,
+    pub entries: Vec<AuditEntry>,
+}
+
+// A divergence `verify_integrity` can't repair from the entries alone
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum IntegrityIssue {
+    // The entry was last updated after the current time
+    FutureTimestamp {
+        principal_id: Principal,
+        last_updated_at: u64,
+    },
+    // The entry was added after its last update
+    AddedAfterUpdate {
+        principal_id: Principal,
+        added_at: u64,
+        last_updated_at: u64,
+    },
+    // The event at `position` in the log doesn't have the id `position + 1`
+    EventIdMismatch {
+        position: u64,
+        id: u64,
+    },
+}
+
+// Outcome of `verify_integrity`. `repaired` names the indexes rebuilt from the entries.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct IntegrityReport {
+    pub checked_at: u64,
+    pub entries: u64,
+    pub events: u64,
+    pub repaired: Vec<String>,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SortBy {
+    Name,
+    AddedAt,
+    LastModified,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ListOptions {
+    pub sort_by: SortBy,
+    pub direction: SortDirection,
+    pub offset: u64,
+    pub limit: u16,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ListPage {
+    pub entries: Vec<Token>,
+    pub total: u64,
 }
 
-#[derive(CandidType, Debug, Deserialize)]
-pub enum OperationError {
-    NotAuthorized,
-    NonExistentItem,
-    BadParameters,
-    Unknown(String),
+// An entry cut to the fields a client asked for, see `get_all_projected`. Fields the entry
+// doesn't have are left out.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PartialEntry {
+    pub principal_id: Principal,
+    pub fields: Vec<(String, DetailValue)>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PartialListPage {
+    pub entries: Vec<PartialEntry>,
+    pub total: u64,
+}
+
+// Entries that fit in the response budget, `truncated` when some were left out
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct GetAllResponse {
+    pub entries: Vec<Token>,
+    pub truncated: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    #[serde(with = "serde_bytes")]
+    pub body: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    #[serde(with = "serde_bytes")]
+    pub body: Vec<u8>,
+    pub streaming_strategy: Option<StreamingStrategy>,
+}
+
+// Identifies the next chunk of a streamed response: the exported path and
+// the position of the first row the chunk starts at.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct StreamingCallbackToken {
+    pub key: String,
+    pub index: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum StreamingStrategy {
+    Callback {
+        callback: Func,
+        token: StreamingCallbackToken,
+    },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StreamingCallbackHttpResponse {
+    #[serde(with = "serde_bytes")]
+    pub body: Vec<u8>,
+    pub token: Option<StreamingCallbackToken>,
+}
+
+// See `registry_framework::types::Event`
+pub type Event = registry_framework::types::Event<Token>;
+
+// Oracle canister quoting the price of a token, and the pair to ask it for
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PriceSource {
+    pub oracle: Principal,
+    pub pair: String,
+}
+
+// Symbol, decimals and fee of a token. `formatted_fee` is the fee in whole tokens
+// followed by the symbol, e.g. "0.0001 ICP", left out when the decimals are over 255.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct DisplayInfo {
+    pub symbol: String,
+    pub decimals: u64,
+    pub fee: Option<u64>,
+    pub formatted_fee: Option<String>,
+}
+
+// Set when the token deviates from a plain transfer: part of the amount is taken as a fee
+// on every transfer, balances change without transfers, or the issuer can freeze transfers
+// globally or per account.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub struct RiskFlags {
+    pub fee_on_transfer: bool,
+    pub rebasing: bool,
+    pub pausable: bool,
+    pub blacklistable: bool,
+}
+
+// Links of the project behind an entry, from its details. `frontends` starts with the
+// `frontend` of the entry when it has one.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ProjectLinks {
+    pub website: Option<String>,
+    pub docs: Option<String>,
+    pub twitter: Option<String>,
+    pub discord: Option<String>,
+    pub github: Option<String>,
+    pub frontends: Vec<String>,
+}
+
+// Comparison a `Filter` applies to the detail under its key. `Contains` looks for a
+// case-insensitive substring in text details and for an element in vector details,
+// the orderings only compare numbers of the same type.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum FilterOp {
+    Equals,
+    Contains,
+    GreaterThan,
+    LessThan,
+}
+
+// Predicate over one detail of an entry. Entries without the detail never match.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Filter {
+    pub key: String,
+    pub op: FilterOp,
+    pub value: DetailValue,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Page {
+    pub offset: u64,
+    pub limit: u64,
+}
+
+// Summary of the registry build and its contents
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RegistryInfo {
+    pub name: String,
+    pub version: String,
+    pub entry_count: u64,
+    pub supported_standards: Vec<String>,
+    pub admins: Vec<Principal>,
+    pub last_modified_at: u64,
+}
+
+// Settings of a registry created by `create_child_registry`
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ChildRegistryConfig {
+    pub name: String,
+    // Cycles moved from this registry to the new canister
+    pub cycles: u64,
+    // Admins and controllers of the new registry, besides this canister
+    pub admins: Vec<Principal>,
+    pub controllers: Vec<Principal>,
+    // Entries of this registry copied to the new one
+    pub entries: Vec<Principal>,
+    // Installed instead of the Wasm set with `set_registry_wasm`
+    pub wasm: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChildRegistry {
+    pub canister_id: Principal,
+    pub name: String,
+    pub created_by: Principal,
+    pub created_at: u64,
+    pub entries: u64,
+}
+
+// Settings admins can change without an upgrade
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RegistryConfig {
+    pub maintenance: bool,
+    pub mirror: MirrorStatus,
+    pub conflict_router: Option<Principal>,
+    pub alert_sink: Option<(Principal, String)>,
+    pub log_sink: Option<(Principal, String)>,
+    pub deposit: Option<Deposit>,
+    pub vote_threshold: Option<u32>,
+    pub required_details: Vec<DetailKeySpec>,
+    pub detail_limits: DetailLimits,
+    pub submission_ttl: u64,
+    pub cycles_threshold: u64,
+    pub external_validator: Option<Principal>,
+    pub response_budget: u64,
+    pub approval_rules: Vec<ApprovalRule>,
+    pub report_threshold: u32,
+}
+
+// `pending_notifications` are the deliveries to subscribers that failed and wait for a retry,
+// `pending_refunds` the deposit refunds the ledger rejected
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Dashboard {
+    pub info: RegistryInfo,
+    pub pending_submissions: u64,
+    pub recent_events: Vec<Event>,
+    pub pending_notifications: Vec<Delivery>,
+    pub pending_refunds: Vec<RetryTask>,
+    pub cap_root: Option<Principal>,
+    pub config: RegistryConfig,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct SymbolCollision {
+    pub symbol: String,
+    pub principals: Vec<Principal>,
+}
+
+// A registry reported by the router as already listing a principal
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub registry: Principal,
+    pub registry_name: String,
+    pub entry_name: String,
 }
 
 #[derive(Deserialize, CandidType)]
@@ -53,3 +480,41 @@ pub enum RegistryResponse {
 }
 
 pub const CANISTER_REGISTRY_ID: &'static str = "curr3-vaaaa-aaaah-abbdq-cai";
+pub const CAP_ROUTER_ID: &'static str = "lj532-6iaaa-aaaah-qcc7a-cai";
+pub const SUPPORTED_STANDARDS: [&str; 3] = ["DIP20", "ICRC1", "EXT"];
+// Optional capabilities of this build, see `supported_features`
+pub const SUPPORTED_FEATURES: [&str; 20] = [
+    "pagination",
+    "cap",
+    "certification",
+    "submissions",
+    "subscriptions",
+    "history",
+    "filters",
+    "translations",
+    "principal_search",
+    "protection",
+    "price_feeds",
+    "symbol_collisions",
+    "origin_chain",
+    "reports",
+    "featured",
+    "review_queues",
+    "assets",
+    "metrics",
+    "blocklist",
+    "child_registries",
+];
+
+pub const FILTERS_LIMIT: usize = 16;
+pub const FILTER_RESULTS_LIMIT: usize = 500;
+pub const SEARCH_LIMIT: usize = 20;
+pub const FRONTENDS_LIMIT: usize = 5;
+// Ledgers report their decimals as a nat8
+pub const DECIMALS_LIMIT: u64 = 255;
+// Canisters accepted in one `add_from_manifest` call
+pub const MANIFEST_ENTRIES_LIMIT: usize = 50;
+// Shape of the entries in the envelopes of `get_v2` and `list_v2`, bumped on every change
+pub const ENTRY_SCHEMA_VERSION: u16 = 1;
+// Encoded bytes `get_all_v2` returns by default, replies are capped at 2MiB
+pub const DEFAULT_RESPONSE_BUDGET: u64 = 1_500_000;
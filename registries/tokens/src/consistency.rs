@@ -0,0 +1,139 @@
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::assets::AssetRefs;
+use registry_framework::guard::check_caller;
+use registry_framework::integrity::Integrity;
+use registry_framework::logger::log;
+use registry_framework::management::*;
+
+use crate::common_types::*;
+use crate::history::History;
+use crate::index::*;
+use crate::tokens::TokenRegistry;
+
+// Names of the indexes rebuilt from the entries
+#[derive(Default)]
+struct Repairs(Vec<String>);
+
+impl Repairs {
+    // Replaces `index` by `rebuilt` when they differ
+    fn reconcile<T: PartialEq>(&mut self, name: &str, index: &mut T, rebuilt: T) {
+        if *index != rebuilt {
+            *index = rebuilt;
+            self.0.push(String::from(name));
+        }
+    }
+}
+
+fn timestamp_issues(entries: &[&Token]) -> Vec<IntegrityIssue> {
+    let now = ic::time();
+    let sort_index = ic::get::<SortIndex>();
+    let mut issues = vec![];
+    for entry in entries.iter() {
+        if entry.last_updated_at > now {
+            issues.push(IntegrityIssue::FutureTimestamp {
+                principal_id: entry.principal_id,
+                last_updated_at: entry.last_updated_at,
+            });
+        }
+        match sort_index.added_at(&entry.principal_id) {
+            Some(added_at) if added_at > entry.last_updated_at => {
+                issues.push(IntegrityIssue::AddedAfterUpdate {
+                    principal_id: entry.principal_id,
+                    added_at,
+                    last_updated_at: entry.last_updated_at,
+                })
+            }
+            _ => {}
+        }
+    }
+    issues
+}
+
+// Event ids are contiguous from the oldest kept event, only the first mismatch is reported
+fn event_issues(history: &History) -> Vec<IntegrityIssue> {
+    let first_id = history.first_id();
+    history
+        .0
+        .iter()
+        .enumerate()
+        .find(|(position, event)| event.id != first_id + *position as u64)
+        .map(|(position, event)| IntegrityIssue::EventIdMismatch {
+            position: position as u64,
+            id: event.id,
+        })
+        .into_iter()
+        .collect()
+}
+
+// Checks the secondary indexes against the entries and rebuilds the ones that diverged, then
+// reports what can't be repaired. It doesn't change entries, so it runs in maintenance mode
+// too, e.g. right after a migration.
+#[update]
+pub fn verify_integrity() -> Result<IntegrityReport, OperationError> {
+    check_caller()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    let mut repairs = Repairs::default();
+    if ic::get_mut::<TokenRegistry>().reshard() {
+        repairs.0.push(String::from("standards"));
+    }
+
+    let entries = ic::get::<TokenRegistry>().get_all();
+
+    let sort_index = ic::get_mut::<SortIndex>();
+    let mut rebuilt = SortIndex::default();
+    rebuilt.load(sort_index.archive(), entries.clone());
+    repairs.reconcile("sort", sort_index, rebuilt);
+
+    let mut rebuilt = FrontendIndex::default();
+    rebuilt.load(entries.clone());
+    repairs.reconcile("frontends", ic::get_mut::<FrontendIndex>(), rebuilt);
+
+    let mut rebuilt = ManagerIndex::default();
+    rebuilt.load(entries.clone());
+    repairs.reconcile("managers", ic::get_mut::<ManagerIndex>(), rebuilt);
+
+    let mut rebuilt = PrincipalIndex::default();
+    rebuilt.load(entries.clone());
+    repairs.reconcile("principals", ic::get_mut::<PrincipalIndex>(), rebuilt);
+
+    let mut rebuilt = SymbolIndex::default();
+    rebuilt.load(entries.clone());
+    repairs.reconcile("symbols", ic::get_mut::<SymbolIndex>(), rebuilt);
+
+    let mut rebuilt = AssetRefs::default();
+    for entry in entries.iter() {
+        rebuilt.track(entry.principal_id, &entry.details);
+    }
+    repairs.reconcile("asset_references", ic::get_mut::<AssetRefs>(), rebuilt);
+
+    // Loading certifies the rebuilt digest, which is the right one either way
+    let mut rebuilt = Integrity::default();
+    rebuilt.load(entries.clone());
+    repairs.reconcile("integrity", ic::get_mut::<Integrity>(), rebuilt);
+
+    let history = ic::get::<History>();
+    let mut issues = timestamp_issues(&entries);
+    issues.extend(event_issues(history));
+
+    let report = IntegrityReport {
+        checked_at: ic::time(),
+        entries: entries.len() as u64,
+        events: history.0.len() as u64,
+        repaired: repairs.0,
+        issues,
+    };
+    if !report.repaired.is_empty() || !report.issues.is_empty() {
+        let message = format!(
+            "Rebuilt {:?}, found {} issues",
+            report.repaired,
+            report.issues.len()
+        );
+        log(LogLevel::Warn, "verify_integrity", ic::caller(), message);
+    }
+    Ok(report)
+}
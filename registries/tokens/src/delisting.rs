@@ -0,0 +1,92 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::delisting::Delistings;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+use std::collections::BTreeSet;
+
+use crate::common_types::*;
+use crate::tokens::TokenRegistry;
+
+// Principals that can never be registered, such as known scams or exploited ledgers
+#[derive(Default)]
+pub struct Blocklist(pub BTreeSet<Principal>);
+
+impl Blocklist {
+    pub fn check(&self, principal_id: &Principal) -> Result<(), OperationError> {
+        match self.0.contains(principal_id) {
+            true => Err(OperationError::Validation(vec![FieldError {
+                field: String::from("principal_id"),
+                reason: String::from("principal is blocked"),
+            }])),
+            false => Ok(()),
+        }
+    }
+}
+
+fn delist(
+    principal_id: Principal,
+    reason: String,
+    caller: Principal,
+) -> Result<(), OperationError> {
+    let removed = ic::get_mut::<TokenRegistry>().delist(&principal_id)?;
+    ic::get_mut::<Delistings>().record(principal_id, removed.name, reason, caller);
+    Ok(())
+}
+
+// Takes a scam or phishing entry down at once. Unlike `remove` it skips locks and the
+// second admin of protected entries. Subscribers get a `Delist` event right away, and the
+// router drops the entry from its cache like for any other change.
+#[update]
+pub fn emergency_delist(principal_id: Principal, reason: String) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    let caller = ic::caller();
+    if !is_admin(&caller) {
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
+    } else if reason.trim().is_empty() {
+        return Err(OperationError::BadParameters);
+    }
+
+    delist(principal_id, reason, caller)
+}
+
+// Keeps `principal_id` from being added or submitted. A registered entry is delisted with
+// `reason` as if by `emergency_delist`.
+#[update]
+pub fn block_principal(principal_id: Principal, reason: String) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    let caller = ic::caller();
+    if !is_admin(&caller) {
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
+    } else if reason.trim().is_empty() {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::get_mut::<Blocklist>().0.insert(principal_id);
+    if ic::get::<TokenRegistry>().get_info(&principal_id).is_some() {
+        delist(principal_id, reason, caller)?;
+    }
+    Ok(())
+}
+
+#[update]
+pub fn unblock_principal(principal_id: Principal) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    match ic::get_mut::<Blocklist>().0.remove(&principal_id) {
+        true => Ok(()),
+        false => Err(OperationError::NonExistentItem),
+    }
+}
+
+#[query]
+pub fn is_blocked(principal_id: Principal) -> bool {
+    ic::get::<Blocklist>().0.contains(&principal_id)
+}
@@ -0,0 +1,244 @@
+use ic_kit::candid::{encode_args, CandidType, Deserialize, Principal};
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::guard::check_principal;
+use registry_framework::logger::log;
+use registry_framework::maintenance::{CyclesThreshold, Maintenance};
+use registry_framework::management::*;
+use registry_framework::mirror::Mirror;
+use registry_framework::reserved::ReservedNames;
+
+use crate::cap::{CanisterSettings, CreateCanisterArgument, CreateCanisterResult};
+use crate::common_types::*;
+use crate::delisting::Blocklist;
+use crate::metadata::check_entry_limits;
+use crate::tokens::TokenRegistry;
+use crate::validation::validate;
+
+#[derive(CandidType, Deserialize)]
+enum InstallMode {
+    #[serde(rename = "install")]
+    Install,
+}
+
+#[derive(CandidType)]
+struct InstallCodeArgument {
+    mode: InstallMode,
+    canister_id: Principal,
+    wasm_module: Vec<u8>,
+    arg: Vec<u8>,
+}
+
+// Registries created from this one for ecosystems running their own curated instance. The
+// new canister is installed with the Wasm admins uploaded, this canister stays one of its
+// controllers and admins.
+#[derive(Default)]
+pub struct Factory {
+    pub wasm: Option<Vec<u8>>,
+    pub children: Vec<ChildRegistry>,
+}
+
+async fn create_canister(config: &ChildRegistryConfig) -> Result<Principal, String> {
+    let mut controllers = vec![ic::id()];
+    controllers.extend(config.controllers.iter().cloned());
+    let arg = CreateCanisterArgument {
+        settings: Some(CanisterSettings {
+            controllers: Some(controllers),
+            compute_allocation: None,
+            memory_allocation: None,
+            freezing_threshold: None,
+        }),
+    };
+
+    match ic::call_with_payment(
+        Principal::management_canister(),
+        "create_canister",
+        (arg,),
+        config.cycles,
+    )
+    .await
+    {
+        Ok((CreateCanisterResult { canister_id },)) => Ok(canister_id),
+        Err((_code, msg)) => Err(msg),
+    }
+}
+
+// Installs the registry, which makes this canister its first admin, then hands it the
+// other admins and the entries
+async fn install(
+    canister_id: Principal,
+    wasm_module: Vec<u8>,
+    admins: &[Principal],
+    entries: Vec<Token>,
+) -> Result<(), String> {
+    let arg = InstallCodeArgument {
+        mode: InstallMode::Install,
+        canister_id,
+        wasm_module,
+        arg: encode_args(()).unwrap(),
+    };
+    let result: Result<(), _> =
+        ic::call(Principal::management_canister(), "install_code", (arg,)).await;
+    result.map_err(|(_code, msg)| msg)?;
+
+    for admin in admins {
+        let result: Result<(Result<(), OperationError>,), _> =
+            ic::call(canister_id, "add_admin", (*admin,)).await;
+        match result {
+            Ok((Ok(()),)) => (),
+            Ok((Err(err),)) => return Err(format!("add_admin failed: {:?}", err)),
+            Err((_code, msg)) => return Err(msg),
+        }
+    }
+
+    let result: Result<(Result<u64, OperationError>,), _> =
+        ic::call(canister_id, "import_entries", (entries,)).await;
+    match result {
+        Ok((Ok(_),)) => Ok(()),
+        Ok((Err(err),)) => Err(format!("import_entries failed: {:?}", err)),
+        Err((_code, msg)) => Err(msg),
+    }
+}
+
+#[update]
+pub fn set_registry_wasm(wasm: Vec<u8>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if wasm.is_empty() {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::get_mut::<Factory>().wasm = Some(wasm);
+    Ok(())
+}
+
+// Creates a registry canister seeded with `config.entries` and returns its principal. A
+// canister created but not set up is left to its controllers, the error says why.
+#[update]
+pub async fn create_child_registry(
+    config: ChildRegistryConfig,
+) -> Result<Principal, OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    let caller = ic::caller();
+    if !is_admin(&caller) {
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
+    } else if config.name.trim().is_empty() {
+        return Err(OperationError::BadParameters);
+    }
+    config
+        .admins
+        .iter()
+        .chain(&config.controllers)
+        .try_for_each(check_principal)?;
+
+    let wasm = config
+        .wasm
+        .clone()
+        .or_else(|| ic::get::<Factory>().wasm.clone())
+        .ok_or(OperationError::BadParameters)?;
+    if ic::balance() < config.cycles.saturating_add(ic::get::<CyclesThreshold>().0) {
+        return Err(OperationError::LowCycles);
+    }
+
+    let db = ic::get::<TokenRegistry>();
+    let entries = config
+        .entries
+        .iter()
+        .map(|principal_id| db.get_info(principal_id).cloned())
+        .collect::<Option<Vec<_>>>()
+        .ok_or(OperationError::NonExistentItem)?;
+    let imported = entries.len() as u64;
+
+    let canister_id = create_canister(&config)
+        .await
+        .map_err(OperationError::Unknown)?;
+    if let Err(msg) = install(canister_id, wasm, &config.admins, entries).await {
+        let message = format!("setting up child registry {} failed: {}", canister_id, msg);
+        log(LogLevel::Error, "create_child_registry", caller, message);
+        return Err(OperationError::Unknown(msg));
+    }
+
+    ic::get_mut::<Factory>().children.push(ChildRegistry {
+        canister_id,
+        name: config.name,
+        created_by: caller,
+        created_at: ic::time(),
+        entries: imported,
+    });
+    Ok(canister_id)
+}
+
+#[query]
+pub fn get_child_registries() -> Vec<ChildRegistry> {
+    ic::get::<Factory>().children.clone()
+}
+
+// Runs an imported entry through the checks of `add` that don't call other canisters
+fn check_import(entry: &Token) -> Result<(), OperationError> {
+    check_principal(&entry.submitter)?;
+    entry.managed_by.as_ref().map_or(Ok(()), check_principal)?;
+    ic::get::<ReservedNames>().check(&entry.name, &entry.submitter, &entry.principal_id)?;
+
+    validate(&AddTokenInput {
+        name: entry.name.clone(),
+        description: entry.description.clone(),
+        thumbnail: entry.thumbnail.clone(),
+        frontend: entry.frontend.clone(),
+        principal_id: entry.principal_id,
+        details: entry.details.clone(),
+    })?;
+    check_entry_limits(None, entry)
+}
+
+// Stores entries copied from another registry, skipping the listed and blocked ones.
+// Nothing is stored unless every entry passes the checks of `add`, failed fields are
+// reported as `entries.<index>.<field>`. Returns how many were stored.
+#[update]
+pub fn import_entries(entries: Vec<Token>) -> Result<u64, OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+    ic::get::<Mirror>().check_writable()?;
+
+    let db = ic::get_mut::<TokenRegistry>();
+    let blocklist = ic::get::<Blocklist>();
+    let entries: Vec<Token> = entries
+        .into_iter()
+        .filter(|entry| {
+            db.get_info(&entry.principal_id).is_none() && !blocklist.0.contains(&entry.principal_id)
+        })
+        .collect();
+
+    let mut errors = vec![];
+    for (index, entry) in entries.iter().enumerate() {
+        match check_import(entry) {
+            Ok(()) => (),
+            Err(OperationError::Validation(fields)) => {
+                errors.extend(fields.into_iter().map(|error| FieldError {
+                    field: format!("entries.{}.{}", index, error.field),
+                    reason: error.reason,
+                }))
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(OperationError::Validation(errors));
+    }
+
+    let imported = entries.len() as u64;
+    for entry in entries {
+        db.apply(Event {
+            id: 0,
+            time: ic::time(),
+            kind: EventKind::Add,
+            entry,
+        });
+    }
+    Ok(imported)
+}
@@ -0,0 +1,300 @@
+use ic_kit::candid::Func;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::integrity::Integrity;
+
+use crate::common_types::*;
+use crate::history::History;
+use crate::index::SortIndex;
+use crate::tokens::TokenRegistry;
+
+const CSV_HEADER: &str = "name,symbol,principal_id,standard,added_at,last_updated_at\n";
+
+// Responses are split in chunks well below the 3MB message limit. Every chunk
+// is rendered on demand from the current state of the registry.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+// Quotes a CSV field when it contains a separator, a quote or a line break. Fields a
+// spreadsheet would read as a formula are prefixed with `'` and quoted.
+fn csv_field(value: &str) -> String {
+    if value.starts_with(|c| matches!(c, '=' | '+' | '-' | '@' | '\t' | '\r')) {
+        format!("\"'{}\"", value.replace('"', "\"\""))
+    } else if value.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn text_detail(token: &Token, key: &str) -> String {
+    match token.details.iter().find(|(name, _)| name == key) {
+        Some((_, DetailValue::Text(value))) => value.clone(),
+        _ => String::new(),
+    }
+}
+
+pub fn csv_row(token: &Token) -> String {
+    let added_at = ic::get::<SortIndex>()
+        .added_at(&token.principal_id)
+        .unwrap_or(token.last_updated_at);
+
+    format!(
+        "{},{},{},{},{},{}\n",
+        csv_field(&token.name),
+        csv_field(&text_detail(token, "symbol")),
+        token.principal_id,
+        csv_field(&text_detail(token, "standard")),
+        added_at,
+        token.last_updated_at
+    )
+}
+
+// Renders the rows starting at `index` until the chunk is full. Returns the
+// token of the next chunk, if any rows are left.
+fn csv_chunk(index: u64) -> (Vec<u8>, Option<StreamingCallbackToken>) {
+    let db = ic::get::<TokenRegistry>();
+    let mut body = String::new();
+    if index == 0 {
+        body.push_str(CSV_HEADER);
+    }
+
+    let mut next = index;
+    for principal_id in ic::get::<SortIndex>()
+        .sorted(SortBy::Name, SortDirection::Ascending)
+        .skip(index as usize)
+    {
+        if body.len() >= CHUNK_SIZE {
+            let token = StreamingCallbackToken {
+                key: String::from("/export.csv"),
+                index: next,
+            };
+            return (body.into_bytes(), Some(token));
+        }
+
+        if let Some(token) = db.get_info(&principal_id) {
+            body.push_str(&csv_row(token));
+        }
+        next += 1;
+    }
+
+    (body.into_bytes(), None)
+}
+
+fn export_csv() -> HttpResponse {
+    let (body, token) = csv_chunk(0);
+    let streaming_strategy = token.map(|token| StreamingStrategy::Callback {
+        callback: Func {
+            principal: ic::id(),
+            method: String::from("http_request_streaming_callback"),
+        },
+        token,
+    });
+
+    HttpResponse {
+        status_code: 200,
+        headers: vec![
+            (
+                String::from("Content-Type"),
+                String::from("text/csv; charset=utf-8"),
+            ),
+            (
+                String::from("Content-Disposition"),
+                String::from("attachment; filename=\"tokens.csv\""),
+            ),
+        ],
+        body,
+        streaming_strategy,
+    }
+}
+
+// Events returned by a `/changes` request that doesn't ask for fewer
+const CHANGES_LIMIT: u16 = 100;
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_optional(value: Option<String>) -> String {
+    value.unwrap_or_else(|| String::from("null"))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Integers are written as strings since JSON numbers lose precision past 2^53, and slices
+// as hex
+fn json_detail(value: &DetailValue) -> String {
+    match value {
+        DetailValue::True => String::from("true"),
+        DetailValue::False => String::from("false"),
+        DetailValue::U64(value) => json_string(&value.to_string()),
+        DetailValue::I64(value) => json_string(&value.to_string()),
+        DetailValue::Float(value) => value.to_string(),
+        DetailValue::Text(value) => json_string(value),
+        DetailValue::Principal(value) => json_string(&value.to_text()),
+        DetailValue::Slice(value) => json_string(&hex(value)),
+        DetailValue::Vec(values) => format!(
+            "[{}]",
+            values.iter().map(json_detail).collect::<Vec<_>>().join(",")
+        ),
+    }
+}
+
+fn json_token(token: &Token) -> String {
+    let details = token
+        .details
+        .iter()
+        .map(|(key, value)| format!("{}:{}", json_string(key), json_detail(value)))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        "{{\"principal_id\":{},\"name\":{},\"description\":{},\"thumbnail\":{},\"frontend\":{},\
+         \"submitter\":{},\"managed_by\":{},\"last_updated_by\":{},\"last_updated_at\":\"{}\",\
+         \"details\":{{{}}}}}",
+        json_string(&token.principal_id.to_text()),
+        json_string(&token.name),
+        json_string(&token.description),
+        json_string(&token.thumbnail),
+        json_optional(token.frontend.as_deref().map(json_string)),
+        json_string(&token.submitter.to_text()),
+        json_optional(token.managed_by.map(|owner| json_string(&owner.to_text()))),
+        json_string(&token.last_updated_by.to_text()),
+        token.last_updated_at,
+        details
+    )
+}
+
+fn json_event(event: &Event) -> String {
+    format!(
+        "{{\"id\":\"{}\",\"time\":\"{}\",\"kind\":\"{:?}\",\"entry\":{}}}",
+        event.id,
+        event.time,
+        event.kind,
+        json_token(&event.entry)
+    )
+}
+
+fn query_param<'a>(url: &'a str, key: &str) -> Option<&'a str> {
+    url.split_once('?')?
+        .1
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| *name == key)
+        .map(|(_, value)| value)
+}
+
+// `get_changes` for clients that can only speak HTTP, e.g. `/changes?since=120&limit=50`.
+// Clients poll with the returned `next` cursor, a cursor older than the kept history
+// answers 410 Gone and has to be rebuilt from `/export.csv`. The body isn't certified, the
+// `X-Registry-Hash` header can be checked against the certified `registry_hash` instead.
+fn changes(url: &str) -> HttpResponse {
+    let since = match query_param(url, "since").map(str::parse::<u64>) {
+        Some(Ok(since)) => since,
+        None => 0,
+        Some(Err(_)) => return bad_request("`since` must be an event id"),
+    };
+    let limit = match query_param(url, "limit").map(str::parse::<u16>) {
+        Some(Ok(limit)) => std::cmp::min(limit, CHANGES_LIMIT),
+        None => CHANGES_LIMIT,
+        Some(Err(_)) => return bad_request("`limit` must be a number"),
+    };
+
+    let events = match ic::get::<History>().get_changes(since, limit) {
+        Ok(events) => events,
+        Err(OperationError::HistoryTruncated(first_id)) => return gone(first_id),
+        Err(_) => return bad_request("invalid cursor"),
+    };
+    let next = events.last().map_or(since, |event| event.id);
+    let body = format!(
+        "{{\"events\":[{}],\"next\":\"{}\"}}",
+        events
+            .iter()
+            .map(|event| json_event(event))
+            .collect::<Vec<_>>()
+            .join(","),
+        next
+    );
+
+    HttpResponse {
+        status_code: 200,
+        headers: vec![
+            (
+                String::from("Content-Type"),
+                String::from("application/json"),
+            ),
+            (String::from("Cache-Control"), String::from("no-store")),
+            (
+                String::from("X-Registry-Hash"),
+                hex(&ic::get::<Integrity>().hash()),
+            ),
+        ],
+        body: body.into_bytes(),
+        streaming_strategy: None,
+    }
+}
+
+fn gone(first_id: u64) -> HttpResponse {
+    HttpResponse {
+        status_code: 410,
+        headers: vec![],
+        body: format!("events before {} were dropped from the history", first_id).into_bytes(),
+        streaming_strategy: None,
+    }
+}
+
+fn bad_request(reason: &str) -> HttpResponse {
+    HttpResponse {
+        status_code: 400,
+        headers: vec![],
+        body: reason.as_bytes().to_vec(),
+        streaming_strategy: None,
+    }
+}
+
+fn not_found() -> HttpResponse {
+    HttpResponse {
+        status_code: 404,
+        headers: vec![],
+        body: b"Not found".to_vec(),
+        streaming_strategy: None,
+    }
+}
+
+#[query]
+pub fn http_request(request: HttpRequest) -> HttpResponse {
+    let path = request.url.split('?').next().unwrap_or("");
+
+    match path {
+        "/export.csv" => export_csv(),
+        "/changes" => changes(&request.url),
+        _ => not_found(),
+    }
+}
+
+#[query]
+pub fn http_request_streaming_callback(
+    token: StreamingCallbackToken,
+) -> StreamingCallbackHttpResponse {
+    let (body, token) = match token.key.as_str() {
+        "/export.csv" => csv_chunk(token.index),
+        _ => (vec![], None),
+    };
+
+    StreamingCallbackHttpResponse { body, token }
+}
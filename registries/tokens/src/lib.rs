@@ -1,5 +1,63 @@
+mod audit;
+mod cap;
 mod common_types;
-mod management;
+mod consistency;
+mod dashboard;
+mod delisting;
+mod factory;
+mod filters;
+mod heartbeat;
+mod history;
+mod http;
+mod index;
+mod manifest;
+mod memory;
+mod metadata;
+mod migrations;
+mod projection;
+mod protection;
+mod retries;
+mod reviewers;
+mod rules;
+mod seed;
+mod submissions;
 mod tests;
 mod tokens;
 mod upgrade;
+mod validation;
+
+// Methods of the stores shared with the other registries, see `registry_framework`
+registry_framework::alerts_methods!();
+registry_framework::aliases_methods!(crate::tokens::TokenRegistry);
+registry_framework::assets_methods!(crate::submissions::Submissions);
+registry_framework::callbacks_methods!(
+    crate::tokens::TokenRegistry,
+    crate::submissions::Submissions
+);
+registry_framework::curation_methods!(
+    crate::submissions::Submissions,
+    crate::submissions::promote,
+    crate::submissions::dismiss
+);
+registry_framework::delisting_methods!();
+registry_framework::integrity_methods!();
+registry_framework::locks_methods!(crate::tokens::TokenRegistry);
+registry_framework::logger_methods!();
+registry_framework::maintenance_methods!();
+registry_framework::management_methods!();
+registry_framework::messages_methods!();
+registry_framework::metrics_methods!(crate::memory::report);
+registry_framework::mirror_methods!(crate::tokens::TokenRegistry);
+registry_framework::pinning_methods!(crate::tokens::TokenRegistry);
+registry_framework::protection_methods!(
+    crate::tokens::TokenRegistry,
+    crate::common_types::AddTokenInput,
+    crate::protection::apply
+);
+registry_framework::reports_methods!(crate::tokens::TokenRegistry);
+registry_framework::reserved_methods!();
+registry_framework::retries_methods!(crate::retries::run);
+registry_framework::sponsors_methods!(crate::tokens::TokenRegistry);
+registry_framework::subscribers_methods!();
+registry_framework::translations_methods!(crate::tokens::TokenRegistry);
+registry_framework::usage_methods!();
@@ -0,0 +1,157 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::logger::log_result;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+use registry_framework::usage::Usage;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::common_types::*;
+use crate::protection::Protection;
+use crate::tokens::add_entry;
+use crate::validation::validate;
+
+// Project description accepted by `add_from_manifest`, shaped after dfx.json:
+//
+// { "canisters": { "<name>": { "principal_id": "...", "description": "...",
+//   "thumbnail": "...", "frontend": "...", "details": { "symbol": "XTC" } } } }
+//
+// The name of an entry defaults to the key of its canister.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    canisters: BTreeMap<String, ManifestCanister>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestCanister {
+    principal_id: String,
+    name: Option<String>,
+    description: String,
+    thumbnail: String,
+    frontend: Option<String>,
+    #[serde(default)]
+    details: BTreeMap<String, Value>,
+}
+
+// Integers are stored as `U64`, or `I64` when negative. Objects and nulls have no detail value.
+fn detail_value(value: Value) -> Option<DetailValue> {
+    match value {
+        Value::Bool(true) => Some(DetailValue::True),
+        Value::Bool(false) => Some(DetailValue::False),
+        Value::Number(number) => number
+            .as_u64()
+            .map(DetailValue::U64)
+            .or_else(|| number.as_i64().map(DetailValue::I64))
+            .or_else(|| number.as_f64().map(DetailValue::Float)),
+        Value::String(text) => Some(DetailValue::Text(text)),
+        Value::Array(values) => values
+            .into_iter()
+            .map(detail_value)
+            .collect::<Option<Vec<_>>>()
+            .map(DetailValue::Vec),
+        Value::Null | Value::Object(_) => None,
+    }
+}
+
+fn field_error(field: String, reason: &str) -> FieldError {
+    FieldError {
+        field,
+        reason: String::from(reason),
+    }
+}
+
+// Turns the manifest into inputs and checks them like `add` does. Every problem is reported,
+// fields are prefixed with "canisters.<name>".
+fn parse(json_text: &str) -> Result<Vec<AddTokenInput>, OperationError> {
+    let manifest: Manifest = serde_json::from_str(json_text).map_err(|err| {
+        OperationError::Validation(vec![field_error(
+            String::from("manifest"),
+            &err.to_string(),
+        )])
+    })?;
+    if manifest.canisters.is_empty() || manifest.canisters.len() > MANIFEST_ENTRIES_LIMIT {
+        return Err(OperationError::BadParameters);
+    }
+
+    let mut errors = vec![];
+    let mut inputs = vec![];
+    for (key, canister) in manifest.canisters {
+        let field = |name: &str| format!("canisters.{}.{}", key, name);
+        let principal_id = match Principal::from_text(&canister.principal_id) {
+            Ok(principal_id) => principal_id,
+            Err(_) => {
+                errors.push(field_error(field("principal_id"), "invalid principal"));
+                continue;
+            }
+        };
+
+        let mut details = vec![];
+        for (detail, value) in canister.details {
+            match detail_value(value) {
+                Some(value) => details.push((detail, value)),
+                None => errors.push(field_error(
+                    field(&format!("details.{}", detail)),
+                    "unsupported value",
+                )),
+            }
+        }
+
+        let input = AddTokenInput {
+            name: canister.name.unwrap_or_else(|| key.clone()),
+            description: canister.description,
+            thumbnail: canister.thumbnail,
+            frontend: canister.frontend,
+            principal_id,
+            details,
+        };
+        match validate(&input) {
+            Ok(()) => inputs.push(input),
+            Err(OperationError::Validation(found)) => {
+                errors.extend(found.into_iter().map(|error| FieldError {
+                    field: field(&error.field),
+                    ..error
+                }))
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    match errors.is_empty() {
+        true => Ok(inputs),
+        false => Err(OperationError::Validation(errors)),
+    }
+}
+
+// Adds or updates every canister of a project manifest and returns how many entries were
+// written. The manifest is checked as a whole before the first write. Protected entries
+// wait for a second admin instead, as with `add`.
+#[update]
+pub async fn add_from_manifest(json_text: String) -> Result<u64, OperationError> {
+    ic::get_mut::<Usage>().record("add_from_manifest");
+    ic::get::<Maintenance>().check()?;
+
+    let caller = ic::caller();
+    if !is_admin(&caller) {
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
+    }
+
+    let mut written = 0;
+    for input in parse(&json_text)? {
+        let protection = ic::get_mut::<Protection>();
+        if protection.is_protected(&input.principal_id) {
+            protection.defer(input.principal_id, None, ProposedChange::Update(input));
+            continue;
+        }
+
+        let result = add_entry(caller, None, input).await;
+        log_result("add_from_manifest", caller, &result);
+        result?;
+        written += 1;
+    }
+    Ok(written)
+}
@@ -0,0 +1,146 @@
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::alerts::heap_size;
+use registry_framework::aliases::Aliases;
+use registry_framework::assets::{AssetRefs, Assets};
+use registry_framework::callbacks::EntryCallbacks;
+use registry_framework::curation::Curation;
+use registry_framework::delisting::Delistings;
+use registry_framework::guard::check_caller;
+use registry_framework::integrity::Integrity;
+use registry_framework::locks::Locks;
+use registry_framework::logger::Logger;
+use registry_framework::management::*;
+use registry_framework::messages::Messages;
+use registry_framework::pinning::Pins;
+use registry_framework::reports::Reports;
+use registry_framework::requests::Requests;
+use registry_framework::retries::Retries;
+use registry_framework::sponsors::Sponsors;
+use registry_framework::subscribers::Subscribers;
+use registry_framework::translations::Translations;
+
+use crate::common_types::*;
+use crate::delisting::Blocklist;
+use crate::factory::Factory;
+use crate::history::History;
+use crate::index::{FrontendIndex, ManagerIndex, PrincipalIndex, SortIndex, SymbolIndex};
+use crate::reviewers::Reviewers;
+use crate::submissions::{Expired, Submissions};
+use crate::tokens::TokenRegistry;
+
+// What `compact` keeps of the logs, the oldest records are dropped
+const COMPACT_HISTORY: usize = HISTORY_LIMIT / 10;
+const COMPACT_DELISTINGS: usize = 1_000;
+const COMPACT_LOGS: usize = 100;
+
+#[cfg(target_arch = "wasm32")]
+fn stable_pages() -> u64 {
+    ic_cdk::api::stable::stable64_size()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn stable_pages() -> u64 {
+    0
+}
+
+pub fn report() -> MemoryReport {
+    let count = |store: &str, len: usize| (String::from(store), len as u64);
+
+    MemoryReport {
+        heap_size: heap_size(),
+        stable_pages: stable_pages(),
+        stores: vec![
+            count("entries", ic::get::<TokenRegistry>().len()),
+            count("history", ic::get::<History>().0.len()),
+            count("submissions", ic::get::<Submissions>().get_all().len()),
+            count("expired_submissions", ic::get::<Expired>().get_all().len()),
+            count("aliases", ic::get::<Aliases>().get_all().len()),
+            count("subscribers", ic::get::<Subscribers>().get_all().len()),
+            count(
+                "pending_notifications",
+                ic::get::<Subscribers>().get_pending().len(),
+            ),
+            count("sponsors", ic::get::<Sponsors>().get_all().len()),
+            count("locks", ic::get::<Locks>().0.len()),
+            count("reports", ic::get::<Reports>().len()),
+            count("delistings", ic::get::<Delistings>().0.len()),
+            count("blocked_principals", ic::get::<Blocklist>().0.len()),
+            count("logs", ic::get::<Logger>().len()),
+            count("retries", ic::get::<Retries>().len()),
+            count("pins", ic::get::<Pins>().0.len()),
+            count("reviewers", ic::get::<Reviewers>().0.len()),
+            count("assets", ic::get::<Assets>().content.len()),
+            count("asset_references", ic::get::<AssetRefs>().0.len()),
+            count("error_languages", ic::get::<Messages>().len()),
+            count("child_registries", ic::get::<Factory>().children.len()),
+            count("entry_callbacks", ic::get::<EntryCallbacks>().0.len()),
+            count(
+                "interned_strings",
+                ic::get::<TokenRegistry>().interned() + ic::get::<FrontendIndex>().interned(),
+            ),
+        ],
+    }
+}
+
+// Rebuilds the stores through the same archive and load steps as an upgrade, which
+// sizes every map to its content and drops expired request results, and trims the
+// history, delistings and logs. The wasm heap never shrinks, the freed space is
+// reused by later allocations.
+fn compact_stores() {
+    macro_rules! rebuild {
+        ($store:ty) => {{
+            let store = ic::get_mut::<$store>();
+            let archive = store.archive();
+            store.load(archive);
+        }};
+    }
+
+    ic::get_mut::<Requests>().expire();
+
+    rebuild!(TokenRegistry);
+    rebuild!(Aliases);
+    rebuild!(Translations);
+    rebuild!(Submissions);
+    rebuild!(Sponsors);
+    rebuild!(Curation);
+    rebuild!(Subscribers);
+    rebuild!(Requests);
+    rebuild!(Reports);
+
+    let entries = ic::get::<TokenRegistry>().get_all();
+    let added_at = ic::get::<SortIndex>().archive();
+    ic::get_mut::<SortIndex>().load(added_at, entries.clone());
+    ic::get_mut::<FrontendIndex>().load(entries.clone());
+    ic::get_mut::<PrincipalIndex>().load(entries.clone());
+    ic::get_mut::<Integrity>().load(entries.clone());
+    ic::get_mut::<ManagerIndex>().load(entries.clone());
+    ic::get_mut::<SymbolIndex>().load(entries);
+
+    ic::get_mut::<History>().trim(COMPACT_HISTORY);
+    ic::get_mut::<History>().0.shrink_to_fit();
+    ic::get_mut::<Delistings>().trim(COMPACT_DELISTINGS);
+    ic::get_mut::<Delistings>().0.shrink_to_fit();
+    ic::get_mut::<Logger>().trim(COMPACT_LOGS);
+}
+
+#[query]
+pub fn memory_report() -> MemoryReport {
+    report()
+}
+
+// Returns the report taken once the stores are rebuilt. Clients following `get_changes`
+// from before the kept history have to reload the registry afterwards. Admins can call
+// it: the registry can't check the controllers of its canister, admins stand for them
+// like they do for `propose_controller`.
+#[update]
+pub fn compact() -> Result<MemoryReport, OperationError> {
+    check_caller()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    compact_stores();
+    Ok(report())
+}
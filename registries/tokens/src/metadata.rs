@@ -0,0 +1,559 @@
+use ic_kit::candid::{encode_one, Principal};
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+use std::collections::{BTreeMap, HashSet};
+use validator::validate_url;
+
+use crate::common_types::*;
+use crate::index::url_host;
+use crate::rules::standard_key;
+use crate::tokens::TokenRegistry;
+use crate::validation::Validator;
+
+// Detail keys accepted by the registry, editable by admins without an upgrade
+pub struct DetailSchema(pub Vec<DetailKeySpec>);
+
+fn spec(key: &str, value_type: DetailType) -> DetailKeySpec {
+    DetailKeySpec {
+        key: String::from(key),
+        value_type,
+        required: true,
+    }
+}
+
+pub fn optional(key: &str, value_type: DetailType) -> DetailKeySpec {
+    DetailKeySpec {
+        required: false,
+        ..spec(key, value_type)
+    }
+}
+
+impl Default for DetailSchema {
+    fn default() -> Self {
+        DetailSchema(vec![
+            spec("symbol", DetailType::Text),
+            spec("standard", DetailType::Text),
+            spec("total_supply", DetailType::Any),
+            spec("verified", DetailType::Bool),
+            spec("decimals", DetailType::U64),
+            spec("fee", DetailType::Any),
+            // vec { principal oracle; text pair }, see `price_source`
+            optional("price_feed", DetailType::Vec),
+            // Provenance of wrapped assets, see `validate_origin`
+            optional("origin_chain", DetailType::Text),
+            optional("origin_contract", DetailType::Text),
+            optional("bridge", DetailType::Principal),
+            // Non-standard behaviors wallets warn about, see `get_risk_flags`
+            optional("fee_on_transfer", DetailType::Bool),
+            optional("rebasing", DetailType::Bool),
+            optional("pausable", DetailType::Bool),
+            optional("blacklistable", DetailType::Bool),
+            // Project profile links, see `validate_links`
+            optional("website", DetailType::Text),
+            optional("docs", DetailType::Text),
+            optional("twitter", DetailType::Text),
+            optional("discord", DetailType::Text),
+            optional("github", DetailType::Text),
+            // vec { text url }, frontends besides the `frontend` of the entry
+            optional("frontends", DetailType::Vec),
+        ])
+    }
+}
+
+// Keys whose spec depends on the standard of the token. EXT has no standard transfer fee, and
+// ICRC-1 ledgers have a minting account.
+fn standard_specs(standard: &str) -> Vec<DetailKeySpec> {
+    match standard_key(standard).as_str() {
+        "EXT" => vec![optional("fee", DetailType::Any)],
+        "ICRC1" => vec![optional("minting_account", DetailType::Principal)],
+        _ => vec![],
+    }
+}
+
+// The `DetailSchema` with the specs of `standard` replacing or added to its own
+pub fn schema_for(standard: &str) -> Vec<DetailKeySpec> {
+    let mut schema = ic::get::<DetailSchema>().0.clone();
+    for spec in standard_specs(standard) {
+        match schema.iter_mut().find(|current| current.key == spec.key) {
+            Some(current) => *current = spec,
+            None => schema.push(spec),
+        }
+    }
+    schema
+}
+
+// The details of a token keyed by name, checked against the schema of its standard
+pub struct Metadata(BTreeMap<String, DetailValue>);
+
+impl Metadata {
+    pub fn parse(details: &[(String, DetailValue)]) -> Result<Self, OperationError> {
+        let mut validator = Validator::default();
+        let metadata = Metadata::check(details, &mut validator);
+        validator.finish()?;
+        Ok(metadata)
+    }
+
+    // Keeps the valid details, reporting every invalid or missing one to `validator`
+    pub fn check(details: &[(String, DetailValue)], validator: &mut Validator) -> Self {
+        let standard = detail(details, "standard").and_then(DetailValue::as_text);
+        let schema = schema_for(standard.unwrap_or_default());
+        let limits = ic::get::<DetailLimits>();
+        let slices = ic::get::<SliceLimits>();
+
+        let size = encode_one(details).map_or(u64::MAX, |bytes| bytes.len() as u64);
+        validator.check(size <= limits.max_size, "details", "too large");
+
+        let mut map = BTreeMap::new();
+        for (key, value) in details.iter() {
+            let field = format!("details.{}", key);
+            let spec = match schema.iter().find(|spec| spec.key == *key) {
+                Some(spec) => spec,
+                None => {
+                    validator.check(false, &field, "unknown key");
+                    continue;
+                }
+            };
+
+            if map.contains_key(key) {
+                validator.check(false, &field, "duplicated key");
+            } else if !spec.value_type.matches(value) {
+                validator.check(false, &field, &format!("expected {:?}", spec.value_type));
+            } else if !value.is_finite() {
+                validator.check(false, &field, "floats must be finite");
+            } else if value.depth() > limits.max_depth {
+                validator.check(false, &field, "nested too deeply");
+            } else if slices
+                .asset_threshold
+                .map_or(false, |threshold| value.largest_slice() > threshold)
+            {
+                validator.check(false, &field, "store large slices as assets");
+            } else if value.largest_slice() > slices.max_size {
+                validator.check(false, &field, "slice too large");
+            } else {
+                map.insert(key.clone(), value.clone());
+            }
+        }
+
+        if let Some(decimals) = map.get("decimals").and_then(DetailValue::as_u64) {
+            validator.check(
+                decimals <= DECIMALS_LIMIT,
+                "details.decimals",
+                &format!("at most {}", DECIMALS_LIMIT),
+            );
+        }
+
+        for spec in schema.iter() {
+            if spec.required && !details.iter().any(|(key, _)| *key == spec.key) {
+                validator.check(false, &format!("details.{}", spec.key), "missing");
+            }
+        }
+
+        Metadata(map)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&DetailValue> {
+        self.0.get(key)
+    }
+
+    pub fn text(&self, key: &str) -> Option<&str> {
+        self.0.get(key)?.as_text()
+    }
+
+    pub fn u64(&self, key: &str) -> Option<u64> {
+        self.0.get(key)?.as_u64()
+    }
+
+    pub fn standard(&self) -> &str {
+        self.text("standard").unwrap_or_default()
+    }
+
+    pub fn symbol(&self) -> &str {
+        self.text("symbol").unwrap_or_default()
+    }
+
+    pub fn decimals(&self) -> u64 {
+        self.u64("decimals").unwrap_or_default()
+    }
+
+    pub fn verified(&self) -> bool {
+        self.0
+            .get("verified")
+            .and_then(DetailValue::as_bool)
+            .unwrap_or(false)
+    }
+
+    // A flag left out is taken as unset
+    pub fn flag(&self, key: &str) -> bool {
+        self.0
+            .get(key)
+            .and_then(DetailValue::as_bool)
+            .unwrap_or(false)
+    }
+
+    pub fn risk_flags(&self) -> RiskFlags {
+        RiskFlags {
+            fee_on_transfer: self.flag("fee_on_transfer"),
+            rebasing: self.flag("rebasing"),
+            pausable: self.flag("pausable"),
+            blacklistable: self.flag("blacklistable"),
+        }
+    }
+
+    // Fees are integers in the smallest unit, given as a number or as its decimal text
+    pub fn fee(&self) -> Option<u64> {
+        match self.0.get("fee")? {
+            DetailValue::Text(fee) => fee.trim().parse().ok(),
+            fee => fee.as_u64(),
+        }
+    }
+
+    pub fn origin_chain(&self) -> Option<&str> {
+        self.text("origin_chain")
+    }
+
+    pub fn price_feed(&self) -> Option<PriceSource> {
+        price_source(self.get("price_feed")?)
+    }
+
+    // The `frontend` of the entry comes first among the frontends
+    pub fn links(&self, frontend: Option<&String>) -> ProjectLinks {
+        let link = |key| self.text(key).map(String::from);
+        let frontends = self
+            .get("frontends")
+            .and_then(frontends)
+            .unwrap_or_default();
+        ProjectLinks {
+            website: link("website"),
+            docs: link("docs"),
+            twitter: link("twitter"),
+            discord: link("discord"),
+            github: link("github"),
+            frontends: frontend.into_iter().cloned().chain(frontends).collect(),
+        }
+    }
+}
+
+// Reads a `price_feed` detail, given as the oracle principal followed by a non empty pair
+// identifier, e.g. vec { principal "uf6dk-hyaaa-aaaaq-qaaaq-cai"; "ICP/USD" }
+pub fn price_source(value: &DetailValue) -> Option<PriceSource> {
+    match value {
+        DetailValue::Vec(values) => match values.as_slice() {
+            [DetailValue::Principal(oracle), DetailValue::Text(pair)] if !pair.is_empty() => {
+                Some(PriceSource {
+                    oracle: *oracle,
+                    pair: pair.clone(),
+                })
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Writes `amount` smallest units as a decimal number, e.g. 10_000 with 8 decimals
+// is "0.0001". Trailing zeros of the fraction are dropped. `None` for decimals no ledger
+// can report, which entries listed before they were checked may still hold.
+pub fn format_amount(amount: u64, decimals: u64) -> Option<String> {
+    if decimals > DECIMALS_LIMIT {
+        return None;
+    }
+
+    let decimals = usize::try_from(decimals).ok()?;
+    let digits = format!("{:0>width$}", amount, width = decimals.checked_add(1)?);
+    let (whole, fraction) = digits.split_at(digits.len().checked_sub(decimals)?);
+    Some(match fraction.trim_end_matches('0') {
+        "" => whole.to_string(),
+        fraction => format!("{}.{}", whole, fraction),
+    })
+}
+
+// Chains are named by lowercase identifiers such as "bitcoin" or "ethereum"
+pub fn is_chain_name(chain: &str) -> bool {
+    !chain.is_empty()
+        && chain
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+// A contract or bridge only make sense for an asset wrapped from another chain
+pub fn validate_origin(metadata: &Metadata, validator: &mut Validator) {
+    match metadata.origin_chain() {
+        Some(chain) => validator.check(
+            is_chain_name(chain),
+            "details.origin_chain",
+            "expected a lowercase chain name",
+        ),
+        None => {
+            for key in ["origin_contract", "bridge"] {
+                if metadata.get(key).is_some() {
+                    let field = format!("details.{}", key);
+                    validator.check(false, &field, "requires details.origin_chain");
+                }
+            }
+        }
+    }
+
+    if let Some(contract) = metadata.text("origin_contract") {
+        let valid = !contract.trim().is_empty();
+        validator.check(valid, "details.origin_contract", "empty");
+    }
+}
+
+// Hosts each link detail must point to, any host when empty
+const LINK_HOSTS: [(&str, &[&str]); 5] = [
+    ("website", &[]),
+    ("docs", &[]),
+    ("twitter", &["twitter.com", "x.com"]),
+    ("discord", &["discord.gg", "discord.com"]),
+    ("github", &["github.com"]),
+];
+
+// Reads a `frontends` detail, a list of URLs, e.g. vec { "https://app.sonic.ooo" }
+pub fn frontends(value: &DetailValue) -> Option<Vec<String>> {
+    value
+        .as_vec()?
+        .iter()
+        .map(|url| url.as_text().map(String::from))
+        .collect()
+}
+
+// Links must be URLs, on the site of the service they're named after
+pub fn validate_links(metadata: &Metadata, validator: &mut Validator) {
+    for (key, hosts) in LINK_HOSTS {
+        let url = match metadata.text(key) {
+            Some(url) => url,
+            None => continue,
+        };
+        let field = format!("details.{}", key);
+        if !validate_url(url) {
+            validator.check(false, &field, "invalid URL");
+        } else if !hosts.is_empty() {
+            let valid = url_host(url).map_or(false, |host| hosts.contains(&host.as_str()));
+            validator.check(
+                valid,
+                &field,
+                &format!("expected a {} URL", hosts.join(" or ")),
+            );
+        }
+    }
+
+    if let Some(value) = metadata.get("frontends") {
+        match frontends(value) {
+            Some(urls) => {
+                validator.check(
+                    urls.len() <= FRONTENDS_LIMIT,
+                    "details.frontends",
+                    "too many",
+                );
+                for url in urls.iter() {
+                    validator.check(validate_url(url), "details.frontends", "invalid URL");
+                }
+            }
+            None => validator.check(false, "details.frontends", "expected a vec of URLs"),
+        }
+    }
+}
+
+#[update]
+pub fn set_required_details(keys: Vec<DetailKeySpec>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    let mut seen = HashSet::new();
+    if keys
+        .iter()
+        .any(|spec| spec.key.is_empty() || !seen.insert(spec.key.as_str()))
+    {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::store(DetailSchema(keys));
+    Ok(())
+}
+
+// Renames the `old` detail key across all entries, e.g. `fee` to `transfer_fee`, and returns
+// how many entries hold it. The key is renamed in the detail schema too, unless `new` is
+// already in it. `dry_run` only counts the entries.
+#[update]
+pub fn migrate_detail_key(old: String, new: String, dry_run: bool) -> Result<u64, OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if old.is_empty() || new.is_empty() || old == new {
+        return Err(OperationError::BadParameters);
+    }
+
+    let touched =
+        ic::get_mut::<TokenRegistry>().rename_detail_key(&ic::caller(), &old, &new, dry_run)?;
+    if dry_run {
+        return Ok(touched);
+    }
+
+    let schema = &mut ic::get_mut::<DetailSchema>().0;
+    if schema.iter().any(|spec| spec.key == new) {
+        schema.retain(|spec| spec.key != old);
+    } else if let Some(spec) = schema.iter_mut().find(|spec| spec.key == old) {
+        spec.key = new;
+    }
+    Ok(touched)
+}
+
+#[query]
+pub fn get_price_source(principal_id: Principal) -> Option<PriceSource> {
+    let token = ic::get::<TokenRegistry>().get_info(&principal_id)?;
+    price_source(detail(&token.details, "price_feed")?)
+}
+
+// What wallets need to show a token, read from its validated details
+#[query]
+pub fn get_display_info(principal_id: Principal) -> Option<DisplayInfo> {
+    let token = ic::get::<TokenRegistry>().get_info(&principal_id)?;
+    let metadata = Metadata::check(&token.details, &mut Validator::default());
+    let (symbol, decimals, fee) = (metadata.symbol(), metadata.decimals(), metadata.fee());
+
+    Some(DisplayInfo {
+        symbol: symbol.to_string(),
+        decimals,
+        fee,
+        formatted_fee: fee
+            .and_then(|fee| format_amount(fee, decimals))
+            .map(|amount| format!("{} {}", amount, symbol)),
+    })
+}
+
+// Behaviors of the token wallets should warn users about before a transfer
+#[query]
+pub fn get_risk_flags(principal_id: Principal) -> Option<RiskFlags> {
+    let token = ic::get::<TokenRegistry>().get_info(&principal_id)?;
+    Some(Metadata::check(&token.details, &mut Validator::default()).risk_flags())
+}
+
+// Website, docs, socials and frontends of the project, so explorers can show its profile
+#[query]
+pub fn get_links(principal_id: Principal) -> Option<ProjectLinks> {
+    let token = ic::get::<TokenRegistry>().get_info(&principal_id)?;
+    let metadata = Metadata::check(&token.details, &mut Validator::default());
+    Some(metadata.links(token.frontend.as_ref()))
+}
+
+// Entries wrapped from `chain`, so wallets can group bridged assets
+#[query]
+pub fn get_all_by_origin_chain(chain: String) -> Vec<&'static Token> {
+    let chain = chain.trim().to_lowercase();
+    ic::get::<TokenRegistry>()
+        .get_all()
+        .into_iter()
+        .filter(|token| {
+            detail(&token.details, "origin_chain").and_then(DetailValue::as_text)
+                == Some(chain.as_str())
+        })
+        .collect()
+}
+
+#[update]
+pub fn set_detail_limits(limits: DetailLimits) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if limits.max_depth == 0 || limits.max_size == 0 {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::store(limits);
+    Ok(())
+}
+
+#[query]
+pub fn get_detail_limits() -> DetailLimits {
+    *ic::get::<DetailLimits>()
+}
+
+#[update]
+pub fn set_entry_limits(limits: EntryLimits) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if limits.max_size == 0 || limits.max_details == 0 {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::store(limits);
+    Ok(())
+}
+
+#[query]
+pub fn get_entry_limits() -> EntryLimits {
+    *ic::get::<EntryLimits>()
+}
+
+// Rejects an entry over the `EntryLimits`, unless it's an edit shrinking the `current` one
+// so that entries listed before a limit was lowered can still be trimmed
+pub fn check_entry_limits(current: Option<&Token>, updated: &Token) -> Result<(), OperationError> {
+    let limits = ic::get::<EntryLimits>();
+    let size = |token: &Token| encode_one(token).map_or(u64::MAX, |bytes| bytes.len() as u64);
+
+    let count = updated.details.len();
+    let grows = current.map_or(true, |current| count > current.details.len());
+    let mut validator = Validator::default();
+    validator.check(
+        count as u64 <= limits.max_details || !grows,
+        "details",
+        "too many",
+    );
+    validator.finish()?;
+
+    let bytes = size(updated);
+    if bytes > limits.max_size && current.map_or(true, |current| bytes > size(current)) {
+        return Err(OperationError::EntryTooLarge {
+            bytes,
+            limit: limits.max_size,
+        });
+    }
+    Ok(())
+}
+
+#[update]
+pub fn set_slice_limits(limits: SliceLimits) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if limits.max_size == 0 || limits.asset_threshold == Some(0) {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::store(limits);
+    Ok(())
+}
+
+#[query]
+pub fn get_slice_limits() -> SliceLimits {
+    *ic::get::<SliceLimits>()
+}
+
+#[query]
+pub fn get_required_details() -> Vec<DetailKeySpec> {
+    ic::get::<DetailSchema>().0.clone()
+}
+
+// Detail keys and value types `validate` checks a token of `standard` against
+#[query]
+pub fn get_detail_schema(standard: String) -> Result<Vec<DetailKeySpec>, OperationError> {
+    let standard = standard_key(&standard);
+    if !SUPPORTED_STANDARDS
+        .iter()
+        .any(|supported| standard_key(supported) == standard)
+    {
+        return Err(OperationError::BadParameters);
+    }
+
+    Ok(schema_for(&standard))
+}
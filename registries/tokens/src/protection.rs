@@ -0,0 +1,19 @@
+use ic_kit::*;
+
+use crate::common_types::*;
+use crate::tokens::{add_entry, TokenRegistry};
+
+// See `registry_framework::protection`
+pub type Protection = registry_framework::protection::Protection<AddTokenInput>;
+
+// Runs a change to a protected entry once a second admin confirmed it
+pub async fn apply(proposal: ChangeProposal) -> Result<(), OperationError> {
+    let submitter = proposal.trusted_source.unwrap_or(proposal.proposed_by);
+    match proposal.change {
+        ProposedChange::Update(entry) => add_entry(submitter, proposal.trusted_source, entry).await,
+        ProposedChange::Remove => {
+            ic::get_mut::<TokenRegistry>().remove(&submitter, &proposal.principal_id)
+        }
+        ProposedChange::Unprotect => Ok(()),
+    }
+}
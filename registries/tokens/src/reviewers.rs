@@ -0,0 +1,99 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+use std::collections::BTreeMap;
+
+use crate::common_types::*;
+use crate::rules::standard_key;
+use crate::submissions::Submissions;
+
+// Moderators assigned to the submissions of each standard, keyed by `standard_key`.
+// Admins review the submissions of every standard.
+#[derive(Default)]
+pub struct Reviewers(pub BTreeMap<String, Vec<Principal>>);
+
+impl Reviewers {
+    pub fn assign(&mut self, standard: &str, principals: Vec<Principal>) {
+        let standard = standard_key(standard);
+        match principals.is_empty() {
+            true => self.0.remove(&standard),
+            false => self.0.insert(standard, principals),
+        };
+    }
+
+    // Whether `account` reviews the submission, from the standard in its details
+    pub fn reviews(&self, account: &Principal, submission: &Submission) -> bool {
+        detail(&submission.entry.details, "standard")
+            .and_then(DetailValue::as_text)
+            .and_then(|standard| self.0.get(&standard_key(standard)))
+            .map_or(false, |reviewers| reviewers.contains(account))
+    }
+}
+
+// Allows `caller` to approve or reject the pending submission of `principal_id`
+pub fn check_reviewer(caller: &Principal, principal_id: &Principal) -> Result<(), OperationError> {
+    if is_admin(caller) {
+        return Ok(());
+    }
+
+    let submission = ic::get::<Submissions>()
+        .get(principal_id)
+        .ok_or(OperationError::NonExistentItem)?;
+    match ic::get::<Reviewers>().reviews(caller, submission) {
+        true => Ok(()),
+        false => Err(OperationError::not_authorized(Role::Reviewer, caller)),
+    }
+}
+
+// Routes the submissions of `standard` to `principals`, an empty list unassigns it
+#[update]
+pub fn assign_reviewers(
+    standard: String,
+    principals: Vec<Principal>,
+) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    if !SUPPORTED_STANDARDS
+        .iter()
+        .any(|supported| standard_key(supported) == standard_key(&standard))
+    {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::get_mut::<Reviewers>().assign(&standard, principals);
+    Ok(())
+}
+
+#[query]
+pub fn get_reviewers() -> Vec<(String, Vec<Principal>)> {
+    ic::get::<Reviewers>()
+        .0
+        .iter()
+        .map(|(standard, reviewers)| (standard.clone(), reviewers.clone()))
+        .collect()
+}
+
+// Pending submissions the caller reviews, oldest first. Admins get every submission.
+#[query]
+pub fn get_my_review_queue() -> Result<Vec<&'static Submission>, OperationError> {
+    let caller = ic::caller();
+    let reviewers = ic::get::<Reviewers>();
+    let admin = is_admin(&caller);
+    if !admin && !reviewers.0.values().any(|group| group.contains(&caller)) {
+        return Err(OperationError::not_authorized(Role::Reviewer, &caller));
+    }
+
+    let mut queue: Vec<&Submission> = ic::get::<Submissions>()
+        .get_all()
+        .into_iter()
+        .filter(|submission| admin || reviewers.reviews(&caller, submission))
+        .collect();
+    queue.sort_by_key(|submission| submission.submitted_at);
+    Ok(queue)
+}
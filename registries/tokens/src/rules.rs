@@ -0,0 +1,70 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+
+use crate::audit::check_ledger;
+use crate::common_types::*;
+
+// Submissions approved without waiting for an admin, see `ApprovalRule`
+#[derive(Default)]
+pub struct Rules(pub Vec<ApprovalRule>);
+
+// "ICRC-1" and "icrc1" name the same standard
+pub fn standard_key(standard: &str) -> String {
+    standard.replace('-', "").to_uppercase()
+}
+
+impl ApprovalRule {
+    fn matches(&self, submission: &Submission) -> bool {
+        let standard = detail(&submission.entry.details, "standard").and_then(DetailValue::as_text);
+
+        self.submitters.contains(&submission.submitter)
+            && standard.map(standard_key) == Some(standard_key(&self.standard))
+    }
+}
+
+impl Rules {
+    // Whether a rule approves `submission`. Rules asking for a ledger check only pass
+    // when the ledger reports the submitted symbol and decimals.
+    pub async fn approves(&self, submission: &Submission) -> bool {
+        let verify_ledger = match self.0.iter().find(|rule| rule.matches(submission)) {
+            Some(rule) => rule.verify_ledger,
+            None => return false,
+        };
+
+        !verify_ledger
+            || check_ledger(submission.entry.principal_id, &submission.entry.details)
+                .await
+                .is_empty()
+    }
+}
+
+// Replaces every rule, an empty list turns auto-approval off
+#[update]
+pub fn set_approval_rules(rules: Vec<ApprovalRule>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if rules
+        .iter()
+        .any(|rule| rule.standard.is_empty() || rule.submitters.is_empty())
+    {
+        return Err(OperationError::BadParameters);
+    } else if rules
+        .iter()
+        .any(|rule| rule.submitters.contains(&Principal::anonymous()))
+    {
+        return Err(OperationError::Anonymous);
+    }
+
+    ic::store(Rules(rules));
+    Ok(())
+}
+
+#[query]
+pub fn get_approval_rules() -> Vec<ApprovalRule> {
+    ic::get::<Rules>().0.clone()
+}
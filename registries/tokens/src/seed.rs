@@ -0,0 +1,56 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+
+use crate::common_types::*;
+use crate::tokens::TokenRegistry;
+use crate::validation::validate;
+
+fn seed_input(token: &::seed::SeedToken) -> AddTokenInput {
+    AddTokenInput {
+        name: token.name.to_string(),
+        description: token.description.to_string(),
+        thumbnail: token.thumbnail.to_string(),
+        frontend: token.frontend.map(String::from),
+        principal_id: Principal::from_text(token.principal_id).unwrap(),
+        details: vec![
+            (String::from("symbol"), DetailValue::from(token.symbol)),
+            (String::from("standard"), DetailValue::from(token.standard)),
+            (
+                String::from("total_supply"),
+                DetailValue::U64(token.total_supply),
+            ),
+            (String::from("verified"), DetailValue::True),
+            (String::from("decimals"), DetailValue::U64(token.decimals)),
+            (String::from("fee"), DetailValue::U64(token.fee)),
+        ],
+    }
+}
+
+// Adds the canonical mainnet tokens of the `seed` crate that aren't listed yet, returning
+// how many were added. Listed entries are left untouched, so calling it again is a no-op.
+#[update]
+pub fn load_seed() -> Result<u64, OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    let caller = ic::caller();
+    if !is_admin(&caller) {
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
+    }
+
+    let db = ic::get_mut::<TokenRegistry>();
+    let inputs = ::seed::TOKENS
+        .iter()
+        .map(seed_input)
+        .filter(|input| db.get_info(&input.principal_id).is_none())
+        .collect::<Vec<_>>();
+    inputs.iter().try_for_each(validate)?;
+
+    let added = inputs.len() as u64;
+    for input in inputs {
+        db.add(&caller, input)?;
+    }
+    Ok(added)
+}
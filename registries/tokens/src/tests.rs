@@ -265,3 +265,142 @@
 //         assert!(token.is_none());
 //     }
 // }
+
+// Archive/load and Candid round trips of random entries. Entries go through both on every
+// upgrade, so a change that loses or alters stored data fails here first.
+#[cfg(test)]
+mod round_trip_tests {
+    use ic_kit::candid::{Decode, Encode, Principal};
+    use proptest::collection::vec;
+    use proptest::option;
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+
+    use crate::common_types::*;
+    use crate::tokens::TokenRegistry;
+
+    fn principal() -> impl Strategy<Value = Principal> {
+        vec(any::<u8>(), 0..=29).prop_map(|bytes| Principal::from_slice(&bytes))
+    }
+
+    // Floats are kept finite, NaN isn't equal to itself
+    fn detail_value() -> impl Strategy<Value = DetailValue> {
+        let leaf = prop_oneof![
+            Just(DetailValue::True),
+            Just(DetailValue::False),
+            any::<u64>().prop_map(DetailValue::U64),
+            any::<i64>().prop_map(DetailValue::I64),
+            (-1e15f64..1e15).prop_map(DetailValue::Float),
+            ".*".prop_map(DetailValue::Text),
+            principal().prop_map(DetailValue::Principal),
+            vec(any::<u8>(), 0..64).prop_map(DetailValue::Slice),
+        ];
+        leaf.prop_recursive(3, 32, 4, |inner| {
+            vec(inner, 0..4).prop_map(DetailValue::Vec)
+        })
+    }
+
+    fn token() -> impl Strategy<Value = Token> {
+        (
+            (".*", ".*", ".*", option::of(".*")),
+            (principal(), principal(), principal(), any::<u64>()),
+            vec((".*", detail_value()), 0..8),
+            (
+                option::of(principal()),
+                option::of(principal()),
+                option::of(any::<bool>()),
+            ),
+        )
+            .prop_map(
+                |(
+                    (name, description, thumbnail, frontend),
+                    (principal_id, submitter, last_updated_by, last_updated_at),
+                    details,
+                    (resolved_from, managed_by, conflicted),
+                )| Token {
+                    name,
+                    description,
+                    thumbnail,
+                    frontend,
+                    principal_id,
+                    submitter,
+                    last_updated_by,
+                    last_updated_at,
+                    details,
+                    resolved_from,
+                    managed_by,
+                    conflicted,
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn test_archive_load_round_trip(tokens in vec(token(), 0..16)) {
+            let expected: BTreeMap<Principal, Token> = tokens
+                .into_iter()
+                .map(|token| (token.principal_id, token))
+                .collect();
+
+            let mut registry = TokenRegistry::default();
+            registry.load(expected.clone().into_iter().collect());
+            prop_assert_eq!(registry.len(), expected.len());
+
+            let archive = registry.archive();
+            prop_assert_eq!(registry.len(), 0);
+            prop_assert_eq!(archive.into_iter().collect::<BTreeMap<_, _>>(), expected);
+        }
+
+        #[test]
+        fn test_candid_round_trip(tokens in vec(token(), 0..16)) {
+            let archive: Vec<(Principal, Token)> = tokens
+                .into_iter()
+                .map(|token| (token.principal_id, token))
+                .collect();
+
+            let bytes = Encode!(&archive).unwrap();
+            let decoded = Decode!(&bytes, Vec<(Principal, Token)>).unwrap();
+            prop_assert_eq!(decoded, archive);
+        }
+    }
+}
+
+#[cfg(test)]
+mod format_amount_tests {
+    use crate::common_types::DECIMALS_LIMIT;
+    use crate::metadata::format_amount;
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(10_000, 8).as_deref(), Some("0.0001"));
+        assert_eq!(format_amount(150_000_000, 8).as_deref(), Some("1.5"));
+        assert_eq!(format_amount(42, 0).as_deref(), Some("42"));
+        assert_eq!(format_amount(0, 8).as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn test_format_amount_max_amount() {
+        assert_eq!(
+            format_amount(u64::MAX, 0).as_deref(),
+            Some("18446744073709551615")
+        );
+        assert_eq!(
+            format_amount(u64::MAX, 8).as_deref(),
+            Some("184467440737.09551615")
+        );
+        assert_eq!(
+            format_amount(u64::MAX, 20).as_deref(),
+            Some("0.18446744073709551615")
+        );
+    }
+
+    #[test]
+    fn test_format_amount_large_decimals() {
+        let formatted = format_amount(1, DECIMALS_LIMIT).unwrap();
+        assert_eq!(formatted.len(), 2 + DECIMALS_LIMIT as usize);
+        assert!(formatted.starts_with("0.") && formatted.ends_with("01"));
+
+        assert_eq!(format_amount(1, DECIMALS_LIMIT + 1), None);
+        assert_eq!(format_amount(u64::MAX, u64::MAX), None);
+    }
+}
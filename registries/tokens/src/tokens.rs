@@ -1,30 +1,139 @@
-use ic_kit::candid::Principal;
+use ic_cdk::api::call::RejectionCode;
+use ic_kit::candid::{encode_one, Principal};
 use ic_kit::macros::*;
 use ic_kit::*;
+use registry_framework::alerts::*;
+use registry_framework::aliases::*;
+use registry_framework::assets::{release, AssetRefs};
+use registry_framework::callbacks::{notify_owner, EntryCallbacks};
+use registry_framework::entries::EntryStore;
+use registry_framework::guard::check_principal;
+use registry_framework::integrity::Integrity;
+use registry_framework::interner::{StrId, StringPool};
+use registry_framework::locks::*;
+use registry_framework::logger::log_result;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+use registry_framework::messages::localize;
+use registry_framework::mirror::*;
+use registry_framework::pinning::Pins;
+use registry_framework::reports::Reports;
+use registry_framework::requests::Requests;
+use registry_framework::reserved::*;
+use registry_framework::translations::*;
+use registry_framework::usage::Usage;
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
-use validator::validate_url;
 
 use crate::common_types::*;
-use crate::management::*;
+use crate::delisting::Blocklist;
+use crate::history::*;
+use crate::index::*;
+use crate::metadata::{check_entry_limits, Metadata};
+use crate::protection::Protection;
+use crate::submissions::Submissions;
+use crate::validation::{check_external, validate};
 
 pub trait Object {
     fn type_name(&self) -> &str;
     fn as_any(&self) -> &dyn Any;
 }
 
+// Entries are sharded by their `standard` detail, so listing a single standard doesn't touch
+// the others. Shards are ordered by principal so listings are stable across calls and upgrades.
 #[derive(Default)]
-pub struct TokenRegistry(HashMap<Principal, Token>);
+pub struct TokenRegistry {
+    shards: BTreeMap<String, BTreeMap<Principal, Token>>,
+    standards: HashMap<Principal, StrId>,
+    pool: StringPool,
+}
+
+fn standard_of(token: &Token) -> String {
+    detail(&token.details, "standard")
+        .and_then(DetailValue::as_text)
+        .unwrap_or_default()
+        .to_string()
+}
 
 impl TokenRegistry {
     pub fn archive(&mut self) -> Vec<(Principal, Token)> {
-        let map = std::mem::replace(&mut self.0, HashMap::new());
-        map.into_iter().collect()
+        let registry = std::mem::take(self);
+        registry
+            .shards
+            .into_values()
+            .flat_map(|shard| shard.into_iter())
+            .collect()
     }
 
     pub fn load(&mut self, archive: Vec<(Principal, Token)>) {
-        self.0 = archive.into_iter().collect();
+        for (_, token) in archive {
+            self.insert(token);
+        }
+    }
+
+    fn insert(&mut self, token: Token) {
+        self.take(&token.principal_id);
+        let standard = standard_of(&token);
+        let id = self.pool.intern(&standard);
+        self.standards.insert(token.principal_id, id);
+        self.shards
+            .entry(standard)
+            .or_default()
+            .insert(token.principal_id, token);
+    }
+
+    // Stores the entry, releasing the assets its previous version was the last to reference
+    fn store(&mut self, token: Token) {
+        let previous = self
+            .get_info(&token.principal_id)
+            .map_or(&[][..], |previous| &previous.details);
+        let orphans =
+            ic::get_mut::<AssetRefs>().retrack(token.principal_id, previous, &token.details);
+        self.insert(token);
+        release::<Submissions>(orphans);
+    }
+
+    // Whether every entry is in the shard of its standard, and mapped to it in `standards`
+    fn is_sharded(&self) -> bool {
+        let count: usize = self.shards.values().map(BTreeMap::len).sum();
+        count == self.standards.len()
+            && self.shards.iter().all(|(standard, shard)| {
+                !shard.is_empty()
+                    && shard.values().all(|token| {
+                        standard_of(token) == *standard
+                            && self.standard(&token.principal_id) == Some(standard.as_str())
+                    })
+            })
+    }
+
+    // Shards the entries again when they aren't, returns whether it had to
+    pub fn reshard(&mut self) -> bool {
+        if self.is_sharded() {
+            return false;
+        }
+
+        let archive = self.archive();
+        self.load(archive);
+        true
+    }
+
+    fn take(&mut self, principal_id: &Principal) -> Option<Token> {
+        let standard = self.pool.get(self.standards.remove(principal_id)?);
+        let shard = self.shards.get_mut(standard)?;
+        let token = shard.remove(principal_id);
+        if shard.is_empty() {
+            self.shards.remove(standard);
+        }
+        token
+    }
+
+    pub fn len(&self) -> usize {
+        self.standards.len()
+    }
+
+    pub fn interned(&self) -> usize {
+        self.pool.len()
     }
 
     pub fn add(
@@ -32,11 +141,17 @@ impl TokenRegistry {
         caller: &Principal,
         token_info: AddTokenInput,
     ) -> Result<(), OperationError> {
-        let token = self.0.get(&token_info.principal_id);
+        ic::get::<Mirror>().check_writable()?;
+
+        let token = self.get_info(&token_info.principal_id);
+
+        if token.is_some() {
+            ic::get::<Locks>().check(&token_info.principal_id)?;
+        }
 
-        // If its an update, check if the caller matches the submitter or if its an admin
-        if token.is_some() && !is_admin(caller) && token.unwrap().submitter != *caller {
-            return Err(OperationError::NotAuthorized);
+        // If its an update, check if the caller manages the entry or if its an admin
+        if token.is_some() && !is_admin(caller) && token.unwrap().manager() != *caller {
+            return Err(OperationError::not_authorized(Role::Submitter, caller));
         }
         // An admin can update any entry
         else if token.is_some() && is_admin(caller) {
@@ -50,27 +165,89 @@ impl TokenRegistry {
                 last_updated_by: *caller,
                 last_updated_at: ic::time(),
                 details: token_info.details.clone(),
+                resolved_from: None,
+                managed_by: token.unwrap().managed_by,
+                conflicted: None,
             };
-
-            self.0.insert(token_info.principal_id, updated_token);
+            check_entry_limits(token, &updated_token)?;
+
+            store_event(EventKind::Update, &updated_token);
+            ic::get_mut::<SortIndex>().insert(&updated_token);
+            ic::get_mut::<FrontendIndex>().insert(&updated_token);
+            ic::get_mut::<ManagerIndex>().insert(&updated_token);
+            ic::get_mut::<PrincipalIndex>().insert(&updated_token);
+            ic::get_mut::<Integrity>().insert(&updated_token);
+            ic::get_mut::<SymbolIndex>().insert(&updated_token);
+            self.store(updated_token);
         }
         // Its a new entry
         else {
+            let kind = match token {
+                Some(_) => EventKind::Update,
+                None => EventKind::Add,
+            };
             let new_token = Token {
                 name: token_info.name,
                 description: token_info.description,
                 thumbnail: token_info.thumbnail,
                 frontend: token_info.frontend,
                 principal_id: token_info.principal_id,
-                submitter: *caller,
+                submitter: token.map_or(*caller, |token| token.submitter),
                 last_updated_by: *caller,
                 last_updated_at: ic::time(),
                 details: token_info.details.clone(),
+                resolved_from: None,
+                managed_by: token.and_then(|token| token.managed_by),
+                conflicted: None,
             };
+            check_entry_limits(token, &new_token)?;
+
+            store_event(kind, &new_token);
+            ic::get_mut::<SortIndex>().insert(&new_token);
+            ic::get_mut::<FrontendIndex>().insert(&new_token);
+            ic::get_mut::<ManagerIndex>().insert(&new_token);
+            ic::get_mut::<PrincipalIndex>().insert(&new_token);
+            ic::get_mut::<Integrity>().insert(&new_token);
+            ic::get_mut::<SymbolIndex>().insert(&new_token);
+            self.store(new_token);
+        }
+
+        ic::get_mut::<AlertSink>().check_capacity();
+        Ok(())
+    }
+
+    // Hands the management of an entry over to `new_owner`
+    pub fn transfer_ownership(
+        &mut self,
+        caller: &Principal,
+        principal_id: &Principal,
+        new_owner: Principal,
+    ) -> Result<(), OperationError> {
+        ic::get::<Mirror>().check_writable()?;
+
+        check_principal(&new_owner)?;
 
-            self.0.insert(token_info.principal_id, new_token);
+        let mut token = match self.get_info(principal_id) {
+            Some(token) => token.clone(),
+            None => return Err(OperationError::NonExistentItem),
+        };
+
+        ic::get::<Locks>().check(principal_id)?;
+
+        if token.manager() != *caller && !is_admin(caller) {
+            return Err(OperationError::not_authorized(Role::Submitter, caller));
         }
 
+        token.managed_by = Some(new_owner);
+        token.last_updated_by = *caller;
+        token.last_updated_at = ic::time();
+
+        store_event(EventKind::Update, &token);
+        ic::get_mut::<SortIndex>().insert(&token);
+        ic::get_mut::<ManagerIndex>().insert(&token);
+        ic::get_mut::<PrincipalIndex>().insert(&token);
+        ic::get_mut::<Integrity>().insert(&token);
+        self.store(token);
         Ok(())
     }
 
@@ -79,33 +256,257 @@ impl TokenRegistry {
         caller: &Principal,
         principal_id: &Principal,
     ) -> Result<(), OperationError> {
-        if !self.0.contains_key(principal_id) {
-            return Err(OperationError::NonExistentItem);
-        }
+        ic::get::<Mirror>().check_writable()?;
+
+        let token = match self.get_info(principal_id) {
+            Some(token) => token,
+            None => return Err(OperationError::NonExistentItem),
+        };
 
-        let token = self.0.get(principal_id).unwrap();
+        ic::get::<Locks>().check(principal_id)?;
 
-        if token.submitter != *caller && !is_admin(caller) {
-            return Err(OperationError::NotAuthorized);
+        if token.manager() != *caller && !is_admin(caller) {
+            return Err(OperationError::not_authorized(Role::Submitter, caller));
         }
 
-        self.0.remove(principal_id);
+        let removed = self.take(principal_id).unwrap();
+        store_event(EventKind::Remove, &removed);
+        raise(AlertKind::EntryRemoved {
+            principal_id: *principal_id,
+            by: *caller,
+        });
+        ic::get_mut::<SortIndex>().remove(principal_id);
+        ic::get_mut::<FrontendIndex>().remove(principal_id);
+        ic::get_mut::<ManagerIndex>().remove(principal_id);
+        ic::get_mut::<PrincipalIndex>().remove(principal_id);
+        ic::get_mut::<Integrity>().remove(principal_id);
+        ic::get_mut::<SymbolIndex>().remove(principal_id);
+        ic::get_mut::<Translations>().remove(principal_id);
+        ic::get_mut::<Reports>().clear(principal_id);
+        ic::get_mut::<Pins>().unpin(principal_id);
+        ic::get_mut::<EntryCallbacks>().0.remove(principal_id);
+        release::<Submissions>(ic::get_mut::<AssetRefs>().untrack(principal_id, &removed.details));
 
         return Ok(());
     }
 
+    // Removes an entry regardless of its locks and manager, see `emergency_delist`
+    pub fn delist(&mut self, principal_id: &Principal) -> Result<Token, OperationError> {
+        ic::get::<Mirror>().check_writable()?;
+
+        let removed = self
+            .take(principal_id)
+            .ok_or(OperationError::NonExistentItem)?;
+        store_event(EventKind::Delist, &removed);
+        ic::get_mut::<SortIndex>().remove(principal_id);
+        ic::get_mut::<FrontendIndex>().remove(principal_id);
+        ic::get_mut::<ManagerIndex>().remove(principal_id);
+        ic::get_mut::<PrincipalIndex>().remove(principal_id);
+        ic::get_mut::<Integrity>().remove(principal_id);
+        ic::get_mut::<SymbolIndex>().remove(principal_id);
+        ic::get_mut::<Translations>().remove(principal_id);
+        ic::get_mut::<Reports>().clear(principal_id);
+        ic::get_mut::<Pins>().unpin(principal_id);
+        release::<Submissions>(ic::get_mut::<AssetRefs>().untrack(principal_id, &removed.details));
+        Ok(removed)
+    }
+
+    pub fn standard(&self, principal_id: &Principal) -> Option<&str> {
+        let id = self.standards.get(principal_id)?;
+        Some(self.pool.get(*id))
+    }
+
     pub fn get_info(&self, principal_id: &Principal) -> Option<&Token> {
-        self.0.get(principal_id)
+        let standard = self.standard(principal_id)?;
+        self.shards.get(standard)?.get(principal_id)
+    }
+
+    // Replays an event of the upstream registry, skipping validation and authorization
+    pub fn apply(&mut self, event: Event) {
+        let principal_id = event.entry.principal_id;
+        store_event(event.kind, &event.entry);
+
+        match event.kind {
+            EventKind::Remove | EventKind::Delist => {
+                if let Some(removed) = self.take(&principal_id) {
+                    release::<Submissions>(
+                        ic::get_mut::<AssetRefs>().untrack(&principal_id, &removed.details),
+                    );
+                }
+                ic::get_mut::<SortIndex>().remove(&principal_id);
+                ic::get_mut::<FrontendIndex>().remove(&principal_id);
+                ic::get_mut::<ManagerIndex>().remove(&principal_id);
+                ic::get_mut::<PrincipalIndex>().remove(&principal_id);
+                ic::get_mut::<Integrity>().remove(&principal_id);
+                ic::get_mut::<SymbolIndex>().remove(&principal_id);
+                ic::get_mut::<Translations>().remove(&principal_id);
+                ic::get_mut::<Reports>().clear(&principal_id);
+                ic::get_mut::<Pins>().unpin(&principal_id);
+            }
+            EventKind::Add | EventKind::Update => {
+                ic::get_mut::<SortIndex>().insert(&event.entry);
+                ic::get_mut::<FrontendIndex>().insert(&event.entry);
+                ic::get_mut::<ManagerIndex>().insert(&event.entry);
+                ic::get_mut::<PrincipalIndex>().insert(&event.entry);
+                ic::get_mut::<Integrity>().insert(&event.entry);
+                ic::get_mut::<SymbolIndex>().insert(&event.entry);
+                self.store(event.entry);
+            }
+        }
+    }
+
+    // Renames the `old` detail of every entry to `new` and returns how many entries hold it.
+    // Nothing is written on a dry run, which fails the same way the rename would.
+    pub fn rename_detail_key(
+        &mut self,
+        caller: &Principal,
+        old: &str,
+        new: &str,
+        dry_run: bool,
+    ) -> Result<u64, OperationError> {
+        ic::get::<Mirror>().check_writable()?;
+
+        let touched: Vec<Principal> = self
+            .get_all()
+            .into_iter()
+            .filter(|token| detail(&token.details, old).is_some())
+            .map(|token| token.principal_id)
+            .collect();
+
+        for principal_id in touched.iter() {
+            ic::get::<Locks>().check(principal_id)?;
+            // Entries holding both keys would lose one of the values
+            if detail(&self.get_info(principal_id).unwrap().details, new).is_some() {
+                return Err(OperationError::BadParameters);
+            }
+        }
+
+        if dry_run {
+            return Ok(touched.len() as u64);
+        }
+
+        for principal_id in touched.iter() {
+            let mut token = self.get_info(principal_id).unwrap().clone();
+            for (key, _) in token.details.iter_mut().filter(|(key, _)| key == old) {
+                *key = String::from(new);
+            }
+            token.last_updated_by = *caller;
+            token.last_updated_at = ic::time();
+
+            store_event(EventKind::Update, &token);
+            ic::get_mut::<SortIndex>().insert(&token);
+            ic::get_mut::<FrontendIndex>().insert(&token);
+            ic::get_mut::<ManagerIndex>().insert(&token);
+            ic::get_mut::<PrincipalIndex>().insert(&token);
+            ic::get_mut::<Integrity>().insert(&token);
+            ic::get_mut::<SymbolIndex>().insert(&token);
+            self.store(token);
+        }
+
+        Ok(touched.len() as u64)
     }
 
     pub fn get_all(&self) -> Vec<&Token> {
-        self.0.values().collect()
+        self.shards
+            .values()
+            .flat_map(|shard| shard.values())
+            .collect()
+    }
+
+    // Entries of one standard, `offset` and `limit` apply within the standard
+    pub fn get_page(&self, standard: Option<&str>, offset: usize, limit: usize) -> Vec<&Token> {
+        match standard {
+            Some(standard) => match self.shards.get(standard) {
+                Some(shard) => shard.values().skip(offset).take(limit).collect(),
+                None => vec![],
+            },
+            None => self
+                .shards
+                .values()
+                .flat_map(|shard| shard.values())
+                .skip(offset)
+                .take(limit)
+                .collect(),
+        }
+    }
+
+    pub fn list(&self, options: ListOptions) -> ListPage {
+        let entries = ic::get::<SortIndex>()
+            .sorted(options.sort_by, options.direction)
+            .skip(options.offset as usize)
+            .take(options.limit as usize)
+            .filter_map(|principal_id| self.get_info(&principal_id).cloned())
+            .collect();
+
+        ListPage {
+            entries,
+            total: self.len() as u64,
+        }
+    }
+}
+
+impl EntryStore for TokenRegistry {
+    type Entry = Token;
+
+    fn entry(&self, principal_id: &Principal) -> Option<&Token> {
+        self.get_info(principal_id)
+    }
+
+    fn principals(&self) -> Vec<Principal> {
+        self.get_all()
+            .into_iter()
+            .map(|token| token.principal_id)
+            .collect()
+    }
+
+    fn replay(&mut self, event: Event) {
+        self.apply(event)
+    }
+}
+
+// Router consulted for cross-registry conflicts before a new principal is accepted.
+// Enforcement stays disabled while no router is set.
+#[derive(Default)]
+pub struct ConflictRouter(pub Option<Principal>);
+
+// Probes `principal_id` with a method no canister implements. Only a call to a principal
+// that isn't an existing canister is rejected as an invalid destination.
+async fn check_live_canister(principal_id: Principal) -> Result<(), OperationError> {
+    let result: Result<(), _> = ic::call(principal_id, "dab_liveness_probe", ()).await;
+    match result {
+        // The principal is not a live canister
+        Err((RejectionCode::DestinationInvalid, _)) => Err(OperationError::BadParameters),
+        _ => Ok(()),
+    }
+}
+
+async fn check_conflicts(principal_id: Principal) -> Result<(), OperationError> {
+    let router = match ic::get::<ConflictRouter>().0 {
+        Some(router) => router,
+        None => return Ok(()),
+    };
+
+    let conflicts: Vec<Conflict> = match ic::call(router, "check_conflicts", (principal_id,)).await
+    {
+        Ok((x,)) => x,
+        Err((_code, msg)) => {
+            return Err(OperationError::Unknown(msg));
+        }
+    };
+
+    match conflicts
+        .iter()
+        .find(|conflict| conflict.registry != ic::id())
+    {
+        Some(conflict) => Err(OperationError::Conflict(conflict.registry)),
+        None => Ok(()),
     }
 }
 
 #[init]
 pub fn init() {
     ic::store(Admins(vec![ic::caller()]));
+    ic::get::<Integrity>().certify();
 }
 
 #[query]
@@ -113,41 +514,101 @@ pub fn name() -> String {
     String::from("Token Registry Canister")
 }
 
+#[query]
+pub fn registry_info() -> RegistryInfo {
+    let db = ic::get::<TokenRegistry>();
+    let entries = db.get_all();
+
+    // Removals only show up in the history, entries stored before it existed only in the db
+    let last_modified_at = std::cmp::max(
+        ic::get::<History>()
+            .0
+            .last()
+            .map(|event| event.time)
+            .unwrap_or_default(),
+        entries
+            .iter()
+            .map(|entry| entry.last_updated_at)
+            .max()
+            .unwrap_or_default(),
+    );
+
+    RegistryInfo {
+        name: name(),
+        version: String::from(env!("CARGO_PKG_VERSION")),
+        entry_count: entries.len() as u64,
+        supported_standards: SUPPORTED_STANDARDS.iter().map(|s| s.to_string()).collect(),
+        admins: ic::get::<Admins>().0.clone(),
+        last_modified_at,
+    }
+}
+
+// Lets clients detect optional endpoints instead of assuming them from the registry
+#[query]
+pub fn supported_features() -> Vec<String> {
+    SUPPORTED_FEATURES.iter().map(|s| s.to_string()).collect()
+}
+
+// Calls retried with the same `request_id` return the result of the first one
 #[update]
 pub async fn add(
     trusted_source: Option<Principal>,
     token: AddTokenInput,
+    request_id: Option<Vec<u8>>,
 ) -> Result<(), OperationError> {
+    ic::get_mut::<Usage>().record("add");
+    ic::get::<Maintenance>().check()?;
+
     // Check authorization
     let caller = ic::caller();
 
     if !is_admin(&caller) {
-        return Err(OperationError::NotAuthorized);
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
     }
+    trusted_source.as_ref().map_or(Ok(()), check_principal)?;
 
-    // Check URLs
-    if !validate_url(&token.thumbnail) || !token.clone().frontend.map(validate_url).unwrap_or(true)
-    {
-        return Err(OperationError::BadParameters);
+    if let Some(result) = ic::get_mut::<Requests>().start(&caller, &request_id) {
+        return localize(result);
     }
 
-    // Check Character Limits
-    let name = token.name.clone();
-    if name.len() > 120 && token.description.len() > 1200 {
-        return Err(OperationError::BadParameters);
+    let principal_id = token.principal_id;
+    let listed = ic::get::<TokenRegistry>().get_info(&principal_id).is_some();
+    let protection = ic::get_mut::<Protection>();
+    let result = match protection.is_protected(&principal_id) {
+        true => Err(protection.defer(principal_id, trusted_source, ProposedChange::Update(token))),
+        false => add_entry(trusted_source.unwrap_or(caller), trusted_source, token).await,
+    };
+    if listed && result.is_ok() {
+        notify_owner(principal_id, EntryStatus::EditedByAdmin);
     }
+    ic::get_mut::<Requests>().finish(&caller, &request_id, &result);
+    log_result("add", caller, &result);
+    localize(result)
+}
 
-    // Check details
-    if token.details.len() != 6
-        || token.details[0].0 != String::from("symbol")
-        || token.details[1].0 != String::from("standard")
-        || token.details[2].0 != String::from("total_supply")
-        || token.details[3].0 != String::from("verified")
-        || (token.details[3].1 != DetailValue::True && token.details[3].1 != DetailValue::False)
-        || token.details[4].0 != String::from("decimals")
-        || token.details[5].0 != String::from("fee")
+// Validates and stores a token on behalf of `submitter`.
+// Authorizing the call is up to the caller of this function.
+pub async fn add_entry(
+    submitter: Principal,
+    trusted_source: Option<Principal>,
+    token: AddTokenInput,
+) -> Result<(), OperationError> {
+    ic::get::<Blocklist>().check(&token.principal_id)?;
+    // Check reserved names
+    ic::get::<ReservedNames>().check(&token.name, &submitter, &token.principal_id)?;
+    ic::get::<Mirror>().check_writable()?;
+    ic::get::<Locks>().check(&token.principal_id)?;
+
+    validate(&token)?;
+    check_external(&token).await?;
+
+    // Check the token is a canister not listed by other registries
+    if ic::get::<TokenRegistry>()
+        .get_info(&token.principal_id)
+        .is_none()
     {
-        return Err(OperationError::BadParameters);
+        check_live_canister(token.principal_id).await?;
+        check_conflicts(token.principal_id).await?;
     }
 
     // Add the collection to the canister registry
@@ -171,32 +632,268 @@ pub async fn add(
     };
 
     let db = ic::get_mut::<TokenRegistry>();
-    return db.add(&trusted_source.unwrap_or(caller), token);
+    return db.add(&submitter, token);
 }
 
 #[update]
 pub fn remove(
     trusted_source: Option<Principal>,
     principal_id: Principal,
+    request_id: Option<Vec<u8>>,
 ) -> Result<(), OperationError> {
+    ic::get_mut::<Usage>().record("remove");
+    ic::get::<Maintenance>().check()?;
+
     let caller = ic::caller();
 
     if !is_admin(&caller) {
-        return Err(OperationError::NotAuthorized);
+        return Err(OperationError::not_authorized(Role::Admin, &caller));
     }
+    trusted_source.as_ref().map_or(Ok(()), check_principal)?;
 
-    let db = ic::get_mut::<TokenRegistry>();
-    return db.remove(&trusted_source.unwrap_or(caller), &principal_id);
+    if let Some(result) = ic::get_mut::<Requests>().start(&caller, &request_id) {
+        return localize(result);
+    }
+
+    let protection = ic::get_mut::<Protection>();
+    let result = match protection.is_protected(&principal_id) {
+        true => Err(protection.defer(principal_id, trusted_source, ProposedChange::Remove)),
+        false => {
+            let db = ic::get_mut::<TokenRegistry>();
+            db.remove(&trusted_source.unwrap_or(caller), &principal_id)
+        }
+    };
+    ic::get_mut::<Requests>().finish(&caller, &request_id, &result);
+    log_result("remove", caller, &result);
+    localize(result)
+}
+
+#[update]
+pub fn transfer_entry_ownership(
+    principal_id: Principal,
+    new_owner: Principal,
+) -> Result<(), OperationError> {
+    ic::get_mut::<Usage>().record("transfer_entry_ownership");
+    ic::get::<Maintenance>().check()?;
+
+    localize(ic::get_mut::<TokenRegistry>().transfer_ownership(
+        &ic::caller(),
+        &principal_id,
+        new_owner,
+    ))
+}
+
+// Whether a verified entry added before `token` uses the same symbol
+fn is_conflicted(db: &TokenRegistry, token: &Token) -> bool {
+    let symbol = match symbol_key(token) {
+        Some(symbol) => symbol,
+        None => return false,
+    };
+
+    let index = ic::get::<SortIndex>();
+    let added_at = |principal_id: Principal| {
+        (
+            index.added_at(&principal_id).unwrap_or(u64::MAX),
+            principal_id,
+        )
+    };
+
+    ic::get::<SymbolIndex>()
+        .get(&symbol)
+        .into_iter()
+        .filter(|principal_id| added_at(*principal_id) < added_at(token.principal_id))
+        .filter_map(|principal_id| db.get_info(&principal_id))
+        .any(|other| Metadata::parse(&other.details).map_or(false, |metadata| metadata.verified()))
 }
 
 #[query]
-pub fn get(principal_id: Principal) -> Option<&'static Token> {
-    let db = ic::get_mut::<TokenRegistry>();
-    db.get_info(&principal_id)
+pub fn get(principal_id: Principal) -> Option<Token> {
+    let db = ic::get::<TokenRegistry>();
+    let mut entry = match db.get_info(&principal_id) {
+        Some(entry) => entry.clone(),
+        None => {
+            // Legacy principals resolve to the entry they were redeployed as
+            let new = ic::get::<Aliases>().resolve(&principal_id)?;
+            let mut entry = db.get_info(&new)?.clone();
+            entry.resolved_from = Some(principal_id);
+            entry
+        }
+    };
+
+    entry.conflicted = Some(is_conflicted(db, &entry));
+    Some(entry)
 }
 
+// Standard of the entry, or `None` when `principal_id` isn't listed. Legacy principals
+// resolve like in `get`, without the cost of cloning the whole entry.
 #[query]
-pub fn get_all() -> Vec<&'static Token> {
-    let db = ic::get_mut::<TokenRegistry>();
-    db.get_all()
+pub fn exists(principal_id: Principal) -> Option<String> {
+    let db = ic::get::<TokenRegistry>();
+    db.standard(&principal_id)
+        .or_else(|| db.standard(&ic::get::<Aliases>().resolve(&principal_id)?))
+        .map(String::from)
+}
+
+// Symbols shared by several entries, one of them is likely impersonating another
+#[query]
+pub fn get_symbol_collisions() -> Vec<SymbolCollision> {
+    ic::get::<SymbolIndex>().collisions()
+}
+
+// Every entry, or the entries of `standard`, optionally paginated
+#[query]
+pub fn get_all(standard: Option<String>, page: Option<Page>) -> Vec<&'static Token> {
+    let db = ic::get::<TokenRegistry>();
+    let page = page.unwrap_or(Page {
+        offset: 0,
+        limit: u64::MAX,
+    });
+    db.get_page(
+        standard.as_deref(),
+        page.offset as usize,
+        page.limit as usize,
+    )
+}
+
+// Principals of every entry in the order of `get_all`, for clients that only check
+// membership or `get` entries one by one
+#[query]
+pub fn get_all_principals() -> Vec<Principal> {
+    get_principals(Page {
+        offset: 0,
+        limit: u64::MAX,
+    })
+}
+
+#[query]
+pub fn get_principals(page: Page) -> Vec<Principal> {
+    ic::get::<TokenRegistry>()
+        .get_page(None, page.offset as usize, page.limit as usize)
+        .into_iter()
+        .map(|token| token.principal_id)
+        .collect()
+}
+
+// Encoded size of the replies of `get_all_v2`, editable by admins without an upgrade
+pub struct ResponseBudget(pub u64);
+
+impl Default for ResponseBudget {
+    fn default() -> Self {
+        ResponseBudget(DEFAULT_RESPONSE_BUDGET)
+    }
+}
+
+// `get_all` cut to the response budget, so large registries don't fail to reply
+#[query]
+pub fn get_all_v2(standard: Option<String>, page: Option<Page>) -> GetAllResponse {
+    let entries = get_all(standard, page);
+    let budget = ic::get::<ResponseBudget>().0;
+
+    let mut size = 0u64;
+    let mut response = GetAllResponse {
+        entries: Vec::with_capacity(entries.len()),
+        truncated: false,
+    };
+    for entry in entries {
+        let bytes = encode_one(entry).map_or(u64::MAX, |bytes| bytes.len() as u64);
+        size = size.saturating_add(bytes);
+        if size > budget {
+            response.truncated = true;
+            break;
+        }
+        response.entries.push(entry.clone());
+    }
+
+    response
+}
+
+#[update]
+pub fn set_response_budget(bytes: u64) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    } else if bytes == 0 {
+        return Err(OperationError::BadParameters);
+    }
+
+    ic::store(ResponseBudget(bytes));
+    Ok(())
+}
+
+#[query]
+pub fn get_response_budget() -> u64 {
+    ic::get::<ResponseBudget>().0
+}
+
+#[update]
+pub fn set_conflict_router(router: Option<Principal>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    ic::get_mut::<ConflictRouter>().0 = router;
+    Ok(())
+}
+
+#[query]
+pub fn list(options: ListOptions) -> ListPage {
+    ic::get::<TokenRegistry>().list(options)
+}
+
+// `get` and `list` tagged with the version of the entry shape
+#[query]
+pub fn get_v2(principal_id: Principal) -> Envelope<Option<Token>> {
+    Envelope::new(ENTRY_SCHEMA_VERSION, get(principal_id))
+}
+
+#[query]
+pub fn list_v2(options: ListOptions) -> Envelope<ListPage> {
+    Envelope::new(ENTRY_SCHEMA_VERSION, list(options))
+}
+
+// Entries whose frontend is served from the host of `host`, which may be given as a full URL
+#[query]
+pub fn get_by_frontend_host(host: String) -> Vec<&'static Token> {
+    let db = ic::get::<TokenRegistry>();
+    let host = url_host(&host).unwrap_or_default();
+    ic::get::<FrontendIndex>()
+        .get(&host)
+        .iter()
+        .filter_map(|principal_id| db.get_info(principal_id))
+        .collect()
+}
+
+// Entries managed by `owner`, through an ownership transfer or as their submitter
+#[query]
+pub fn get_entries_managed_by(owner: Principal) -> Vec<&'static Token> {
+    let db = ic::get::<TokenRegistry>();
+    ic::get::<ManagerIndex>()
+        .get(&owner)
+        .iter()
+        .filter_map(|principal_id| db.get_info(principal_id))
+        .collect()
+}
+
+// Up to `SEARCH_LIMIT` entries whose textual principal starts with `prefix`
+#[query]
+pub fn search_principal(prefix: String) -> Vec<&'static Token> {
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.is_empty() {
+        return vec![];
+    }
+
+    let db = ic::get::<TokenRegistry>();
+    ic::get::<PrincipalIndex>()
+        .search(&prefix, SEARCH_LIMIT)
+        .iter()
+        .filter_map(|principal_id| db.get_info(principal_id))
+        .collect()
+}
+
+#[query]
+pub fn get_changes(since_event_id: u64, limit: u16) -> Result<Vec<&'static Event>, OperationError> {
+    ic::get::<History>().get_changes(since_event_id, limit)
 }
@@ -0,0 +1,94 @@
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use registry_framework::maintenance::Maintenance;
+use registry_framework::management::*;
+use validator::validate_url;
+
+use crate::common_types::*;
+use crate::metadata::{validate_links, validate_origin, Metadata};
+
+// Collects every problem of an input instead of stopping at the first one
+#[derive(Default)]
+pub struct Validator(Vec<FieldError>);
+
+impl Validator {
+    pub fn check(&mut self, valid: bool, field: &str, reason: &str) {
+        if !valid {
+            self.0.push(FieldError {
+                field: String::from(field),
+                reason: String::from(reason),
+            });
+        }
+    }
+
+    pub fn finish(self) -> Result<(), OperationError> {
+        match self.0.is_empty() {
+            true => Ok(()),
+            false => Err(OperationError::Validation(self.0)),
+        }
+    }
+}
+
+// Checks the fields of an entry that don't depend on other canisters
+pub fn validate(input: &AddTokenInput) -> Result<(), OperationError> {
+    let mut validator = Validator::default();
+    validator.check(input.name.len() <= NAME_LIMIT, "name", "too long");
+    validator.check(
+        input.description.len() <= DESCRIPTION_LIMIT,
+        "description",
+        "too long",
+    );
+    validator.check(validate_url(&input.thumbnail), "thumbnail", "invalid URL");
+    if let Some(frontend) = &input.frontend {
+        validator.check(validate_url(frontend), "frontend", "invalid URL");
+    }
+
+    let metadata = Metadata::check(&input.details, &mut validator);
+    validate_origin(&metadata, &mut validator);
+    validate_links(&metadata, &mut validator);
+    validator.finish()
+}
+
+// Canister applying an organization's own policy on top of `validate`, see `check_external`
+#[derive(Default)]
+pub struct ExternalValidator(pub Option<Principal>);
+
+// Asks the external validator, when one is set, to accept the entry. It implements
+// `validate : (add_token_input) -> (variant { Ok; Err : text })`, a rejection is reported
+// on the `entry` field. Entries can't be written while the validator is unreachable.
+pub async fn check_external(input: &AddTokenInput) -> Result<(), OperationError> {
+    let validator = match ic::get::<ExternalValidator>().0 {
+        Some(validator) => validator,
+        None => return Ok(()),
+    };
+
+    let result: Result<(), String> = match ic::call(validator, "validate", (input,)).await {
+        Ok((result,)) => result,
+        Err((_code, msg)) => return Err(OperationError::Unknown(msg)),
+    };
+
+    result.map_err(|reason| {
+        OperationError::Validation(vec![FieldError {
+            field: String::from("entry"),
+            reason,
+        }])
+    })
+}
+
+#[update]
+pub fn set_external_validator(validator: Option<Principal>) -> Result<(), OperationError> {
+    ic::get::<Maintenance>().check()?;
+
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::not_authorized(Role::Admin, &ic::caller()));
+    }
+
+    ic::get_mut::<ExternalValidator>().0 = validator;
+    Ok(())
+}
+
+#[query]
+pub fn get_external_validator() -> Option<Principal> {
+    ic::get::<ExternalValidator>().0
+}
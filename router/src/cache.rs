@@ -0,0 +1,118 @@
+use crate::common_types::*;
+use crate::management::*;
+use crate::router::Registries;
+
+use ic_kit::candid::Principal;
+use ic_kit::macros::*;
+use ic_kit::*;
+use std::collections::HashMap;
+
+const DEFAULT_CACHE_TTL: u64 = 5 * 60 * 1_000_000_000;
+const CACHE_LIMIT: usize = 10_000;
+
+// Answers of the registries to `get`, keyed by registry and principal. Unlisted principals
+// are cached too. An answer is reused until it is `ttl` nanoseconds old or a registry
+// invalidates the principal, a `ttl` of 0 turns caching off.
+pub struct Cache {
+    ttl: u64,
+    entries: HashMap<(Principal, Principal), (Option<RegistryEntry>, u64)>,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Cache {
+            ttl: DEFAULT_CACHE_TTL,
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl Cache {
+    pub fn ttl(&self) -> u64 {
+        self.ttl
+    }
+
+    pub fn set_ttl(&mut self, ttl: u64) {
+        self.ttl = ttl;
+        self.entries.clear();
+    }
+
+    fn get(&self, registry: Principal, principal_id: Principal) -> Option<Option<RegistryEntry>> {
+        let (entry, fetched_at) = self.entries.get(&(registry, principal_id))?;
+        match ic::time().saturating_sub(*fetched_at) < self.ttl {
+            true => Some(entry.clone()),
+            false => None,
+        }
+    }
+
+    fn insert(
+        &mut self,
+        registry: Principal,
+        principal_id: Principal,
+        entry: Option<RegistryEntry>,
+    ) {
+        if self.ttl == 0 {
+            return;
+        }
+
+        let now = ic::time();
+        if self.entries.len() >= CACHE_LIMIT {
+            let ttl = self.ttl;
+            self.entries
+                .retain(|_, (_, fetched_at)| now.saturating_sub(*fetched_at) < ttl);
+        }
+        if self.entries.len() >= CACHE_LIMIT {
+            self.entries.clear();
+        }
+        self.entries.insert((registry, principal_id), (entry, now));
+    }
+
+    pub fn invalidate(&mut self, principal_id: &Principal) {
+        self.entries.retain(|(_, cached), _| cached != principal_id);
+    }
+}
+
+// `get` of `registry` for `principal_id`, answered from the cache while it is fresh.
+// Failed calls aren't cached.
+pub async fn cached_get(
+    registry: Principal,
+    principal_id: Principal,
+) -> Result<Option<RegistryEntry>, String> {
+    if let Some(entry) = ic::get::<Cache>().get(registry, principal_id) {
+        return Ok(entry);
+    }
+
+    let (entry,) = ic::call::<_, (Option<RegistryEntry>,), _>(registry, "get", (principal_id,))
+        .await
+        .map_err(|(_, message)| message)?;
+    ic::get_mut::<Cache>().insert(registry, principal_id, entry.clone());
+    Ok(entry)
+}
+
+// Called by the registered registries whenever one of their entries changes
+#[update]
+pub fn invalidate(principal_id: Principal) -> Result<(), OperationError> {
+    let caller = ic::caller();
+    if ic::get::<Registries>().get(&caller).is_none() && !is_admin(&caller) {
+        return Err(OperationError::NotAuthorized);
+    }
+
+    ic::get_mut::<Cache>().invalidate(&principal_id);
+    Ok(())
+}
+
+// Replacing the TTL drops every cached answer
+#[update]
+pub fn set_cache_ttl(ttl: u64) -> Result<(), OperationError> {
+    if !is_admin(&ic::caller()) {
+        return Err(OperationError::NotAuthorized);
+    }
+
+    ic::get_mut::<Cache>().set_ttl(ttl);
+    Ok(())
+}
+
+#[query]
+pub fn get_cache_ttl() -> u64 {
+    ic::get::<Cache>().ttl()
+}
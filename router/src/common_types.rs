@@ -25,6 +25,55 @@ pub struct Registry {
     pub details: Vec<(String, DetailValue)>,
 }
 
+// The subset of a registry entry the router needs when looking a principal up
+// across registries. Candid ignores the extra fields each registry returns.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RegistryEntry {
+    pub name: String,
+    pub principal_id: Principal,
+    pub details: Vec<(String, DetailValue)>,
+    // Optional so registries returning entries without them still decode
+    pub thumbnail: Option<String>,
+    pub frontend: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EntryKind {
+    Token,
+    Nft,
+    // Listed without the details of a token or a collection
+    Canister,
+}
+
+// The `verified` detail of the entry, `Unknown` when the registry doesn't set one
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Verification {
+    Verified,
+    Unverified,
+    Unknown,
+}
+
+// A registry entry in the same shape whichever registry lists it, see `resolve`
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResolvedEntry {
+    pub principal_id: Principal,
+    pub registry: Principal,
+    pub kind: EntryKind,
+    pub name: String,
+    pub symbol: Option<String>,
+    pub standard: Option<String>,
+    pub thumbnail: Option<String>,
+    pub verification: Verification,
+    pub frontend: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub registry: Principal,
+    pub registry_name: String,
+    pub entry_name: String,
+}
+
 #[derive(CandidType, Debug)]
 pub enum OperationError {
     NotAuthorized,
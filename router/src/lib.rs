@@ -1,3 +1,4 @@
+mod cache;
 mod common_types;
 mod management;
 mod router;
@@ -1,9 +1,11 @@
+use crate::cache::cached_get;
 use crate::common_types::*;
 use crate::management::*;
 
 use ic_kit::candid::Principal;
 use ic_kit::macros::*;
 use ic_kit::*;
+use standards::{UserBalance, UserNft};
 use std::collections::HashMap;
 use validator::validate_url;
 
@@ -93,3 +95,164 @@ pub fn get_all() -> Vec<&'static Registry> {
     let db = ic::get_mut::<Registries>();
     db.get_all()
 }
+
+// Returns every other registered registry listing `principal_id` as a token or a collection.
+// Registries call this before accepting a new entry when conflict enforcement is enabled, so
+// the calling registry is left out, and so are plain canister listings such as the ones of the
+// canister registry, which the token and NFT registries add themselves.
+#[update]
+pub async fn check_conflicts(principal_id: Principal) -> Vec<Conflict> {
+    let caller = ic::caller();
+    let registries: Vec<(Principal, String)> = ic::get::<Registries>()
+        .get_all()
+        .iter()
+        .filter(|registry| registry.principal_id != caller)
+        .map(|registry| (registry.principal_id, registry.name.clone()))
+        .collect();
+
+    let mut conflicts = vec![];
+    for (registry, registry_name) in registries {
+        // Registries that can't be reached are skipped rather than failing the whole lookup
+        if let Ok(Some(entry)) = cached_get(registry, principal_id).await {
+            let entry = normalize(registry, entry);
+            if entry.kind == EntryKind::Canister {
+                continue;
+            }
+
+            conflicts.push(Conflict {
+                registry,
+                registry_name,
+                entry_name: entry.name,
+            });
+        }
+    }
+
+    conflicts
+}
+
+// Finds the first registered registry listing `principal_id`, and its entry
+async fn lookup(principal_id: Principal) -> Option<(Principal, RegistryEntry)> {
+    let registries: Vec<Principal> = ic::get::<Registries>()
+        .get_all()
+        .iter()
+        .map(|registry| registry.principal_id)
+        .collect();
+
+    for registry in registries {
+        if let Ok(Some(entry)) = cached_get(registry, principal_id).await {
+            return Some((registry, entry));
+        }
+    }
+
+    None
+}
+
+fn detail<'a>(entry: &'a RegistryEntry, key: &str) -> Option<&'a DetailValue> {
+    entry
+        .details
+        .iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value)
+}
+
+fn text_detail(entry: &RegistryEntry, key: &str) -> Option<String> {
+    match detail(entry, key) {
+        Some(DetailValue::Text(text)) => Some(text.clone()),
+        _ => None,
+    }
+}
+
+// Token entries are the ones carrying `decimals`, other entries with a standard are
+// NFT collections
+pub fn normalize(registry: Principal, entry: RegistryEntry) -> ResolvedEntry {
+    let standard = text_detail(&entry, "standard");
+    let kind = match (detail(&entry, "decimals"), &standard) {
+        (Some(_), _) => EntryKind::Token,
+        (None, Some(_)) => EntryKind::Nft,
+        (None, None) => EntryKind::Canister,
+    };
+    let verification = match detail(&entry, "verified") {
+        Some(DetailValue::True) => Verification::Verified,
+        Some(DetailValue::False) => Verification::Unverified,
+        _ => Verification::Unknown,
+    };
+
+    ResolvedEntry {
+        principal_id: entry.principal_id,
+        registry,
+        kind,
+        symbol: text_detail(&entry, "symbol"),
+        standard,
+        verification,
+        name: entry.name,
+        thumbnail: entry.thumbnail,
+        frontend: entry.frontend,
+    }
+}
+
+// Looks `principal_id` up in every registered registry and returns its entry in a single
+// shape, so wallets handle tokens and collections alike. `get` keeps answering with the
+// registered registries, as the DRS standard requires.
+#[update]
+pub async fn resolve(principal_id: Principal) -> Option<ResolvedEntry> {
+    let (registry, entry) = lookup(principal_id).await?;
+    Some(normalize(registry, entry))
+}
+
+// Lists the NFTs `user` owns across `collections`, using the standard each
+// collection is registered with. Unregistered collections and collections
+// that fail to answer are skipped.
+#[update]
+pub async fn get_user_nfts(user: Principal, collections: Vec<Principal>) -> Vec<UserNft> {
+    let mut nfts = vec![];
+    for collection in collections {
+        let standard = match lookup(collection).await {
+            Some((_, entry)) => match detail(&entry, "standard") {
+                Some(DetailValue::Text(standard)) => Some(standard.clone()),
+                _ => None,
+            },
+            None => None,
+        };
+
+        if let Some(standard) = standard {
+            if let Ok(mut owned) = standards::get_user_nfts(&standard, collection, user).await {
+                nfts.append(&mut owned);
+            }
+        }
+    }
+
+    nfts
+}
+
+// Lists the balances `user` holds of `tokens`, using the standard and decimals
+// each token is registered with. Unregistered tokens and tokens that fail to
+// answer are skipped.
+#[update]
+pub async fn get_user_balances(user: Principal, tokens: Vec<Principal>) -> Vec<UserBalance> {
+    let mut balances = vec![];
+    for token in tokens {
+        let entry = match lookup(token).await {
+            Some((_, entry)) => entry,
+            None => continue,
+        };
+
+        let (standard, decimals) = match (detail(&entry, "standard"), detail(&entry, "decimals")) {
+            (Some(DetailValue::Text(standard)), Some(DetailValue::U64(decimals))) => {
+                (standard.clone(), *decimals)
+            }
+            _ => continue,
+        };
+
+        if let Ok(amount) = standards::get_balance(&standard, token, user).await {
+            balances.push(UserBalance {
+                token,
+                normalized: standards::normalize(&amount, decimals),
+                standard,
+                amount,
+                decimals,
+            });
+        }
+    }
+
+    balances
+}
@@ -1,3 +1,4 @@
+use crate::cache::*;
 use crate::common_types::*;
 use crate::management::*;
 use crate::router::*;
@@ -252,4 +253,89 @@ mod tests {
 
         assert!(registry.is_none());
     }
+
+    #[test]
+    fn test_invalidate_is_open_to_registries_only() {
+        let context = MockContext::new()
+            .with_caller(mock_principals::alice())
+            .inject();
+
+        init();
+
+        let registry_info = Registry {
+            name: String::from("Wrapped ICP"),
+            description: String::from("Wrapped ICP description"),
+            thumbnail: String::from("https://logo.com"),
+            frontend: Some(String::from("https://website.com")),
+            principal_id: mock_principals::xtc(),
+            details: vec![(String::from("verified"), DetailValue::True)],
+        };
+
+        assert!(add(registry_info).is_ok());
+
+        context.update_caller(mock_principals::bob());
+        assert!(invalidate(mock_principals::john()).is_err());
+
+        context.update_caller(mock_principals::xtc());
+        assert!(invalidate(mock_principals::john()).is_ok());
+    }
+
+    #[test]
+    fn test_set_cache_ttl() {
+        let context = MockContext::new()
+            .with_caller(mock_principals::alice())
+            .inject();
+
+        init();
+
+        assert!(set_cache_ttl(0).is_ok());
+        assert_eq!(get_cache_ttl(), 0);
+
+        context.update_caller(mock_principals::bob());
+        assert!(set_cache_ttl(60).is_err());
+        assert_eq!(get_cache_ttl(), 0);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let text = |text: &str| DetailValue::Text(String::from(text));
+        let entry = |details: Vec<(&str, DetailValue)>| RegistryEntry {
+            name: String::from("Cycles Token"),
+            principal_id: mock_principals::xtc(),
+            details: details
+                .into_iter()
+                .map(|(key, value)| (String::from(key), value))
+                .collect(),
+            thumbnail: Some(String::from("https://logo.com")),
+            frontend: None,
+        };
+
+        let token = normalize(
+            mock_principals::alice(),
+            entry(vec![
+                ("symbol", text("XTC")),
+                ("standard", text("DIP20")),
+                ("decimals", DetailValue::U64(12)),
+                ("verified", DetailValue::True),
+            ]),
+        );
+        assert_eq!(token.kind, EntryKind::Token);
+        assert_eq!(token.registry, mock_principals::alice());
+        assert_eq!(token.symbol, Some(String::from("XTC")));
+        assert_eq!(token.standard, Some(String::from("DIP20")));
+        assert_eq!(token.verification, Verification::Verified);
+
+        let collection = normalize(
+            mock_principals::alice(),
+            entry(vec![("standard", text("DIP721v2"))]),
+        );
+        assert_eq!(collection.kind, EntryKind::Nft);
+        assert_eq!(collection.symbol, None);
+        assert_eq!(collection.verification, Verification::Unknown);
+
+        assert_eq!(
+            normalize(mock_principals::alice(), entry(vec![])).kind,
+            EntryKind::Canister
+        );
+    }
 }
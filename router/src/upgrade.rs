@@ -1,3 +1,4 @@
+use crate::cache::Cache;
 use crate::common_types::Registry;
 use crate::management::Admins;
 use crate::router::Registries;
@@ -11,14 +12,20 @@ use ic_kit::*;
 struct StableStorage {
     db: Vec<(Principal, Registry)>,
     admins: Vec<Principal>,
+    cache_ttl: Option<u64>,
 }
 
 #[pre_upgrade]
 pub fn pre_upgrade() {
     let db = ic::get_mut::<Registries>().archive();
     let admins = ic::get_mut::<Admins>().0.clone();
+    let cache_ttl = Some(ic::get::<Cache>().ttl());
 
-    let stable = StableStorage { db, admins };
+    let stable = StableStorage {
+        db,
+        admins,
+        cache_ttl,
+    };
 
     match ic::stable_store((stable,)) {
         Ok(_) => (),
@@ -36,5 +43,8 @@ pub fn post_upgrade() {
     if let Ok((stable,)) = ic::stable_restore::<(StableStorage,)>() {
         ic::get_mut::<Registries>().load(stable.db);
         ic::store(Admins(stable.admins));
+        if let Some(ttl) = stable.cache_ttl {
+            ic::get_mut::<Cache>().set_ttl(ttl);
+        }
     }
 }
@@ -0,0 +1,247 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+pub use registry_framework::{detail, DetailValue};
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AddNftInput {
+    pub name: String,
+    pub description: String,
+    pub thumbnail: String,
+    pub frontend: Option<String>,
+    pub principal_id: Principal,
+    pub details: Vec<(String, DetailValue)>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NftCanister {
+    pub name: String,
+    pub description: String,
+    pub thumbnail: String,
+    pub frontend: Option<String>,
+    pub principal_id: Principal,
+    pub submitter: Principal,
+    pub last_updated_by: Principal,
+    pub last_updated_at: u64,
+    pub details: Vec<(String, DetailValue)>,
+    pub resolved_from: Option<Principal>,
+    pub managed_by: Option<Principal>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AddTokenInput {
+    pub name: String,
+    pub description: String,
+    pub thumbnail: String,
+    pub frontend: Option<String>,
+    pub principal_id: Principal,
+    pub details: Vec<(String, DetailValue)>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Token {
+    pub name: String,
+    pub description: String,
+    pub thumbnail: String,
+    pub frontend: Option<String>,
+    pub principal_id: Principal,
+    pub submitter: Principal,
+    pub last_updated_by: Principal,
+    pub last_updated_at: u64,
+    pub details: Vec<(String, DetailValue)>,
+    pub resolved_from: Option<Principal>,
+    pub managed_by: Option<Principal>,
+    pub conflicted: Option<bool>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SymbolCollision {
+    pub symbol: String,
+    pub principals: Vec<Principal>,
+}
+
+// Symbol, decimals and fee of a token. `formatted_fee` is the fee in whole tokens
+// followed by the symbol, e.g. "0.0001 ICP".
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DisplayInfo {
+    pub symbol: String,
+    pub decimals: u64,
+    pub fee: Option<u64>,
+    pub formatted_fee: Option<String>,
+}
+
+// Set when the token deviates from a plain transfer: part of the amount is taken as a fee
+// on every transfer, balances change without transfers, or the issuer can freeze transfers
+// globally or per account.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub struct RiskFlags {
+    pub fee_on_transfer: bool,
+    pub rebasing: bool,
+    pub pausable: bool,
+    pub blacklistable: bool,
+}
+
+// Links of the project behind an entry. `frontends` starts with the `frontend` of the entry
+// when it has one.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ProjectLinks {
+    pub website: Option<String>,
+    pub docs: Option<String>,
+    pub twitter: Option<String>,
+    pub discord: Option<String>,
+    pub github: Option<String>,
+    pub frontends: Vec<String>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Deposit {
+    pub ledger: Principal,
+    pub amount: u64,
+}
+
+// An entry proposed by a non-admin, waiting for approval
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Submission<T> {
+    pub submitter: Principal,
+    pub submitted_at: u64,
+    pub deposit: Option<Deposit>,
+    pub entry: T,
+}
+
+pub type NftSubmission = Submission<AddNftInput>;
+pub type TokenSubmission = Submission<AddTokenInput>;
+
+// Reply of `get_v2`, `schema_version` changes with the shape of the entries so clients can
+// check it against `ENTRY_SCHEMA_VERSION` before relying on the data
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Envelope<T> {
+    pub schema_version: u16,
+    pub data: T,
+}
+
+// The entries of `get_all_v2` that fit in the registry's response budget
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GetAllResponse<T> {
+    pub entries: Vec<T>,
+    pub truncated: bool,
+}
+
+// An entry with only the fields asked for, details named "details.<key>"
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PartialEntry {
+    pub principal_id: Principal,
+    pub fields: Vec<(String, DetailValue)>,
+}
+
+// See `registry_hash`. `certificate` is the subnet's certificate over `hash`, only
+// available to query calls.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RegistryHash {
+    pub hash: Vec<u8>,
+    pub entries: u64,
+    pub certificate: Option<Vec<u8>>,
+}
+
+// A counter or gauge sample, e.g. `update_calls` labeled with `endpoint = "add"`
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Metric {
+    pub name: String,
+    pub labels: Vec<(String, String)>,
+    pub value: u64,
+}
+
+// See `get_metrics`, gauges are sampled at `collected_at`
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CanisterMetrics {
+    pub collected_at: u64,
+    pub counters: Vec<Metric>,
+    pub gauges: Vec<Metric>,
+}
+
+// An entry taken down in response to a scam or phishing incident
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Delisting {
+    pub principal_id: Principal,
+    pub name: String,
+    pub reason: String,
+    pub delisted_by: Principal,
+    pub delisted_at: u64,
+}
+
+// Status changes reported to the callback of an entry, see `set_entry_callback`
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EntryStatus {
+    Approved,
+    EditedByAdmin,
+    Reported,
+    Delisted,
+}
+
+// Single argument of the callback method of an entry, for owner canisters decoding it
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EntryNotice {
+    pub principal_id: Principal,
+    pub status: EntryStatus,
+    pub time: u64,
+}
+
+// A submission nobody reviewed before its TTL ran out
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExpiredSubmission<T> {
+    pub submission: Submission<T>,
+    pub expired_at: u64,
+    pub refund_error: Option<String>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Page {
+    pub offset: u64,
+    pub limit: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    Admin,
+    Submitter,
+    Curator,
+    // Assigned to the submissions of a standard, see `assign_reviewers`
+    Reviewer,
+}
+
+#[derive(CandidType, Debug, PartialEq, Deserialize, Clone)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+#[derive(CandidType, Debug, PartialEq, Deserialize, Clone)]
+pub enum OperationError {
+    NotAuthorized {
+        required_role: Role,
+        caller: Principal,
+    },
+    NonExistentItem,
+    BadParameters,
+    Conflict(Principal),
+    InsufficientDeposit,
+    ReservedName(String),
+    EntryLocked,
+    ReadOnlyMirror(Principal),
+    Maintenance,
+    LowCycles,
+    Anonymous,
+    Timelocked(u64),
+    PendingApproval(u64),
+    EntryTooLarge {
+        bytes: u64,
+        limit: u64,
+    },
+    HistoryTruncated(u64),
+    Validation(Vec<FieldError>),
+    Unknown(String),
+}
+
+pub const NFT_REGISTRY_ID: &str = "ctqxp-yyaaa-aaaah-abbda-cai";
+pub const TOKEN_REGISTRY_ID: &str = "qwt65-nyaaa-aaaah-qcl4q-cai";
+// Version of the entry types of this SDK, see `Envelope`
+pub const ENTRY_SCHEMA_VERSION: u16 = 1;
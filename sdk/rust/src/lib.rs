@@ -0,0 +1,15 @@
+// `Transport` would have no variant to reach a registry with
+#[cfg(not(any(feature = "cdk", feature = "agent", feature = "mock")))]
+compile_error!("dab-sdk needs one of the `cdk`, `agent` or `mock` features");
+
+mod common_types;
+mod nft;
+mod tokens;
+mod transport;
+
+pub use common_types::*;
+pub use nft::NftRegistry;
+pub use tokens::TokenRegistry;
+#[cfg(feature = "mock")]
+pub use transport::Replica;
+pub use transport::{Error, Transport};
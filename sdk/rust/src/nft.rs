@@ -0,0 +1,389 @@
+use candid::Principal;
+
+use crate::common_types::*;
+#[cfg(feature = "mock")]
+use crate::transport::Replica;
+use crate::transport::{Error, Transport};
+
+pub struct NftRegistry {
+    canister_id: Principal,
+    transport: Transport,
+}
+
+impl NftRegistry {
+    // Client for calls made from inside a canister
+    #[cfg(feature = "cdk")]
+    pub fn new(canister_id: Principal) -> Self {
+        Self {
+            canister_id,
+            transport: Transport::Canister,
+        }
+    }
+
+    // Client for calls made through an agent
+    #[cfg(feature = "agent")]
+    pub fn with_agent(agent: ic_agent::Agent, canister_id: Principal) -> Self {
+        Self {
+            canister_id,
+            transport: Transport::Agent(agent),
+        }
+    }
+
+    // Client for calls answered in-process, see `dab-test-utils`
+    #[cfg(feature = "mock")]
+    pub fn with_replica(replica: std::sync::Arc<dyn Replica>, canister_id: Principal) -> Self {
+        Self {
+            canister_id,
+            transport: Transport::Mock(replica),
+        }
+    }
+
+    // Client for the mainnet registry
+    pub fn mainnet(transport: Transport) -> Self {
+        Self {
+            canister_id: Principal::from_text(NFT_REGISTRY_ID).unwrap(),
+            transport,
+        }
+    }
+
+    pub fn canister_id(&self) -> Principal {
+        self.canister_id
+    }
+
+    pub async fn name(&self) -> Result<String, Error> {
+        let (name,) = self.transport.query(&self.canister_id, "name", ()).await?;
+        Ok(name)
+    }
+
+    // Optional capabilities of the deployed registry, e.g. "pagination" or "certification"
+    pub async fn supported_features(&self) -> Result<Vec<String>, Error> {
+        let (features,) = self
+            .transport
+            .query(&self.canister_id, "supported_features", ())
+            .await?;
+        Ok(features)
+    }
+
+    // Compare with a local copy to check it holds the same entries as the registry
+    pub async fn registry_hash(&self) -> Result<RegistryHash, Error> {
+        let (hash,) = self
+            .transport
+            .query(&self.canister_id, "registry_hash", ())
+            .await?;
+        Ok(hash)
+    }
+
+    // Call counters and resource gauges, for monitoring dashboards
+    pub async fn get_metrics(&self) -> Result<CanisterMetrics, Error> {
+        let (metrics,) = self
+            .transport
+            .query(&self.canister_id, "get_metrics", ())
+            .await?;
+        Ok(metrics)
+    }
+
+    pub async fn get(&self, principal_id: Principal) -> Result<Option<NftCanister>, Error> {
+        let (entry,) = self
+            .transport
+            .query(&self.canister_id, "get", (principal_id,))
+            .await?;
+        Ok(entry)
+    }
+
+    // `get` with the version of the entry shape of the registry
+    pub async fn get_v2(
+        &self,
+        principal_id: Principal,
+    ) -> Result<Envelope<Option<NftCanister>>, Error> {
+        let (envelope,) = self
+            .transport
+            .query(&self.canister_id, "get_v2", (principal_id,))
+            .await?;
+        Ok(envelope)
+    }
+
+    // Marketplaces the collection trades on, as (name, URL) pairs
+    pub async fn get_marketplaces(
+        &self,
+        principal_id: Principal,
+    ) -> Result<Vec<(String, String)>, Error> {
+        let (marketplaces,) = self
+            .transport
+            .query(&self.canister_id, "get_marketplaces", (principal_id,))
+            .await?;
+        Ok(marketplaces)
+    }
+
+    // Website, docs, socials and frontends of the project behind the entry
+    pub async fn get_links(&self, principal_id: Principal) -> Result<Option<ProjectLinks>, Error> {
+        let (links,) = self
+            .transport
+            .query(&self.canister_id, "get_links", (principal_id,))
+            .await?;
+        Ok(links)
+    }
+
+    // Standard of the entry when `principal_id` is listed, cheaper than `get`
+    pub async fn exists(&self, principal_id: Principal) -> Result<Option<String>, Error> {
+        let (standard,) = self
+            .transport
+            .query(&self.canister_id, "exists", (principal_id,))
+            .await?;
+        Ok(standard)
+    }
+
+    // Root bucket of the collection's transaction history on CAP, when it's known
+    pub async fn get_cap_root_of(
+        &self,
+        principal_id: Principal,
+    ) -> Result<Option<Principal>, Error> {
+        let (root,) = self
+            .transport
+            .query(&self.canister_id, "get_cap_root_of", (principal_id,))
+            .await?;
+        Ok(root)
+    }
+
+    pub async fn get_all(&self) -> Result<Vec<NftCanister>, Error> {
+        let (entries,) = self
+            .transport
+            .query(&self.canister_id, "get_all", ())
+            .await?;
+        Ok(entries)
+    }
+
+    // Principals of the entries only, in the order of `get_all`
+    pub async fn get_all_principals(&self) -> Result<Vec<Principal>, Error> {
+        let (principals,) = self
+            .transport
+            .query(&self.canister_id, "get_all_principals", ())
+            .await?;
+        Ok(principals)
+    }
+
+    pub async fn get_principals(&self, page: Page) -> Result<Vec<Principal>, Error> {
+        let (principals,) = self
+            .transport
+            .query(&self.canister_id, "get_principals", (page,))
+            .await?;
+        Ok(principals)
+    }
+
+    // Like `get_all`, cut short with `truncated` set when the reply would be too large
+    pub async fn get_all_v2(&self) -> Result<GetAllResponse<NftCanister>, Error> {
+        let (response,) = self
+            .transport
+            .query(&self.canister_id, "get_all_v2", ())
+            .await?;
+        Ok(response)
+    }
+
+    // Like `get_all` with only `fields` of each entry, e.g. "name" or "details.standard"
+    pub async fn get_all_projected(
+        &self,
+        fields: Vec<String>,
+    ) -> Result<Result<Vec<PartialEntry>, OperationError>, Error> {
+        let (response,) = self
+            .transport
+            .query(&self.canister_id, "get_all_projected", (fields,))
+            .await?;
+        Ok(response)
+    }
+
+    // Entries whose textual principal starts with `prefix`, for type-ahead search
+    pub async fn search_principal(&self, prefix: String) -> Result<Vec<NftCanister>, Error> {
+        let (entries,) = self
+            .transport
+            .query(&self.canister_id, "search_principal", (prefix,))
+            .await?;
+        Ok(entries)
+    }
+
+    // An entry of a namespace hosted next to the main registry, e.g. "staging"
+    pub async fn get_in_namespace(
+        &self,
+        namespace: String,
+        principal_id: Principal,
+    ) -> Result<Option<NftCanister>, Error> {
+        let (entry,) = self
+            .transport
+            .query(
+                &self.canister_id,
+                "get_in_namespace",
+                (namespace, principal_id),
+            )
+            .await?;
+        Ok(entry)
+    }
+
+    pub async fn list_namespace(
+        &self,
+        namespace: String,
+        offset: u64,
+        limit: u16,
+    ) -> Result<Vec<NftCanister>, Error> {
+        let (entries,) = self
+            .transport
+            .query(
+                &self.canister_id,
+                "list_namespace",
+                (namespace, offset, limit),
+            )
+            .await?;
+        Ok(entries)
+    }
+
+    pub async fn add(
+        &self,
+        trusted_source: Option<Principal>,
+        nft: AddNftInput,
+    ) -> Result<Result<(), OperationError>, Error> {
+        let (response,) = self
+            .transport
+            .update(&self.canister_id, "add", (trusted_source, nft))
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn remove(
+        &self,
+        trusted_source: Option<Principal>,
+        principal_id: Principal,
+    ) -> Result<Result<(), OperationError>, Error> {
+        let (response,) = self
+            .transport
+            .update(&self.canister_id, "remove", (trusted_source, principal_id))
+            .await?;
+        Ok(response)
+    }
+
+    // Flags the entry for moderators, see the registries' `report_entry`
+    pub async fn report_entry(
+        &self,
+        principal_id: Principal,
+        reason: String,
+    ) -> Result<Result<(), OperationError>, Error> {
+        let (response,) = self
+            .transport
+            .update(&self.canister_id, "report_entry", (principal_id, reason))
+            .await?;
+        Ok(response)
+    }
+
+    // Language the errors returned to this client's identity are translated to
+    pub async fn set_error_language(
+        &self,
+        lang: Option<String>,
+    ) -> Result<Result<(), OperationError>, Error> {
+        let (response,) = self
+            .transport
+            .update(&self.canister_id, "set_error_language", (lang,))
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn get_error_language(&self) -> Result<Option<String>, Error> {
+        let (lang,) = self
+            .transport
+            .query(&self.canister_id, "get_error_language", ())
+            .await?;
+        Ok(lang)
+    }
+
+    // Entries taken down by admins, clients should warn users still holding them
+    pub async fn get_delistings(&self) -> Result<Vec<Delisting>, Error> {
+        let (delistings,) = self
+            .transport
+            .query(&self.canister_id, "get_delistings", ())
+            .await?;
+        Ok(delistings)
+    }
+
+    // Canister and method called with an `EntryNotice` when the entry changes status
+    pub async fn get_entry_callback(
+        &self,
+        principal_id: Principal,
+    ) -> Result<Option<(Principal, String)>, Error> {
+        let (callback,) = self
+            .transport
+            .query(&self.canister_id, "get_entry_callback", (principal_id,))
+            .await?;
+        Ok(callback)
+    }
+
+    // Entries pinned by admins, in the order a "featured" section should show them
+    pub async fn get_featured(&self) -> Result<Vec<NftCanister>, Error> {
+        let (entries,) = self
+            .transport
+            .query(&self.canister_id, "get_featured", ())
+            .await?;
+        Ok(entries)
+    }
+
+    pub async fn transfer_entry_ownership(
+        &self,
+        principal_id: Principal,
+        new_owner: Principal,
+    ) -> Result<Result<(), OperationError>, Error> {
+        let (response,) = self
+            .transport
+            .update(
+                &self.canister_id,
+                "transfer_entry_ownership",
+                (principal_id, new_owner),
+            )
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn get_submissions(&self) -> Result<Vec<NftSubmission>, Error> {
+        let (submissions,) = self
+            .transport
+            .query(&self.canister_id, "get_submissions", ())
+            .await?;
+        Ok(submissions)
+    }
+
+    pub async fn get_expired(
+        &self,
+    ) -> Result<Result<Vec<ExpiredSubmission<AddNftInput>>, OperationError>, Error> {
+        let (response,) = self
+            .transport
+            .query(&self.canister_id, "get_expired", ())
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn approve_submission(
+        &self,
+        principal_id: Principal,
+    ) -> Result<Result<(), OperationError>, Error> {
+        let (response,) = self
+            .transport
+            .update(&self.canister_id, "approve_submission", (principal_id,))
+            .await?;
+        Ok(response)
+    }
+
+    pub async fn reject_submission(
+        &self,
+        principal_id: Principal,
+    ) -> Result<Result<(), OperationError>, Error> {
+        let (response,) = self
+            .transport
+            .update(&self.canister_id, "reject_submission", (principal_id,))
+            .await?;
+        Ok(response)
+    }
+
+    // Pending submissions of the standards the caller reviews, oldest first
+    pub async fn get_my_review_queue(
+        &self,
+    ) -> Result<Result<Vec<NftSubmission>, OperationError>, Error> {
+        let (response,) = self
+            .transport
+            .query(&self.canister_id, "get_my_review_queue", ())
+            .await?;
+        Ok(response)
+    }
+}
@@ -0,0 +1,108 @@
+use candid::utils::{ArgumentDecoder, ArgumentEncoder};
+use candid::{decode_args, encode_args, Principal};
+#[cfg(feature = "mock")]
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum Error {
+    Candid(candid::Error),
+    Call(String),
+}
+
+impl From<candid::Error> for Error {
+    fn from(err: candid::Error) -> Self {
+        Error::Candid(err)
+    }
+}
+
+// Stand-in for a registry canister, answering candid encoded calls without a replica
+#[cfg(feature = "mock")]
+pub trait Replica: Send + Sync {
+    fn reply(&self, canister_id: &Principal, method: &str, arg: Vec<u8>) -> Result<Vec<u8>, Error>;
+}
+
+// How a client reaches the registry canister.
+// Arguments are encoded once and the raw reply is decoded the same way for every transport.
+#[derive(Clone)]
+pub enum Transport {
+    #[cfg(feature = "cdk")]
+    Canister,
+    #[cfg(feature = "agent")]
+    Agent(ic_agent::Agent),
+    #[cfg(feature = "mock")]
+    Mock(Arc<dyn Replica>),
+}
+
+impl Transport {
+    pub async fn query<A: ArgumentEncoder, R: for<'a> ArgumentDecoder<'a>>(
+        &self,
+        canister_id: &Principal,
+        method: &str,
+        args: A,
+    ) -> Result<R, Error> {
+        let arg = encode_args(args)?;
+        let reply = match self {
+            #[cfg(feature = "cdk")]
+            Transport::Canister => call_canister(canister_id, method, arg).await?,
+            #[cfg(feature = "agent")]
+            Transport::Agent(agent) => agent
+                .query(&agent_principal(canister_id), method)
+                .with_arg(arg)
+                .call()
+                .await
+                .map_err(|err| Error::Call(err.to_string()))?,
+            #[cfg(feature = "mock")]
+            Transport::Mock(replica) => replica.reply(canister_id, method, arg)?,
+        };
+
+        Ok(decode_args(&reply)?)
+    }
+
+    pub async fn update<A: ArgumentEncoder, R: for<'a> ArgumentDecoder<'a>>(
+        &self,
+        canister_id: &Principal,
+        method: &str,
+        args: A,
+    ) -> Result<R, Error> {
+        let arg = encode_args(args)?;
+        let reply = match self {
+            #[cfg(feature = "cdk")]
+            Transport::Canister => call_canister(canister_id, method, arg).await?,
+            #[cfg(feature = "agent")]
+            Transport::Agent(agent) => {
+                let waiter = garcon::Delay::builder()
+                    .throttle(std::time::Duration::from_millis(500))
+                    .timeout(std::time::Duration::from_secs(60 * 5))
+                    .build();
+
+                agent
+                    .update(&agent_principal(canister_id), method)
+                    .with_arg(arg)
+                    .call_and_wait(waiter)
+                    .await
+                    .map_err(|err| Error::Call(err.to_string()))?
+            }
+            #[cfg(feature = "mock")]
+            Transport::Mock(replica) => replica.reply(canister_id, method, arg)?,
+        };
+
+        Ok(decode_args(&reply)?)
+    }
+}
+
+#[cfg(feature = "cdk")]
+async fn call_canister(
+    canister_id: &Principal,
+    method: &str,
+    arg: Vec<u8>,
+) -> Result<Vec<u8>, Error> {
+    ic_cdk::api::call::call_raw(*canister_id, method, &arg, 0)
+        .await
+        .map_err(|(code, msg)| Error::Call(format!("{:?}: {}", code, msg)))
+}
+
+// ic-agent is built against its own ic-types, so the principal is passed over as bytes
+#[cfg(feature = "agent")]
+fn agent_principal(canister_id: &Principal) -> ic_agent::export::Principal {
+    ic_agent::export::Principal::from_slice(canister_id.as_slice())
+}
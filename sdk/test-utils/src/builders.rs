@@ -0,0 +1,242 @@
+use candid::Principal;
+use dab_sdk::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_PRINCIPAL: AtomicU64 = AtomicU64::new(1);
+
+// Distinct canister-like principal for every call, so entries built without an explicit
+// principal don't overwrite each other
+pub fn test_principal() -> Principal {
+    let id = NEXT_PRINCIPAL.fetch_add(1, Ordering::Relaxed);
+    let mut bytes = id.to_be_bytes().to_vec();
+    bytes.extend_from_slice(&[1, 1]);
+    Principal::from_slice(&bytes)
+}
+
+fn set_detail(details: &mut Vec<(String, DetailValue)>, key: &str, value: DetailValue) {
+    match details.iter_mut().find(|(detail, _)| detail == key) {
+        Some((_, current)) => *current = value,
+        None => details.push((key.to_string(), value)),
+    }
+}
+
+fn flag(value: bool) -> DetailValue {
+    match value {
+        true => DetailValue::True,
+        false => DetailValue::False,
+    }
+}
+
+// Token entry with the details the token registry requires, a DIP20 token by default
+pub struct TokenBuilder {
+    input: AddTokenInput,
+    submitter: Principal,
+}
+
+impl TokenBuilder {
+    pub fn new() -> Self {
+        Self {
+            input: AddTokenInput {
+                name: String::from("Test Token"),
+                description: String::from("Token built by dab-test-utils."),
+                thumbnail: String::from("https://logo_url.com"),
+                frontend: None,
+                principal_id: test_principal(),
+                details: vec![
+                    (
+                        String::from("symbol"),
+                        DetailValue::Text(String::from("TEST")),
+                    ),
+                    (
+                        String::from("standard"),
+                        DetailValue::Text(String::from("DIP20")),
+                    ),
+                    (String::from("total_supply"), DetailValue::U64(0)),
+                    (String::from("verified"), DetailValue::False),
+                    (String::from("decimals"), DetailValue::U64(8)),
+                    (String::from("fee"), DetailValue::U64(0)),
+                ],
+            },
+            submitter: Principal::anonymous(),
+        }
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.input.name = name.to_string();
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.input.description = description.to_string();
+        self
+    }
+
+    pub fn thumbnail(mut self, thumbnail: &str) -> Self {
+        self.input.thumbnail = thumbnail.to_string();
+        self
+    }
+
+    pub fn frontend(mut self, frontend: &str) -> Self {
+        self.input.frontend = Some(frontend.to_string());
+        self
+    }
+
+    pub fn principal_id(mut self, principal_id: Principal) -> Self {
+        self.input.principal_id = principal_id;
+        self
+    }
+
+    pub fn submitter(mut self, submitter: Principal) -> Self {
+        self.submitter = submitter;
+        self
+    }
+
+    pub fn symbol(self, symbol: &str) -> Self {
+        self.detail("symbol", DetailValue::Text(symbol.to_string()))
+    }
+
+    pub fn standard(self, standard: &str) -> Self {
+        self.detail("standard", DetailValue::Text(standard.to_string()))
+    }
+
+    pub fn total_supply(self, total_supply: u64) -> Self {
+        self.detail("total_supply", DetailValue::U64(total_supply))
+    }
+
+    pub fn verified(self, verified: bool) -> Self {
+        self.detail("verified", flag(verified))
+    }
+
+    pub fn decimals(self, decimals: u64) -> Self {
+        self.detail("decimals", DetailValue::U64(decimals))
+    }
+
+    pub fn fee(self, fee: u64) -> Self {
+        self.detail("fee", DetailValue::U64(fee))
+    }
+
+    // Sets any other detail, replacing the value of `key` when it's already set
+    pub fn detail(mut self, key: &str, value: DetailValue) -> Self {
+        set_detail(&mut self.input.details, key, value);
+        self
+    }
+
+    // Argument of `add`
+    pub fn input(self) -> AddTokenInput {
+        self.input
+    }
+
+    // Entry as the registry returns it once listed
+    pub fn build(self) -> Token {
+        Token {
+            name: self.input.name,
+            description: self.input.description,
+            thumbnail: self.input.thumbnail,
+            frontend: self.input.frontend,
+            principal_id: self.input.principal_id,
+            submitter: self.submitter,
+            last_updated_by: self.submitter,
+            last_updated_at: 0,
+            details: self.input.details,
+            resolved_from: None,
+            managed_by: None,
+            conflicted: None,
+        }
+    }
+}
+
+impl Default for TokenBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// NFT collection entry, a DIP721v2 collection by default
+pub struct NftBuilder {
+    input: AddNftInput,
+    submitter: Principal,
+}
+
+impl NftBuilder {
+    pub fn new() -> Self {
+        Self {
+            input: AddNftInput {
+                name: String::from("Test Collection"),
+                description: String::from("Collection built by dab-test-utils."),
+                thumbnail: String::from("https://logo_url.com"),
+                frontend: None,
+                principal_id: test_principal(),
+                details: vec![(
+                    String::from("standard"),
+                    DetailValue::Text(String::from("DIP721v2")),
+                )],
+            },
+            submitter: Principal::anonymous(),
+        }
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.input.name = name.to_string();
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.input.description = description.to_string();
+        self
+    }
+
+    pub fn thumbnail(mut self, thumbnail: &str) -> Self {
+        self.input.thumbnail = thumbnail.to_string();
+        self
+    }
+
+    pub fn frontend(mut self, frontend: &str) -> Self {
+        self.input.frontend = Some(frontend.to_string());
+        self
+    }
+
+    pub fn principal_id(mut self, principal_id: Principal) -> Self {
+        self.input.principal_id = principal_id;
+        self
+    }
+
+    pub fn submitter(mut self, submitter: Principal) -> Self {
+        self.submitter = submitter;
+        self
+    }
+
+    pub fn standard(self, standard: &str) -> Self {
+        self.detail("standard", DetailValue::Text(standard.to_string()))
+    }
+
+    pub fn detail(mut self, key: &str, value: DetailValue) -> Self {
+        set_detail(&mut self.input.details, key, value);
+        self
+    }
+
+    pub fn input(self) -> AddNftInput {
+        self.input
+    }
+
+    pub fn build(self) -> NftCanister {
+        NftCanister {
+            name: self.input.name,
+            description: self.input.description,
+            thumbnail: self.input.thumbnail,
+            frontend: self.input.frontend,
+            principal_id: self.input.principal_id,
+            submitter: self.submitter,
+            last_updated_by: self.submitter,
+            last_updated_at: 0,
+            details: self.input.details,
+            resolved_from: None,
+            managed_by: None,
+        }
+    }
+}
+
+impl Default for NftBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
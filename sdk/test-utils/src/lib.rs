@@ -0,0 +1,5 @@
+mod builders;
+mod mock;
+
+pub use builders::*;
+pub use mock::*;
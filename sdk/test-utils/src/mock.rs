@@ -0,0 +1,344 @@
+use candid::utils::{ArgumentDecoder, ArgumentEncoder};
+use candid::{decode_args, encode_args, Principal};
+use dab_sdk::*;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Fields of the entries the mocks read, common to tokens and NFT collections
+trait Listed: Clone {
+    fn principal_id(&self) -> Principal;
+    fn details(&self) -> &[(String, DetailValue)];
+
+    fn standard(&self) -> Option<String> {
+        self.details()
+            .iter()
+            .find(|(key, _)| key == "standard")
+            .and_then(|(_, value)| match value {
+                DetailValue::Text(standard) => Some(standard.clone()),
+                _ => None,
+            })
+    }
+}
+
+impl Listed for Token {
+    fn principal_id(&self) -> Principal {
+        self.principal_id
+    }
+
+    fn details(&self) -> &[(String, DetailValue)] {
+        &self.details
+    }
+}
+
+impl Listed for NftCanister {
+    fn principal_id(&self) -> Principal {
+        self.principal_id
+    }
+
+    fn details(&self) -> &[(String, DetailValue)] {
+        &self.details
+    }
+}
+
+// Entries in the order they were added, and the principal calls are made as
+struct State<E> {
+    entries: Vec<E>,
+    caller: Principal,
+}
+
+impl<E: Listed> State<E> {
+    fn new() -> Self {
+        Self {
+            entries: vec![],
+            caller: Principal::anonymous(),
+        }
+    }
+
+    fn get(&self, principal_id: &Principal) -> Option<E> {
+        self.entries
+            .iter()
+            .find(|entry| entry.principal_id() == *principal_id)
+            .cloned()
+    }
+
+    // Updates keep the place of the entry
+    fn insert(&mut self, entry: E) {
+        let principal_id = entry.principal_id();
+        match self
+            .entries
+            .iter_mut()
+            .find(|listed| listed.principal_id() == principal_id)
+        {
+            Some(listed) => *listed = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    fn remove(&mut self, principal_id: &Principal) -> Result<(), OperationError> {
+        let len = self.entries.len();
+        self.entries
+            .retain(|entry| entry.principal_id() != *principal_id);
+        match self.entries.len() < len {
+            true => Ok(()),
+            false => Err(OperationError::NonExistentItem),
+        }
+    }
+
+    fn page(&self, standard: Option<String>, page: Option<Page>) -> Vec<E> {
+        let page = page.unwrap_or(Page {
+            offset: 0,
+            limit: u64::MAX,
+        });
+        self.entries
+            .iter()
+            .filter(|entry| standard.is_none() || entry.standard() == standard)
+            .skip(page.offset as usize)
+            .take(page.limit as usize)
+            .cloned()
+            .collect()
+    }
+
+    fn principals(&self, page: Option<Page>) -> Vec<Principal> {
+        self.page(None, page)
+            .iter()
+            .map(Listed::principal_id)
+            .collect()
+    }
+}
+
+fn decode<'a, A: ArgumentDecoder<'a>>(arg: &'a [u8]) -> Result<A, Error> {
+    Ok(decode_args(arg)?)
+}
+
+fn encode<R: ArgumentEncoder>(reply: R) -> Result<Vec<u8>, Error> {
+    Ok(encode_args(reply)?)
+}
+
+fn not_mocked(method: &str) -> Error {
+    Error::Call(format!(
+        "{} is not implemented by the mock registry",
+        method
+    ))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+// Token registry kept in memory. Clones share the entries, so a test can keep one to
+// inspect what the code under test wrote through `client()`. There are no authorization
+// or validation checks, `add` and `remove` succeed for any caller.
+#[derive(Clone)]
+pub struct MockTokenRegistry(Arc<Mutex<State<Token>>>);
+
+impl MockTokenRegistry {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(State::new())))
+    }
+
+    pub fn with_entry(self, entry: Token) -> Self {
+        self.0.lock().unwrap().insert(entry);
+        self
+    }
+
+    // Submitter of the entries added through the clients, anonymous by default
+    pub fn set_caller(&self, caller: Principal) {
+        self.0.lock().unwrap().caller = caller;
+    }
+
+    pub fn entries(&self) -> Vec<Token> {
+        self.0.lock().unwrap().entries.clone()
+    }
+
+    // SDK client answered by this registry, under the id of the mainnet one
+    pub fn client(&self) -> TokenRegistry {
+        let canister_id = Principal::from_text(TOKEN_REGISTRY_ID).unwrap();
+        TokenRegistry::with_replica(Arc::new(self.clone()), canister_id)
+    }
+}
+
+impl Default for MockTokenRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Replica for MockTokenRegistry {
+    fn reply(
+        &self,
+        _canister_id: &Principal,
+        method: &str,
+        arg: Vec<u8>,
+    ) -> Result<Vec<u8>, Error> {
+        let mut state = self.0.lock().unwrap();
+        match method {
+            "name" => encode((String::from("Token Registry Canister"),)),
+            "supported_features" => encode((Vec::<String>::new(),)),
+            "get" => {
+                let (principal_id,): (Principal,) = decode(&arg)?;
+                encode((state.get(&principal_id),))
+            }
+            "exists" => {
+                let (principal_id,): (Principal,) = decode(&arg)?;
+                encode((state.get(&principal_id).and_then(|entry| entry.standard()),))
+            }
+            "get_all" => {
+                let (standard, page): (Option<String>, Option<Page>) = decode(&arg)?;
+                encode((state.page(standard, page),))
+            }
+            "get_all_principals" => encode((state.principals(None),)),
+            "get_principals" => {
+                let (page,): (Page,) = decode(&arg)?;
+                encode((state.principals(Some(page)),))
+            }
+            "add" => {
+                let (trusted_source, input): (Option<Principal>, AddTokenInput) = decode(&arg)?;
+                let submitter = trusted_source.unwrap_or(state.caller);
+                state.insert(Token {
+                    name: input.name,
+                    description: input.description,
+                    thumbnail: input.thumbnail,
+                    frontend: input.frontend,
+                    principal_id: input.principal_id,
+                    submitter,
+                    last_updated_by: submitter,
+                    last_updated_at: now(),
+                    details: input.details,
+                    resolved_from: None,
+                    managed_by: None,
+                    conflicted: None,
+                });
+                encode((Ok::<(), OperationError>(()),))
+            }
+            "remove" => {
+                let (_, principal_id): (Option<Principal>, Principal) = decode(&arg)?;
+                encode((state.remove(&principal_id),))
+            }
+            method => Err(not_mocked(method)),
+        }
+    }
+}
+
+// NFT registry kept in memory, like `MockTokenRegistry`
+#[derive(Clone)]
+pub struct MockNftRegistry(Arc<Mutex<State<NftCanister>>>);
+
+impl MockNftRegistry {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(State::new())))
+    }
+
+    pub fn with_entry(self, entry: NftCanister) -> Self {
+        self.0.lock().unwrap().insert(entry);
+        self
+    }
+
+    pub fn set_caller(&self, caller: Principal) {
+        self.0.lock().unwrap().caller = caller;
+    }
+
+    pub fn entries(&self) -> Vec<NftCanister> {
+        self.0.lock().unwrap().entries.clone()
+    }
+
+    pub fn client(&self) -> NftRegistry {
+        let canister_id = Principal::from_text(NFT_REGISTRY_ID).unwrap();
+        NftRegistry::with_replica(Arc::new(self.clone()), canister_id)
+    }
+}
+
+impl Default for MockNftRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Replica for MockNftRegistry {
+    fn reply(
+        &self,
+        _canister_id: &Principal,
+        method: &str,
+        arg: Vec<u8>,
+    ) -> Result<Vec<u8>, Error> {
+        let mut state = self.0.lock().unwrap();
+        match method {
+            "name" => encode((String::from("NFT Registry Canister"),)),
+            "supported_features" => encode((Vec::<String>::new(),)),
+            "get" => {
+                let (principal_id,): (Principal,) = decode(&arg)?;
+                encode((state.get(&principal_id),))
+            }
+            "exists" => {
+                let (principal_id,): (Principal,) = decode(&arg)?;
+                let entry = state.get(&principal_id);
+                encode((entry.map(|entry| entry.standard().unwrap_or_default()),))
+            }
+            "get_all" => encode((state.page(None, None),)),
+            "get_all_principals" => encode((state.principals(None),)),
+            "get_principals" => {
+                let (page,): (Page,) = decode(&arg)?;
+                encode((state.principals(Some(page)),))
+            }
+            "add" => {
+                let (trusted_source, input): (Option<Principal>, AddNftInput) = decode(&arg)?;
+                let submitter = trusted_source.unwrap_or(state.caller);
+                state.insert(NftCanister {
+                    name: input.name,
+                    description: input.description,
+                    thumbnail: input.thumbnail,
+                    frontend: input.frontend,
+                    principal_id: input.principal_id,
+                    submitter,
+                    last_updated_by: submitter,
+                    last_updated_at: now(),
+                    details: input.details,
+                    resolved_from: None,
+                    managed_by: None,
+                });
+                encode((Ok::<(), OperationError>(()),))
+            }
+            "remove" => {
+                let (_, principal_id): (Option<Principal>, Principal) = decode(&arg)?;
+                encode((state.remove(&principal_id),))
+            }
+            method => Err(not_mocked(method)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::*;
+
+    #[test]
+    fn test_mock_token_registry() {
+        let xtc = TokenBuilder::new().symbol("XTC").standard("DIP20").build();
+        let wicp = TokenBuilder::new()
+            .symbol("WICP")
+            .standard("ICRC-1")
+            .build();
+        let registry = MockTokenRegistry::new()
+            .with_entry(xtc.clone())
+            .with_entry(wicp.clone());
+        let canister_id = Principal::from_text(TOKEN_REGISTRY_ID).unwrap();
+        let call = |method: &str, arg: Vec<u8>| registry.reply(&canister_id, method, arg).unwrap();
+
+        let reply = call("get", encode_args((xtc.principal_id,)).unwrap());
+        let (entry,): (Option<Token>,) = decode_args(&reply).unwrap();
+        assert_eq!(entry, Some(xtc.clone()));
+
+        let arg = encode_args((Some(String::from("ICRC-1")), None::<Page>)).unwrap();
+        let (entries,): (Vec<Token>,) = decode_args(&call("get_all", arg)).unwrap();
+        assert_eq!(entries, vec![wicp]);
+
+        let arg = encode_args((None::<Principal>, xtc.principal_id)).unwrap();
+        let (response,): (Result<(), OperationError>,) = decode_args(&call("remove", arg)).unwrap();
+        assert!(response.is_ok());
+        assert_eq!(registry.entries().len(), 1);
+
+        assert!(registry.reply(&canister_id, "get_metrics", vec![]).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+// Canonical listings of well-known mainnet canisters, loaded by the registries' `load_seed`
+// so fresh deployments and local environments start with realistic data. Principals are
+// in their textual form and amounts in the smallest unit of the token.
+
+pub struct SeedToken {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub thumbnail: &'static str,
+    pub frontend: Option<&'static str>,
+    pub principal_id: &'static str,
+    pub symbol: &'static str,
+    pub standard: &'static str,
+    pub decimals: u64,
+    pub fee: u64,
+    pub total_supply: u64,
+}
+
+pub struct SeedNft {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub thumbnail: &'static str,
+    pub frontend: Option<&'static str>,
+    pub principal_id: &'static str,
+    pub standard: &'static str,
+}
+
+pub const TOKENS: &[SeedToken] = &[
+    SeedToken {
+        name: "Internet Computer",
+        description: "The native utility token of the Internet Computer.",
+        thumbnail: "https://internetcomputer.org/img/IC_logo_horizontal.svg",
+        frontend: Some("https://nns.ic0.app"),
+        principal_id: "ryjl3-tyaaa-aaaaa-aaaba-cai",
+        symbol: "ICP",
+        standard: "ICRC-1",
+        decimals: 8,
+        fee: 10_000,
+        total_supply: 48_000_000_000_000_000,
+    },
+    SeedToken {
+        name: "ckBTC",
+        description:
+            "Bitcoin on the Internet Computer, backed 1:1 by BTC held by the ckBTC minter.",
+        thumbnail: "https://nns.ic0.app/_app/immutable/assets/ckBTC.svg",
+        frontend: Some("https://nns.ic0.app"),
+        principal_id: "mxzaz-hqaaa-aaaar-qaada-cai",
+        symbol: "ckBTC",
+        standard: "ICRC-1",
+        decimals: 8,
+        fee: 10,
+        total_supply: 0,
+    },
+    SeedToken {
+        name: "ckETH",
+        description: "Ether on the Internet Computer, backed 1:1 by ETH held by the ckETH minter.",
+        thumbnail: "https://nns.ic0.app/_app/immutable/assets/ckETH.svg",
+        frontend: Some("https://nns.ic0.app"),
+        principal_id: "ss2fx-dyaaa-aaaar-qacoq-cai",
+        symbol: "ckETH",
+        standard: "ICRC-1",
+        decimals: 18,
+        fee: 2_000_000_000_000,
+        total_supply: 0,
+    },
+    SeedToken {
+        name: "Wrapped ICP",
+        description: "Wrapped ICP on the DIP20 standard.",
+        thumbnail: "https://wicp.app/logo.svg",
+        frontend: Some("https://wicp.app"),
+        principal_id: "utozz-siaaa-aaaam-qaaxq-cai",
+        symbol: "WICP",
+        standard: "DIP20",
+        decimals: 8,
+        fee: 0,
+        total_supply: 0,
+    },
+    SeedToken {
+        name: "Cycles Token",
+        description: "Cycles wrapped as a DIP20 token, one XTC per trillion cycles.",
+        thumbnail: "https://xtc.app/logo.svg",
+        frontend: Some("https://xtc.app"),
+        principal_id: "aanaa-xaaaa-aaaah-aaeiq-cai",
+        symbol: "XTC",
+        standard: "DIP20",
+        decimals: 12,
+        fee: 2_000_000_000,
+        total_supply: 0,
+    },
+];
+
+pub const NFT_COLLECTIONS: &[SeedNft] = &[
+    SeedNft {
+        name: "ICPunks",
+        description:
+            "10,000 randomly generated punks, the first NFT collection on the Internet Computer.",
+        thumbnail: "https://icpunks.com/img/logo.png",
+        frontend: Some("https://icpunks.com"),
+        principal_id: "bxdf4-baaaa-aaaah-qaruq-cai",
+        standard: "ICPunks",
+    },
+    SeedNft {
+        name: "Cronic Critters",
+        description: "Collectible critters that can be bred and played with in Cronic Wars.",
+        thumbnail: "https://cronic.toniqlabs.com/logo.png",
+        frontend: Some("https://cronic.toniqlabs.com"),
+        principal_id: "e3izy-jiaaa-aaaah-qacbq-cai",
+        standard: "EXT",
+    },
+    SeedNft {
+        name: "Starverse",
+        description: "A collection of 10,000 unique stars on the Internet Computer.",
+        thumbnail: "https://starverse.art/logo.png",
+        frontend: Some("https://starverse.art"),
+        principal_id: "nbg4r-saaaa-aaaah-qap7a-cai",
+        standard: "EXT",
+    },
+    SeedNft {
+        name: "Poked Bots",
+        description: "10,000 robots poked into existence on the Internet Computer.",
+        thumbnail: "https://poked.art/logo.png",
+        frontend: Some("https://poked.art"),
+        principal_id: "bzsui-sqaaa-aaaah-qce2a-cai",
+        standard: "EXT",
+    },
+];
@@ -0,0 +1,37 @@
+use ic_kit::candid::{CandidType, Nat, Principal};
+use serde::Deserialize;
+
+// An NFT as seen across standards. `token_id` is the identifier the
+// collection's own interface uses, rendered as text.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct UserNft {
+    pub collection: Principal,
+    pub standard: String,
+    pub token_id: String,
+}
+
+// A fungible token balance. `amount` is in the token's smallest unit and
+// `normalized` is the same amount written with `decimals` decimal places.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct UserBalance {
+    pub token: Principal,
+    pub standard: String,
+    pub amount: Nat,
+    pub decimals: u64,
+    pub normalized: String,
+}
+
+// Size of an NFT collection. `holders` is `None` when the standard doesn't
+// expose its owners in a single call.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct CollectionSupply {
+    pub total_supply: Nat,
+    pub holders: Option<Nat>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum StandardError {
+    UnsupportedStandard(String),
+    CallFailed(String),
+    Rejected(String),
+}
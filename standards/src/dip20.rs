@@ -0,0 +1,11 @@
+use ic_kit::candid::{Nat, Principal};
+use ic_kit::*;
+
+use crate::common_types::*;
+
+pub async fn balance(token: Principal, user: Principal) -> Result<Nat, StandardError> {
+    match ic::call(token, "balanceOf", (user,)).await {
+        Ok((balance,)) => Ok(balance),
+        Err((_code, msg)) => Err(StandardError::CallFailed(msg)),
+    }
+}
@@ -0,0 +1,66 @@
+use ic_kit::candid::{CandidType, Deserialize, Nat, Principal};
+use ic_kit::*;
+
+use crate::common_types::*;
+
+#[derive(CandidType, Deserialize, Debug)]
+enum NftError {
+    SelfTransfer,
+    TokenNotFound,
+    TxNotFound,
+    SelfApprove,
+    OperatorNotFound,
+    UnauthorizedOwner,
+    UnauthorizedOperator,
+    ExistedNFT,
+    OwnerNotFound,
+    Other(String),
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+enum TokenIdsResult {
+    Ok(Vec<Nat>),
+    Err(NftError),
+}
+
+pub async fn owner_token_ids(
+    collection: Principal,
+    user: Principal,
+) -> Result<Vec<String>, StandardError> {
+    match ic::call(collection, "ownerTokenIdentifiers", (user,)).await {
+        Ok((TokenIdsResult::Ok(ids),)) => Ok(ids.iter().map(|id| id.to_string()).collect()),
+        // Owners without any token are reported as unknown
+        Ok((TokenIdsResult::Err(NftError::OwnerNotFound),)) => Ok(vec![]),
+        Ok((TokenIdsResult::Err(err),)) => Err(StandardError::Rejected(format!("{:?}", err))),
+        Err((_code, msg)) => Err(StandardError::CallFailed(msg)),
+    }
+}
+
+pub async fn supply(collection: Principal) -> Result<CollectionSupply, StandardError> {
+    let total_supply: Nat = match ic::call(collection, "totalSupply", ()).await {
+        Ok((total_supply,)) => total_supply,
+        Err((_code, msg)) => return Err(StandardError::CallFailed(msg)),
+    };
+
+    let holders: Nat = match ic::call(collection, "totalUniqueHolders", ()).await {
+        Ok((holders,)) => holders,
+        Err((_code, msg)) => return Err(StandardError::CallFailed(msg)),
+    };
+
+    Ok(CollectionSupply {
+        total_supply,
+        holders: Some(holders),
+    })
+}
+
+// The first version of the interface suffixes its methods with the standard name
+pub async fn supply_v1(collection: Principal) -> Result<CollectionSupply, StandardError> {
+    let result: Result<(u64,), _> = ic::call(collection, "totalSupplyDip721", ()).await;
+    match result {
+        Ok((total_supply,)) => Ok(CollectionSupply {
+            total_supply: Nat::from(total_supply),
+            holders: None,
+        }),
+        Err((_code, msg)) => Err(StandardError::CallFailed(msg)),
+    }
+}
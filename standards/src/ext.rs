@@ -0,0 +1,105 @@
+use ic_kit::candid::{CandidType, Deserialize, Nat, Principal};
+use ic_kit::*;
+use sha2::{Digest, Sha224};
+use std::collections::HashSet;
+
+use crate::common_types::*;
+
+#[derive(CandidType, Deserialize, Debug)]
+enum CommonError {
+    InvalidToken(String),
+    Other(String),
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+enum User {
+    #[serde(rename = "address")]
+    Address(String),
+    #[serde(rename = "principal")]
+    Principal(Principal),
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+struct BalanceRequest {
+    token: String,
+    user: User,
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+enum BalanceResult {
+    #[serde(rename = "ok")]
+    Ok(Nat),
+    #[serde(rename = "err")]
+    Err(CommonError),
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+enum TokensResult {
+    #[serde(rename = "ok")]
+    Ok(Vec<u32>),
+    #[serde(rename = "err")]
+    Err(CommonError),
+}
+
+// Text account identifier of the default subaccount of `principal`
+pub fn account_id(principal: &Principal) -> String {
+    let mut hasher = Sha224::new();
+    hasher.update(b"\x0Aaccount-id");
+    hasher.update(principal.as_slice());
+    hasher.update([0u8; 32]);
+    let hash = hasher.finalize();
+
+    let checksum = crc32fast::hash(&hash).to_be_bytes();
+    hex::encode([&checksum[..], &hash[..]].concat())
+}
+
+// EXT token identifiers encode the collection and the token index the same way principals are encoded
+pub fn token_id(collection: &Principal, index: u32) -> String {
+    let bytes = [b"\x0Atid", collection.as_slice(), &index.to_be_bytes()[..]].concat();
+
+    Principal::from_slice(&bytes).to_text()
+}
+
+pub async fn owner_token_ids(
+    collection: Principal,
+    user: Principal,
+) -> Result<Vec<String>, StandardError> {
+    match ic::call(collection, "tokens", (account_id(&user),)).await {
+        Ok((TokensResult::Ok(indexes),)) => Ok(indexes
+            .into_iter()
+            .map(|index| token_id(&collection, index))
+            .collect()),
+        // EXT reports accounts without tokens as an error
+        Ok((TokensResult::Err(CommonError::Other(_)),)) => Ok(vec![]),
+        Ok((TokensResult::Err(err),)) => Err(StandardError::Rejected(format!("{:?}", err))),
+        Err((_code, msg)) => Err(StandardError::CallFailed(msg)),
+    }
+}
+
+// Fungible EXT canisters identify their single token by the canister principal
+pub async fn balance(token: Principal, user: Principal) -> Result<Nat, StandardError> {
+    let request = BalanceRequest {
+        token: token.to_text(),
+        user: User::Principal(user),
+    };
+
+    match ic::call(token, "balance", (request,)).await {
+        Ok((BalanceResult::Ok(balance),)) => Ok(balance),
+        Ok((BalanceResult::Err(err),)) => Err(StandardError::Rejected(format!("{:?}", err))),
+        Err((_code, msg)) => Err(StandardError::CallFailed(msg)),
+    }
+}
+
+// The registry maps every minted token index to the account holding it
+pub async fn supply(collection: Principal) -> Result<CollectionSupply, StandardError> {
+    let registry: Vec<(u32, String)> = match ic::call(collection, "getRegistry", ()).await {
+        Ok((registry,)) => registry,
+        Err((_code, msg)) => return Err(StandardError::CallFailed(msg)),
+    };
+
+    let holders: HashSet<&String> = registry.iter().map(|(_index, account)| account).collect();
+    Ok(CollectionSupply {
+        total_supply: Nat::from(registry.len() as u64),
+        holders: Some(Nat::from(holders.len() as u64)),
+    })
+}
@@ -0,0 +1,26 @@
+use ic_kit::candid::{Nat, Principal};
+use ic_kit::*;
+
+use crate::common_types::*;
+
+pub async fn owner_token_ids(
+    collection: Principal,
+    user: Principal,
+) -> Result<Vec<String>, StandardError> {
+    let ids: Vec<Nat> = match ic::call(collection, "user_tokens", (user,)).await {
+        Ok((ids,)) => ids,
+        Err((_code, msg)) => return Err(StandardError::CallFailed(msg)),
+    };
+
+    Ok(ids.iter().map(|id| id.to_string()).collect())
+}
+
+pub async fn supply(collection: Principal) -> Result<CollectionSupply, StandardError> {
+    match ic::call(collection, "total_supply", ()).await {
+        Ok((total_supply,)) => Ok(CollectionSupply {
+            total_supply,
+            holders: None,
+        }),
+        Err((_code, msg)) => Err(StandardError::CallFailed(msg)),
+    }
+}
@@ -0,0 +1,22 @@
+use ic_kit::candid::{CandidType, Deserialize, Nat, Principal};
+use ic_kit::*;
+
+use crate::common_types::*;
+
+#[derive(CandidType, Deserialize, Debug)]
+struct Account {
+    owner: Principal,
+    subaccount: Option<Vec<u8>>,
+}
+
+pub async fn balance(token: Principal, user: Principal) -> Result<Nat, StandardError> {
+    let account = Account {
+        owner: user,
+        subaccount: None,
+    };
+
+    match ic::call(token, "icrc1_balance_of", (account,)).await {
+        Ok((balance,)) => Ok(balance),
+        Err((_code, msg)) => Err(StandardError::CallFailed(msg)),
+    }
+}
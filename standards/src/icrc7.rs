@@ -0,0 +1,44 @@
+use ic_kit::candid::{CandidType, Deserialize, Nat, Principal};
+use ic_kit::*;
+
+use crate::common_types::*;
+
+#[derive(CandidType, Deserialize, Debug)]
+struct Account {
+    owner: Principal,
+    subaccount: Option<Vec<u8>>,
+}
+
+// Only the first page, as sized by the collection, is read
+pub async fn owner_token_ids(
+    collection: Principal,
+    user: Principal,
+) -> Result<Vec<String>, StandardError> {
+    let account = Account {
+        owner: user,
+        subaccount: None,
+    };
+
+    let ids: Vec<Nat> = match ic::call(
+        collection,
+        "icrc7_tokens_of",
+        (account, None::<Nat>, None::<Nat>),
+    )
+    .await
+    {
+        Ok((ids,)) => ids,
+        Err((_code, msg)) => return Err(StandardError::CallFailed(msg)),
+    };
+
+    Ok(ids.iter().map(|id| id.to_string()).collect())
+}
+
+pub async fn supply(collection: Principal) -> Result<CollectionSupply, StandardError> {
+    match ic::call(collection, "icrc7_total_supply", ()).await {
+        Ok((total_supply,)) => Ok(CollectionSupply {
+            total_supply,
+            holders: None,
+        }),
+        Err((_code, msg)) => Err(StandardError::CallFailed(msg)),
+    }
+}
@@ -0,0 +1,95 @@
+mod common_types;
+mod dip20;
+mod dip721;
+mod ext;
+mod icpunks;
+mod icrc1;
+mod icrc7;
+
+pub use common_types::*;
+
+use ic_kit::candid::{Nat, Principal};
+
+// Lists the NFTs `user` owns in `collection`, using the interface of `standard`
+pub async fn get_user_nfts(
+    standard: &str,
+    collection: Principal,
+    user: Principal,
+) -> Result<Vec<UserNft>, StandardError> {
+    let token_ids = match standard {
+        "DIP721" | "DIP721v2" => dip721::owner_token_ids(collection, user).await?,
+        "EXT" => ext::owner_token_ids(collection, user).await?,
+        "ICPunks" => icpunks::owner_token_ids(collection, user).await?,
+        "ICRC-7" | "ICRC-37" => icrc7::owner_token_ids(collection, user).await?,
+        _ => return Err(StandardError::UnsupportedStandard(standard.to_string())),
+    };
+
+    Ok(token_ids
+        .into_iter()
+        .map(|token_id| UserNft {
+            collection,
+            standard: standard.to_string(),
+            token_id,
+        })
+        .collect())
+}
+
+// Reads the supply of `collection`, and its holder count when `standard` exposes one
+pub async fn get_collection_supply(
+    standard: &str,
+    collection: Principal,
+) -> Result<CollectionSupply, StandardError> {
+    match standard {
+        "DIP721" => dip721::supply_v1(collection).await,
+        "DIP721v2" => dip721::supply(collection).await,
+        "EXT" => ext::supply(collection).await,
+        "ICPunks" => icpunks::supply(collection).await,
+        "ICRC-7" | "ICRC-37" => icrc7::supply(collection).await,
+        _ => Err(StandardError::UnsupportedStandard(standard.to_string())),
+    }
+}
+
+// Queries the balance `user` holds of `token`, using the interface of `standard`
+pub async fn get_balance(
+    standard: &str,
+    token: Principal,
+    user: Principal,
+) -> Result<Nat, StandardError> {
+    match standard {
+        "DIP20" => dip20::balance(token, user).await,
+        "ICRC1" | "ICRC-1" => icrc1::balance(token, user).await,
+        "EXT" => ext::balance(token, user).await,
+        _ => Err(StandardError::UnsupportedStandard(standard.to_string())),
+    }
+}
+
+// Writes `amount` with `decimals` decimal places, without trailing zeros
+pub fn normalize(amount: &Nat, decimals: u64) -> String {
+    let digits = amount.0.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+
+    let digits = format!("{:0>width$}", digits, width = decimals + 1);
+    let (units, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    match fraction.is_empty() {
+        true => units.to_string(),
+        false => format!("{}.{}", units, fraction),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(&Nat::from(123_450_000u64), 8), "1.2345");
+        assert_eq!(normalize(&Nat::from(5u64), 8), "0.00000005");
+        assert_eq!(normalize(&Nat::from(100_000_000u64), 8), "1");
+        assert_eq!(normalize(&Nat::from(42u64), 0), "42");
+    }
+}
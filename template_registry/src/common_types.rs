@@ -15,14 +15,6 @@ pub enum DetailValue {
     Vec(Vec<DetailValue>),
 }
 
-#[derive(CandidType)]
-pub enum Error {
-    NotAuthorized,
-    BadParameters,
-    NonExistentItem,
-    Unknown(String),
-}
-
 // The metadata structure that you want to store in your registry.
 #[derive(Deserialize, CandidType, Clone, PartialEq, Debug)]
 pub struct CanisterMetadata {
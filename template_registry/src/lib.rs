@@ -1,4 +1,7 @@
 mod common_types;
-mod management;
 mod registry;
-mod upgrade;
+
+registry_framework::registry_canister!(
+    "Template Registry Canister",
+    common_types::CanisterMetadata
+);